@@ -14,6 +14,7 @@ use storey::encoding::{Cover, DecodableWithImpl, EncodableWithImpl, Encoding};
 pub struct CwEncoding;
 
 impl Encoding for CwEncoding {
+    const FORMAT: &'static str = "msgpack";
     type DecodeError = StdError;
     type EncodeError = StdError;
 }
@@ -36,6 +37,59 @@ where
     }
 }
 
+/// An encoding that delegates to the JSON encoding provided by the [`cosmwasm_std`] crate
+/// ([`to_json_vec`] and [`from_json`]).
+///
+/// This is the format [*CosmWasm*] contracts conventionally use for state, so values stored
+/// with it can be read by off-chain tooling that expects JSON - or by contracts migrating from
+/// other storage libraries. It's less compact than [`CwEncoding`], which is why it's not the
+/// default.
+///
+/// # Example
+/// ```
+/// use cosmwasm_std::Storage as _;
+/// use cw_storey::{CwJsonEncoding, CwStorage};
+/// use storey::containers::Item;
+///
+/// let mut raw_storage = cosmwasm_std::testing::MockStorage::new();
+/// let mut storage = CwStorage(&mut raw_storage);
+///
+/// let item = Item::<Vec<u32>, CwJsonEncoding>::new(0);
+/// item.access(&mut storage).set(&vec![1, 2]).unwrap();
+///
+/// assert_eq!(storage.0.get(&[0]), Some(b"[1,2]".to_vec()));
+/// ```
+///
+/// [*CosmWasm*]: https://github.com/CosmWasm/cosmwasm
+/// [`cosmwasm_std`]: https://docs.rs/cosmwasm-std
+/// [`to_json_vec`]: cosmwasm_std::to_json_vec
+/// [`from_json`]: cosmwasm_std::from_json
+pub struct CwJsonEncoding;
+
+impl Encoding for CwJsonEncoding {
+    const FORMAT: &'static str = "json";
+    type DecodeError = StdError;
+    type EncodeError = StdError;
+}
+
+impl<T> EncodableWithImpl<CwJsonEncoding> for Cover<&T>
+where
+    T: serde::Serialize,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, StdError> {
+        cosmwasm_std::to_json_vec(self.0)
+    }
+}
+
+impl<T> DecodableWithImpl<CwJsonEncoding> for Cover<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, StdError> {
+        cosmwasm_std::from_json(data).map(Cover)
+    }
+}
+
 // TODO: remove this module once the following PR is released on crates.io:
 // https://github.com/CosmWasm/cosmwasm/pull/2118
 mod cosmwasm_std_new {
@@ -56,3 +110,52 @@ mod cosmwasm_std_new {
         rmp_serde::to_vec_named(data).map_err(|e| StdError::serialize_err(type_name::<T>(), e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use storey_encoding::test_util::{assert_decode_rejects, assert_encoding_roundtrip};
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Config {
+        owner: String,
+        limits: Vec<u64>,
+        paused: Option<bool>,
+    }
+
+    fn configs() -> Vec<Config> {
+        vec![
+            Config {
+                owner: "".to_string(),
+                limits: vec![],
+                paused: None,
+            },
+            Config {
+                owner: "alice".to_string(),
+                limits: vec![0, u64::MAX],
+                paused: Some(true),
+            },
+        ]
+    }
+
+    #[test]
+    fn msgpack_invariants() {
+        assert_encoding_roundtrip::<CwEncoding, _>([0u64, 1, u64::MAX]);
+        assert_encoding_roundtrip::<CwEncoding, _>(["".to_string(), "foo".to_string()]);
+        assert_encoding_roundtrip::<CwEncoding, _>(configs());
+
+        assert_decode_rejects::<CwEncoding, u64>(&[&[], &[0xc0], &[0xcf, 1], &[0xa1, b'1']]);
+        assert_decode_rejects::<CwEncoding, Config>(&[&[], &[0x80], &[0x93, 0x01]]);
+    }
+
+    #[test]
+    fn json_invariants() {
+        assert_encoding_roundtrip::<CwJsonEncoding, _>([0u64, 1, u64::MAX]);
+        assert_encoding_roundtrip::<CwJsonEncoding, _>(["".to_string(), "foo".to_string()]);
+        assert_encoding_roundtrip::<CwJsonEncoding, _>(configs());
+
+        assert_decode_rejects::<CwJsonEncoding, u64>(&[b"", b"-1", b"\"1\"", b"1.5"]);
+        assert_decode_rejects::<CwJsonEncoding, Config>(&[b"{}", b"[", b"null"]);
+    }
+}
@@ -3,7 +3,8 @@
 //! This crate provides
 //! - a [*CosmWasm*] storage backend for use with [`storey`] collections,
 //! - a [*MessagePack*] encoding integration to be used for serializing and deserializing
-//!   values, and
+//!   values, along with a JSON one for state that needs to be readable by JSON-based
+//!   tooling, and
 //! - a set of container re-exports that remove the need to manually specify the
 //!   encoding, instead relying on the default [*MessagePack*] encoding.
 //!
@@ -16,5 +17,5 @@ mod encoding;
 mod std_error;
 
 pub use backend::CwStorage;
-pub use encoding::CwEncoding;
+pub use encoding::{CwEncoding, CwJsonEncoding};
 pub use std_error::IntoStdError;
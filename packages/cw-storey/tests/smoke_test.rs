@@ -1,4 +1,4 @@
-use cw_storey::{containers::Item, CwStorage};
+use cw_storey::{containers::Item, CwJsonEncoding, CwStorage};
 
 use storey::containers::{IterableAccessor as _, Map};
 
@@ -58,3 +58,72 @@ fn iteration() {
     assert_eq!(iter.next().unwrap().unwrap().0, "foo");
     assert!(iter.next().is_none());
 }
+
+#[test]
+fn tuple_item() {
+    let mut raw_storage = cosmwasm_std::testing::MockStorage::new();
+    let mut storage = CwStorage(&mut raw_storage);
+
+    let item = Item::<(u32, String)>::new(0);
+
+    item.access(&mut storage)
+        .set(&(42, "foo".to_string()))
+        .unwrap();
+    assert_eq!(
+        item.access(&storage).get().unwrap(),
+        Some((42, "foo".to_string()))
+    );
+}
+
+#[test]
+fn array_item() {
+    let mut raw_storage = cosmwasm_std::testing::MockStorage::new();
+    let mut storage = CwStorage(&mut raw_storage);
+
+    let item = Item::<[u8; 4]>::new(0);
+
+    item.access(&mut storage).set(&[1, 2, 3, 4]).unwrap();
+    assert_eq!(item.access(&storage).get().unwrap(), Some([1, 2, 3, 4]));
+}
+
+#[test]
+fn vec_item() {
+    let mut raw_storage = cosmwasm_std::testing::MockStorage::new();
+    let mut storage = CwStorage(&mut raw_storage);
+
+    let item = Item::<Vec<u64>>::new(0);
+
+    item.access(&mut storage).set(&vec![1, 2, 3]).unwrap();
+    assert_eq!(item.access(&storage).get().unwrap(), Some(vec![1, 2, 3]));
+}
+
+#[test]
+fn json_item() {
+    use cosmwasm_std::Storage as _;
+
+    #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Config {
+        owner: String,
+        fee_bps: u16,
+    }
+
+    let mut raw_storage = cosmwasm_std::testing::MockStorage::new();
+    let mut storage = CwStorage(&mut raw_storage);
+
+    let item = storey::containers::Item::<Config, CwJsonEncoding>::new(0);
+    let config = Config {
+        owner: "alice".to_string(),
+        fee_bps: 30,
+    };
+
+    item.access(&mut storage).set(&config).unwrap();
+    assert_eq!(item.access(&storage).get().unwrap(), Some(config.clone()));
+
+    // the stored bytes are plain CosmWasm JSON
+    let raw = storage.0.get(&[0]).unwrap();
+    assert_eq!(raw, br#"{"owner":"alice","fee_bps":30}"#);
+    assert_eq!(cosmwasm_std::from_json::<Config>(&raw).unwrap(), config);
+
+    storage.0.set(&[0], b"not json");
+    assert!(item.access(&storage).get().is_err());
+}
@@ -1,4 +1,8 @@
-use std::{cell::UnsafeCell, collections::BTreeMap, ops::Bound};
+use std::{
+    cell::UnsafeCell,
+    collections::{BTreeMap, HashMap},
+    ops::Bound,
+};
 
 use storey_storage::{IterableStorage, RevIterableStorage, StorageBackend, StorageBackendMut};
 
@@ -13,17 +17,112 @@ use storey_storage::{IterableStorage, RevIterableStorage, StorageBackend, Storag
 pub struct TestStorage(UnsafeCell<BTreeMap<Vec<u8>, Vec<u8>>>);
 
 impl TestStorage {
+    /// Creates an empty storage.
     pub fn new() -> Self {
         Self(UnsafeCell::new(BTreeMap::new()))
     }
+
+    /// Removes all entries, metadata included.
+    ///
+    /// Handy for reusing one storage instance across test cases.
+    pub fn clear(&mut self) {
+        self.0.get_mut().clear();
+    }
+
+    /// Captures the current contents, metadata included.
+    ///
+    /// The snapshot is a deep copy, so later writes to the storage don't affect it. Pass it to
+    /// [`restore`](Self::restore) to roll the storage back, e.g. to try another migration path
+    /// from the same starting state.
+    pub fn snapshot(&self) -> Snapshot {
+        // Safety: see below
+        Snapshot(unsafe { &*self.0.get() }.clone())
+    }
+
+    /// Replaces the contents of the storage with those captured in `snapshot`.
+    ///
+    /// The snapshot isn't consumed, so the same state can be restored any number of times.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        *self.0.get_mut() = snapshot.0.clone();
+    }
 }
 
+/// The state of a [`TestStorage`] at some point in time, created by [`TestStorage::snapshot`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot(BTreeMap<Vec<u8>, Vec<u8>>);
+
 impl Default for TestStorage {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl TestStorage {
+    /// Serializes all entries (metadata included) into a flat byte format.
+    ///
+    /// Each entry is written as a length-prefixed key followed by a length-prefixed value.
+    /// Lengths are little-endian `u32`s. Entries are written in key order, so the output
+    /// is deterministic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key or value is 4 GiB or longer, since its length can't be written.
+    pub fn export(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+
+        // Safety: see below
+        for (key, value) in unsafe { &*self.0.get() } {
+            for bytes in [key, value] {
+                let len = u32::try_from(bytes.len()).expect("entry too large to export");
+                result.extend_from_slice(&len.to_le_bytes());
+                result.extend_from_slice(bytes);
+            }
+        }
+
+        result
+    }
+
+    /// Reconstructs a storage from the output of [`export`](Self::export).
+    pub fn import(mut data: &[u8]) -> Result<Self, ImportError> {
+        let mut map = BTreeMap::new();
+
+        while !data.is_empty() {
+            let key = take_chunk(&mut data)?;
+            let value = take_chunk(&mut data)?;
+            map.insert(key.to_vec(), value.to_vec());
+        }
+
+        Ok(Self(UnsafeCell::new(map)))
+    }
+}
+
+fn take_chunk<'a>(data: &mut &'a [u8]) -> Result<&'a [u8], ImportError> {
+    let len_bytes: [u8; 4] = data
+        .get(..4)
+        .ok_or(ImportError)?
+        .try_into()
+        .map_err(|_| ImportError)?;
+    let len = u32::from_le_bytes(len_bytes) as usize;
+
+    // `usize` may be no wider than the `u32` length on some targets
+    let end = len.checked_add(4).ok_or(ImportError)?;
+    let chunk = data.get(4..end).ok_or(ImportError)?;
+    *data = &data[end..];
+    Ok(chunk)
+}
+
+/// The error returned when [`TestStorage::import`] is given malformed data.
+#[derive(Debug, PartialEq)]
+pub struct ImportError;
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "malformed storage dump")
+    }
+}
+
+impl std::error::Error for ImportError {}
+
 // Safety: in each of the unsafe blocks in this file, we drop the reference to
 // the BTreeMap before the function returns, so we can guarantee that no two references
 // to it exist at the same time.
@@ -124,6 +223,48 @@ impl RevIterableStorage for TestStorage {
     }
 }
 
+/// A storage backend backed by a `HashMap`.
+///
+/// Iteration order is undefined for a `HashMap`, so this backend deliberately does not
+/// implement [`IterableStorage`]. Use it in tests that don't rely on ordered iteration -
+/// the compiler will then point out any code path that does.
+#[derive(Debug)]
+pub struct HashMapStorage(HashMap<Vec<u8>, Vec<u8>>);
+
+impl HashMapStorage {
+    /// Creates an empty storage.
+    pub fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    /// Removes all entries, metadata included.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl Default for HashMapStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StorageBackend for HashMapStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.get(key).cloned()
+    }
+}
+
+impl StorageBackendMut for HashMapStorage {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.0.insert(key.to_vec(), value.to_vec());
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.0.remove(key);
+    }
+}
+
 fn check_bounds(v: &[u8], start: Bound<&Vec<u8>>, end: Bound<&Vec<u8>>) -> bool {
     if let Bound::Included(start) = start {
         if v < start {
@@ -281,4 +422,184 @@ mod tests {
             Some(b"meta".to_vec())
         );
     }
+
+    #[test]
+    fn clear() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0], b"foo");
+        storage.set(&[1, 2], b"bar");
+        storey_storage::StorageMut::set_meta(&mut storage, &[0], b"meta");
+
+        storage.clear();
+        assert_eq!(storage.keys(Bound::Unbounded, Bound::Unbounded).count(), 0);
+
+        storage.set(&[0], b"baz");
+        assert_eq!(StorageBackend::get(&storage, &[0]), Some(b"baz".to_vec()));
+    }
+
+    #[test]
+    fn snapshot_restore() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0], b"foo");
+        storey_storage::StorageMut::set_meta(&mut storage, &[0], b"meta");
+
+        let snapshot = storage.snapshot();
+        let dump = storage.export();
+
+        storage.set(&[0], b"bar");
+        storage.set(&[1], b"baz");
+        storey_storage::StorageMut::remove_meta(&mut storage, &[0]);
+        assert_eq!(snapshot, TestStorage::import(&dump).unwrap().snapshot());
+
+        storage.restore(&snapshot);
+        assert_eq!(storage.export(), dump);
+
+        // the snapshot can be restored again after further writes
+        storage.clear();
+        storage.restore(&snapshot);
+        assert_eq!(storage.export(), dump);
+    }
+
+    #[test]
+    fn hash_map_storage() {
+        use storey_storage::{Storage as _, StorageMut as _};
+
+        let mut storage = HashMapStorage::new();
+
+        StorageBackendMut::set(&mut storage, &[0], b"foo");
+        StorageBackendMut::set(&mut storage, &[1], b"bar");
+        StorageBackendMut::remove(&mut storage, &[1]);
+        storage.set_meta(&[0], b"meta");
+
+        assert_eq!(StorageBackend::get(&storage, &[0]), Some(b"foo".to_vec()));
+        assert_eq!(StorageBackend::get(&storage, &[1]), None);
+        assert_eq!(storage.get_meta(&[0]), Some(b"meta".to_vec()));
+    }
+
+    #[test]
+    fn export_import() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0], b"foo");
+        storage.set(&[1, 2, 3], b"");
+        storage.set(&[], b"bar");
+        storey_storage::StorageMut::set_meta(&mut storage, &[0], b"meta");
+
+        let dump = storage.export();
+        let imported = TestStorage::import(&dump).unwrap();
+
+        assert_eq!(
+            imported
+                .pairs(Bound::Unbounded, Bound::Unbounded)
+                .collect::<Vec<_>>(),
+            storage
+                .pairs(Bound::Unbounded, Bound::Unbounded)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(StorageBackend::get(&imported, &[1, 2, 3]), Some(vec![]));
+        assert_eq!(imported.export(), dump);
+
+        assert!(TestStorage::import(&[]).is_ok());
+        assert_eq!(
+            TestStorage::import(&dump[..dump.len() - 1]).err(),
+            Some(ImportError)
+        );
+        assert_eq!(
+            TestStorage::import(&[0xff, 0xff, 0xff, 0xff]).err(),
+            Some(ImportError)
+        );
+
+        let error: Box<dyn std::error::Error> = TestStorage::import(&[0]).err().unwrap().into();
+        assert_eq!(error.to_string(), "malformed storage dump");
+    }
+
+    #[test]
+    fn prefix_scan() {
+        let mut storage = TestStorage::new();
+
+        storage.set(&[0, 255], b"foo");
+        storage.set(&[1], b"bar");
+        storage.set(&[1, 255], b"baz");
+        storage.set(&[1, 255, 255], b"qux");
+        storage.set(&[2], b"quux");
+        storage.set(&[255, 1], b"corge");
+
+        let keys = |prefix: &[u8]| {
+            storage
+                .prefix_scan(prefix)
+                .map(|(k, _)| k)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(keys(&[1]), vec![vec![1], vec![1, 255], vec![1, 255, 255]]);
+        assert_eq!(keys(&[1, 255]), vec![vec![1, 255], vec![1, 255, 255]]);
+        assert_eq!(keys(&[0, 255]), vec![vec![0, 255]]);
+        assert_eq!(keys(&[255]), vec![vec![255, 1]]);
+    }
+
+    #[test]
+    fn update_raw() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0], b"foo");
+
+        storey_storage::StorageMut::update_raw(&mut storage, &[0], |value| {
+            assert_eq!(value, Some(b"foo".to_vec()));
+            Some(b"bar".to_vec())
+        });
+        assert_eq!(StorageBackend::get(&storage, &[0]), Some(b"bar".to_vec()));
+
+        storey_storage::StorageMut::update_raw(&mut storage, &[1], |value| {
+            assert_eq!(value, None);
+            Some(b"baz".to_vec())
+        });
+        assert_eq!(StorageBackend::get(&storage, &[1]), Some(b"baz".to_vec()));
+
+        storey_storage::StorageMut::update_raw(&mut storage, &[0], |_| None);
+        assert_eq!(StorageBackend::get(&storage, &[0]), None);
+
+        // removing a missing key is a no-op
+        storey_storage::StorageMut::update_raw(&mut storage, &[2], |_| None);
+        assert_eq!(
+            storage
+                .keys(Bound::Unbounded, Bound::Unbounded)
+                .collect::<Vec<_>>(),
+            vec![vec![1]]
+        );
+    }
+
+    #[test]
+    fn move_namespace() {
+        let mut storage = TestStorage::new();
+
+        storage.set(&[0, 1], b"foo");
+        storage.set(&[1], b"bar");
+        storage.set(&[1, 2], b"baz");
+        storage.set(&[1, 2, 3], b"qux");
+        storage.set(&[2, 2], b"old");
+
+        storey_storage::StorageMut::move_namespace(&mut storage, &[1], &[2]);
+        assert_eq!(
+            storage
+                .pairs(Bound::Unbounded, Bound::Unbounded)
+                .collect::<Vec<_>>(),
+            vec![
+                (vec![0, 1], b"foo".to_vec()),
+                (vec![2], b"bar".to_vec()),
+                (vec![2, 2], b"baz".to_vec()),
+                (vec![2, 2, 3], b"qux".to_vec()),
+            ]
+        );
+
+        // overlapping prefixes: move everything one level deeper
+        storey_storage::StorageMut::move_namespace(&mut storage, &[2], &[2, 2]);
+        assert_eq!(
+            storage
+                .keys(Bound::Unbounded, Bound::Unbounded)
+                .collect::<Vec<_>>(),
+            vec![vec![0, 1], vec![2, 2], vec![2, 2, 2], vec![2, 2, 2, 3]]
+        );
+        assert_eq!(
+            StorageBackend::get(&storage, &[2, 2, 2]),
+            Some(b"baz".to_vec())
+        );
+    }
 }
@@ -18,7 +18,10 @@ impl std::fmt::Display for MockError {
     }
 }
 
+impl std::error::Error for MockError {}
+
 impl Encoding for TestEncoding {
+    const FORMAT: &'static str = "test";
     type DecodeError = MockError;
     type EncodeError = MockError;
 }
@@ -33,6 +36,10 @@ where
     fn encode_impl(self) -> Result<Vec<u8>, <TestEncoding as Encoding>::EncodeError> {
         self.0.my_encode()
     }
+
+    fn encoded_len_impl(self) -> Option<usize> {
+        self.0.my_encoded_len()
+    }
 }
 
 impl<T> DecodableWithImpl<TestEncoding> for Cover<T>
@@ -50,31 +57,229 @@ where
 trait MyTestEncoding: Sized {
     fn my_encode(&self) -> Result<Vec<u8>, MockError>;
     fn my_decode(data: &[u8]) -> Result<Self, MockError>;
+
+    fn my_encoded_len(&self) -> Option<usize> {
+        None
+    }
 }
 
+// Integers are encoded as little-endian. This is deliberately different from how
+// `storey` encodes integer map keys (big-endian), so that tests catch any place where
+// the two get mixed up.
+
 impl MyTestEncoding for u64 {
     fn my_encode(&self) -> Result<Vec<u8>, MockError> {
         Ok(self.to_le_bytes().to_vec())
     }
 
     fn my_decode(data: &[u8]) -> Result<Self, MockError> {
-        let mut bytes = [0u8; 8];
-        bytes.copy_from_slice(data);
+        let bytes: [u8; 8] = data.try_into().map_err(|_| MockError)?;
         Ok(u64::from_le_bytes(bytes))
     }
+
+    fn my_encoded_len(&self) -> Option<usize> {
+        Some(8)
+    }
+}
+
+impl MyTestEncoding for u32 {
+    fn my_encode(&self) -> Result<Vec<u8>, MockError> {
+        Ok(self.to_le_bytes().to_vec())
+    }
+
+    fn my_decode(data: &[u8]) -> Result<Self, MockError> {
+        let bytes: [u8; 4] = data.try_into().map_err(|_| MockError)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn my_encoded_len(&self) -> Option<usize> {
+        Some(4)
+    }
+}
+
+impl MyTestEncoding for u8 {
+    fn my_encode(&self) -> Result<Vec<u8>, MockError> {
+        Ok(vec![*self])
+    }
+
+    fn my_decode(data: &[u8]) -> Result<Self, MockError> {
+        match data {
+            [byte] => Ok(*byte),
+            _ => Err(MockError),
+        }
+    }
+
+    fn my_encoded_len(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+impl MyTestEncoding for String {
+    fn my_encode(&self) -> Result<Vec<u8>, MockError> {
+        Ok(self.as_bytes().to_vec())
+    }
+
+    fn my_decode(data: &[u8]) -> Result<Self, MockError> {
+        String::from_utf8(data.to_vec()).map_err(|_| MockError)
+    }
+}
+
+// Composite types are encoded as a sequence of elements, each prefixed with its
+// length as a little-endian `u32`.
+
+fn encode_seq<'a, T, I>(items: I) -> Result<Vec<u8>, MockError>
+where
+    T: MyTestEncoding + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut result = Vec::new();
+    for item in items {
+        let bytes = item.my_encode()?;
+        result.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        result.extend_from_slice(&bytes);
+    }
+    Ok(result)
+}
+
+fn decode_seq_elem<'a>(data: &mut &'a [u8]) -> Result<&'a [u8], MockError> {
+    if data.len() < 4 {
+        return Err(MockError);
+    }
+    let (len, rest) = data.split_at(4);
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    if rest.len() < len {
+        return Err(MockError);
+    }
+    let (elem, rest) = rest.split_at(len);
+    *data = rest;
+    Ok(elem)
+}
+
+impl<A, B> MyTestEncoding for (A, B)
+where
+    A: MyTestEncoding,
+    B: MyTestEncoding,
+{
+    fn my_encode(&self) -> Result<Vec<u8>, MockError> {
+        let mut result = encode_seq([&self.0])?;
+        result.extend(encode_seq([&self.1])?);
+        Ok(result)
+    }
+
+    fn my_decode(mut data: &[u8]) -> Result<Self, MockError> {
+        let a = A::my_decode(decode_seq_elem(&mut data)?)?;
+        let b = B::my_decode(decode_seq_elem(&mut data)?)?;
+        if !data.is_empty() {
+            return Err(MockError);
+        }
+        Ok((a, b))
+    }
+}
+
+impl<T> MyTestEncoding for Vec<T>
+where
+    T: MyTestEncoding,
+{
+    fn my_encode(&self) -> Result<Vec<u8>, MockError> {
+        encode_seq(self)
+    }
+
+    fn my_decode(mut data: &[u8]) -> Result<Self, MockError> {
+        let mut result = Vec::new();
+        while !data.is_empty() {
+            result.push(T::my_decode(decode_seq_elem(&mut data)?)?);
+        }
+        Ok(result)
+    }
+}
+
+impl<T, const N: usize> MyTestEncoding for [T; N]
+where
+    T: MyTestEncoding,
+{
+    fn my_encode(&self) -> Result<Vec<u8>, MockError> {
+        encode_seq(self)
+    }
+
+    fn my_decode(data: &[u8]) -> Result<Self, MockError> {
+        Vec::<T>::my_decode(data)?.try_into().map_err(|_| MockError)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use storey_encoding::{DecodableWith as _, EncodableWith as _};
 
+    use super::MockError;
+
     #[test]
     fn encoding() {
         assert_eq!(12u64.encode(), Ok(12u64.to_le_bytes().to_vec()));
     }
 
+    #[test]
+    fn integer_layout() {
+        assert_eq!(0x12345678u32.encode(), Ok(vec![0x78, 0x56, 0x34, 0x12]));
+        assert_eq!(
+            0x0102030405060708u64.encode(),
+            Ok(vec![0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01])
+        );
+    }
+
     #[test]
     fn decoding() {
         assert_eq!(<u64>::decode(&12u64.to_le_bytes()), Ok(12));
     }
+
+    #[test]
+    fn decoding_invalid_length() {
+        assert_eq!(<u64>::decode(&[1, 2, 3]), Err(MockError));
+    }
+
+    #[test]
+    fn encoded_len() {
+        assert_eq!(12u64.encoded_len(), Some(8));
+        assert_eq!(12u8.encoded_len(), Some(1));
+        assert_eq!("foo".to_string().encoded_len(), None);
+    }
+
+    #[test]
+    fn tuple_roundtrip() {
+        let value = (42u32, "foo".to_string());
+        let encoded = value.encode().unwrap();
+        assert_eq!(<(u32, String)>::decode(&encoded), Ok(value));
+        assert_eq!(<(u32, String)>::decode(&encoded[..5]), Err(MockError));
+    }
+
+    #[test]
+    fn array_roundtrip() {
+        let value = [1u8, 2, 3, 4];
+        let encoded = value.encode().unwrap();
+        assert_eq!(<[u8; 4]>::decode(&encoded), Ok(value));
+        assert_eq!(<[u8; 3]>::decode(&encoded), Err(MockError));
+    }
+
+    #[test]
+    fn vec_roundtrip() {
+        let value = vec![1u64, 2, 3];
+        let encoded = value.encode().unwrap();
+        assert_eq!(<Vec<u64>>::decode(&encoded), Ok(value));
+        assert_eq!(<Vec<u64>>::decode(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn invariants() {
+        use storey_encoding::test_util::{assert_decode_rejects, assert_encoding_roundtrip};
+
+        use super::TestEncoding;
+
+        assert_encoding_roundtrip::<TestEncoding, _>([0u64, 1, u64::MAX]);
+        assert_encoding_roundtrip::<TestEncoding, _>([0u32, u32::MAX]);
+        assert_encoding_roundtrip::<TestEncoding, _>(["".to_string(), "foo".to_string()]);
+        assert_encoding_roundtrip::<TestEncoding, _>([(42u32, "foo".to_string())]);
+        assert_encoding_roundtrip::<TestEncoding, _>([vec![], vec![1u64, 2, 3]]);
+
+        assert_decode_rejects::<TestEncoding, u64>(&[&[], &[1, 2, 3], &[0; 9]]);
+        assert_decode_rejects::<TestEncoding, (u32, String)>(&[&[1, 2]]);
+    }
 }
@@ -1,4 +1,17 @@
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
 pub trait Encoding {
+    /// A short identifier of the format values are encoded in, e.g. `"json"`, `"msgpack"` or
+    /// `"borsh"`.
+    ///
+    /// This lets generic tooling (e.g. storage explorers) pick the right decoder when
+    /// rendering raw values. Use lowercase, well-known names where they exist.
+    ///
+    /// Defaults to `"unknown"`, so existing encodings keep compiling. Tooling should treat
+    /// that value as opaque bytes.
+    const FORMAT: &'static str = "unknown";
+
     /// The error type returned when encoding fails.
     type EncodeError: std::fmt::Display;
 
@@ -8,10 +21,27 @@ pub trait Encoding {
 
 pub trait EncodableWith<E: Encoding>: sealed::SealedE<E> {
     fn encode(&self) -> Result<Vec<u8>, E::EncodeError>;
+
+    /// Returns the length of the encoded value, if it can be computed without
+    /// actually encoding it.
+    ///
+    /// `None` means the length is unknown - callers should fall back to [`encode`](Self::encode).
+    fn encoded_len(&self) -> Option<usize>;
 }
 
 pub trait EncodableWithImpl<E: Encoding> {
     fn encode_impl(self) -> Result<Vec<u8>, E::EncodeError>;
+
+    /// Returns the length of the encoded value, if it's cheap to compute.
+    ///
+    /// Encodings that can't tell the size of a value without encoding it (which is most
+    /// of them) should keep the default, which returns `None`.
+    fn encoded_len_impl(self) -> Option<usize>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 impl<E: Encoding, T> EncodableWith<E> for T
@@ -21,6 +51,10 @@ where
     fn encode(&self) -> Result<Vec<u8>, <E as Encoding>::EncodeError> {
         Cover(self).encode_impl()
     }
+
+    fn encoded_len(&self) -> Option<usize> {
+        Cover(self).encoded_len_impl()
+    }
 }
 
 pub trait DecodableWith<E: Encoding>: Sized + sealed::SealedD<E> {
@@ -0,0 +1,110 @@
+//! Helpers for testing [`Encoding`] implementations.
+//!
+//! These check the invariants every encoding is expected to uphold, so that each encoding's
+//! test suite can run the same checks instead of reinventing them. Enable the `test-util`
+//! feature (typically only in `dev-dependencies`) to use them.
+//!
+//! # Example
+//! ```
+//! # use storey_encoding::{Cover, DecodableWithImpl, EncodableWithImpl, Encoding};
+//! use storey_encoding::test_util::{assert_decode_rejects, assert_encoding_roundtrip};
+//!
+//! struct BeEncoding;
+//!
+//! impl Encoding for BeEncoding {
+//!     const FORMAT: &'static str = "be";
+//!     type EncodeError = String;
+//!     type DecodeError = String;
+//! }
+//!
+//! impl EncodableWithImpl<BeEncoding> for Cover<&u32> {
+//!     fn encode_impl(self) -> Result<Vec<u8>, String> {
+//!         Ok(self.0.to_be_bytes().to_vec())
+//!     }
+//! }
+//!
+//! impl DecodableWithImpl<BeEncoding> for Cover<u32> {
+//!     fn decode_impl(data: &[u8]) -> Result<Self, String> {
+//!         let bytes = data.try_into().map_err(|_| "expected 4 bytes".to_string())?;
+//!         Ok(Cover(u32::from_be_bytes(bytes)))
+//!     }
+//! }
+//!
+//! assert_encoding_roundtrip::<BeEncoding, _>([0, 1, u32::MAX]);
+//! assert_decode_rejects::<BeEncoding, u32>(&[&[], &[1, 2, 3], &[1, 2, 3, 4, 5]]);
+//! ```
+
+use std::fmt::Debug;
+
+use crate::{DecodableWith, EncodableWith, Encoding};
+
+/// Asserts that every value survives an encode/decode round trip with the encoding `E`.
+///
+/// For each value, this also checks that:
+/// - [`encoded_len`](EncodableWith::encoded_len), if it returns anything, matches the length
+///   of the actual encoding,
+/// - decoding any truncated encoding of the value returns (an `Ok` or an `Err`) rather than
+///   panicking.
+///
+/// # Panics
+///
+/// Panics with a descriptive message if any of the checks fail.
+pub fn assert_encoding_roundtrip<E, T>(values: impl IntoIterator<Item = T>)
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E> + PartialEq + Debug,
+{
+    for value in values {
+        let encoded = match value.encode() {
+            Ok(encoded) => encoded,
+            Err(e) => panic!("failed to encode {value:?} with {}: {e}", E::FORMAT),
+        };
+
+        if let Some(len) = value.encoded_len() {
+            assert_eq!(
+                len,
+                encoded.len(),
+                "encoded_len of {value:?} doesn't match its {} encoding",
+                E::FORMAT
+            );
+        }
+
+        match T::decode(&encoded) {
+            Ok(decoded) => assert_eq!(
+                decoded,
+                value,
+                "{value:?} changed after a {} round trip",
+                E::FORMAT
+            ),
+            Err(e) => panic!(
+                "failed to decode {value:?} from its own {} encoding {encoded:?}: {e}",
+                E::FORMAT
+            ),
+        }
+
+        for len in 0..encoded.len() {
+            // Either result is fine, as long as decoding doesn't panic.
+            let _ = T::decode(&encoded[..len]);
+        }
+    }
+}
+
+/// Asserts that decoding each of `inputs` as `T` with the encoding `E` fails with an error.
+///
+/// # Panics
+///
+/// Panics if any of the inputs decodes successfully (or if decoding itself panics).
+pub fn assert_decode_rejects<E, T>(inputs: &[&[u8]])
+where
+    E: Encoding,
+    T: DecodableWith<E> + Debug,
+{
+    for input in inputs {
+        if let Ok(value) = T::decode(input) {
+            panic!(
+                "malformed input {input:?} decoded as {value:?} with {}",
+                E::FORMAT
+            );
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::storage::{Storage, StorageMut};
+use super::storage::{FlushError, Storage, StorageMut};
 
 /// A trait for immutably accessing a storage backend.
 ///
@@ -13,6 +13,13 @@ pub trait StorageBackend {
     fn has(&self, key: &[u8]) -> bool {
         self.get(key).is_some()
     }
+
+    /// Get the length in bytes of the value associated with the given key, if it exists.
+    ///
+    /// The default implementation fetches the value.
+    fn value_len(&self, key: &[u8]) -> Option<usize> {
+        self.get(key).map(|value| value.len())
+    }
 }
 
 /// A trait for mutably accessing a storage backend.
@@ -26,6 +33,13 @@ pub trait StorageBackendMut {
 
     /// Remove the value associated with the given key.
     fn remove(&mut self, key: &[u8]);
+
+    /// Flush buffered writes, if the backend buffers them.
+    ///
+    /// The default implementation does nothing.
+    fn flush(&mut self) -> Result<(), FlushError> {
+        Ok(())
+    }
 }
 
 impl<B> Storage for B
@@ -40,6 +54,10 @@ where
         StorageBackend::has(self, key)
     }
 
+    fn value_len(&self, key: &[u8]) -> Option<usize> {
+        StorageBackend::value_len(self, key)
+    }
+
     fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
         StorageBackend::get(self, &meta_key(key))
     }
@@ -68,6 +86,10 @@ where
     fn remove_meta(&mut self, key: &[u8]) {
         StorageBackendMut::remove(self, &meta_key(key))
     }
+
+    fn flush(&mut self) -> Result<(), FlushError> {
+        StorageBackendMut::flush(self)
+    }
 }
 
 fn meta_key(key: &[u8]) -> Vec<u8> {
@@ -2,4 +2,6 @@ mod backend;
 mod storage;
 
 pub use backend::{StorageBackend, StorageBackendMut};
-pub use storage::{IterableStorage, RevIterableStorage, Storage, StorageMut};
+pub use storage::{
+    prefix_successor, FlushError, IterableStorage, RevIterableStorage, Storage, StorageMut,
+};
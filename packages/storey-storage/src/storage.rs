@@ -10,6 +10,15 @@ pub trait Storage {
         self.get(key).is_some()
     }
 
+    /// Get the length of the value of the key in bytes, or `None` if the key doesn't exist.
+    ///
+    /// The default implementation fetches the value. Storage that can tell the length without
+    /// reading the whole value (e.g. because it keeps lengths in an index) should override
+    /// this. Wrappers should forward this to the storage they wrap.
+    fn value_len(&self, key: &[u8]) -> Option<usize> {
+        self.get(key).map(|value| value.len())
+    }
+
     /// Get the value of the key in the metadata namespace.
     fn get_meta(&self, _key: &[u8]) -> Option<Vec<u8>>;
 
@@ -32,6 +41,146 @@ pub trait StorageMut {
 
     /// Remove the key in the metadata namespace.
     fn remove_meta(&mut self, _key: &[u8]);
+
+    /// Remove the key, returning whether it existed.
+    ///
+    /// The default implementation checks for the key before removing it, which costs an extra
+    /// read. Prefer [`remove`](Self::remove) when the result isn't needed.
+    fn remove_existing(&mut self, key: &[u8]) -> bool
+    where
+        Self: Storage,
+    {
+        let existed = self.has(key);
+        if existed {
+            self.remove(key);
+        }
+        existed
+    }
+
+    /// Set the value of the key, but only if its current value is `expected`.
+    ///
+    /// An `expected` value of `None` means the key must not exist. Returns whether the write
+    /// happened.
+    ///
+    /// The default implementation reads the current value and then conditionally writes.
+    fn compare_and_set(&mut self, key: &[u8], expected: Option<&[u8]>, new: &[u8]) -> bool
+    where
+        Self: Storage,
+    {
+        if self.get(key).as_deref() == expected {
+            self.set(key, new);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Replace the value of the key with the result of `f`, which is given the current value.
+    ///
+    /// If `f` returns `None`, the key is removed.
+    ///
+    /// The default implementation reads the current value and then writes or removes it.
+    /// Backends that can perform the read-modify-write atomically should override this.
+    fn update_raw<F>(&mut self, key: &[u8], f: F)
+    where
+        Self: Storage,
+        F: FnOnce(Option<Vec<u8>>) -> Option<Vec<u8>>,
+    {
+        match f(self.get(key)) {
+            Some(value) => self.set(key, &value),
+            None => self.remove(key),
+        }
+    }
+
+    /// Remove every key visible through [`IterableStorage`].
+    ///
+    /// On a backend this wipes everything, metadata included. On a branch of a storage, only
+    /// the entries under the branch's prefix are removed - metadata kept outside of that
+    /// range is left alone.
+    fn clear_all(&mut self)
+    where
+        Self: IterableStorage,
+    {
+        let keys: Vec<_> = self.keys(Bound::Unbounded, Bound::Unbounded).collect();
+
+        for key in keys {
+            self.remove(&key);
+        }
+    }
+
+    /// Move every entry with a key starting with `from` to the same key under `to`.
+    ///
+    /// The part of each key following `from` is preserved. Entries are collected before
+    /// anything is written, and the originals are removed before the copies are written, so
+    /// the two prefixes may overlap (e.g. when moving entries one level deeper). Entries
+    /// already present under `to` are overwritten if a moved entry lands on the same key,
+    /// and left alone otherwise.
+    ///
+    /// # Metadata
+    ///
+    /// Only entries visible through [`IterableStorage`] are moved. Metadata (see
+    /// [`set_meta`](Self::set_meta)) can't be iterated, so whatever containers keep there -
+    /// e.g. the length and last ID of a column - stays under `from`. Moving a container this
+    /// way leaves it with its entries but without its bookkeeping. Callers have to move the
+    /// metadata keys they know about themselves, with [`Storage::get_meta`] and
+    /// [`set_meta`](Self::set_meta).
+    fn move_namespace(&mut self, from: &[u8], to: &[u8])
+    where
+        Self: IterableStorage,
+    {
+        if from == to {
+            return;
+        }
+
+        let entries: Vec<_> = self.prefix_scan(from).collect();
+
+        for (key, _) in &entries {
+            self.remove(key);
+        }
+
+        for (key, value) in entries {
+            let new_key = [to, &key[from.len()..]].concat();
+            self.set(&new_key, &value);
+        }
+    }
+
+    /// Flush any writes the storage buffers internally, making them durable.
+    ///
+    /// Storage that doesn't buffer writes can rely on the default implementation, which
+    /// does nothing. Wrappers should forward this to the storage they wrap.
+    fn flush(&mut self) -> Result<(), FlushError> {
+        Ok(())
+    }
+}
+
+/// An error reported by a storage that failed to flush buffered writes.
+///
+/// See [`StorageMut::flush`].
+#[derive(Debug)]
+pub struct FlushError(Box<dyn std::error::Error + Send + Sync>);
+
+impl FlushError {
+    /// Creates a new `FlushError` wrapping the given error.
+    pub fn new(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self(error.into())
+    }
+
+    /// Returns the wrapped error.
+    pub fn into_inner(self) -> Box<dyn std::error::Error + Send + Sync> {
+        self.0
+    }
+}
+
+impl std::fmt::Display for FlushError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to flush storage: {}", self.0)
+    }
+}
+
+impl std::error::Error for FlushError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.0)
+    }
 }
 
 /// Iteration interface for binary key-value storage.
@@ -79,6 +228,47 @@ pub trait IterableStorage {
     /// bounded at all, and if so, whether it should be inclusive or exclusive. See the
     /// [`Bound`] documentation for more details.
     fn pairs<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::PairsIterator<'a>;
+
+    /// Get an iterator over key-value pairs whose keys start with `prefix`.
+    ///
+    /// Keys are returned in full, prefix included. The exclusive upper bound of the range is
+    /// computed with [`prefix_successor`], so prefixes ending in `0xFF` bytes are handled
+    /// correctly.
+    fn prefix_scan<'a>(&'a self, prefix: &[u8]) -> Self::PairsIterator<'a> {
+        let end = prefix_successor(prefix);
+
+        self.pairs(
+            Bound::Included(prefix),
+            end.as_deref().map_or(Bound::Unbounded, Bound::Excluded),
+        )
+    }
+
+    /// Count the entries with keys in the given range.
+    ///
+    /// The default implementation iterates over the keys and counts them. Backends that can
+    /// count entries without walking them should override it.
+    fn count_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> usize {
+        self.keys(start, end).count()
+    }
+}
+
+/// Returns the smallest key that's greater than every key starting with `prefix`.
+///
+/// This is the exclusive upper bound for iterating over everything under a prefix. The last
+/// byte is incremented, with `0xFF` bytes carrying over to the previous one. `None` is
+/// returned if there's no such key, i.e. if the prefix is empty or made up of only `0xFF`
+/// bytes - in that case, the range is unbounded.
+pub fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut result = prefix.to_vec();
+
+    while let Some(last) = result.pop() {
+        if last < u8::MAX {
+            result.push(last + 1);
+            return Some(result);
+        }
+    }
+
+    None
 }
 
 impl<T: IterableStorage> IterableStorage for &T {
@@ -97,6 +287,10 @@ impl<T: IterableStorage> IterableStorage for &T {
     fn pairs<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::PairsIterator<'a> {
         (**self).pairs(start, end)
     }
+
+    fn count_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> usize {
+        (**self).count_range(start, end)
+    }
 }
 
 impl<T: IterableStorage> IterableStorage for &mut T {
@@ -115,6 +309,10 @@ impl<T: IterableStorage> IterableStorage for &mut T {
     fn pairs<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::PairsIterator<'a> {
         (**self).pairs(start, end)
     }
+
+    fn count_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> usize {
+        (**self).count_range(start, end)
+    }
 }
 
 /// Iteration interface for binary key-value storage in reverse order.
@@ -171,3 +369,17 @@ pub trait RevIterableStorage {
         end: Bound<&[u8]>,
     ) -> Self::RevPairsIterator<'a>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_successor_carry() {
+        assert_eq!(prefix_successor(&[1, 2]), Some(vec![1, 3]));
+        assert_eq!(prefix_successor(&[1, 255]), Some(vec![2]));
+        assert_eq!(prefix_successor(&[1, 255, 255]), Some(vec![2]));
+        assert_eq!(prefix_successor(&[255, 255]), None);
+        assert_eq!(prefix_successor(&[]), None);
+    }
+}
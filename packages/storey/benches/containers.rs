@@ -0,0 +1,70 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use mocks::backend::TestStorage;
+use mocks::encoding::TestEncoding;
+use storey::containers::{Item, IterableAccessor as _, Map};
+
+const SIZES: [u32; 3] = [10, 100, 1000];
+
+fn item(c: &mut Criterion) {
+    let item = Item::<u64, TestEncoding>::new(0);
+
+    c.bench_function("item_set", |b| {
+        let mut storage = TestStorage::new();
+        b.iter(|| item.access(&mut storage).set(black_box(&1337)).unwrap())
+    });
+
+    c.bench_function("item_get", |b| {
+        let mut storage = TestStorage::new();
+        item.access(&mut storage).set(&1337).unwrap();
+        b.iter(|| item.access(&storage).get().unwrap())
+    });
+}
+
+fn populated_map(n: u32) -> (Map<String, Item<u64, TestEncoding>>, TestStorage) {
+    let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    let mut storage = TestStorage::new();
+
+    let mut access = map.access(&mut storage);
+    for i in 0..n {
+        access
+            .entry_mut(&format!("key{i}"))
+            .set(&(i as u64))
+            .unwrap();
+    }
+
+    (map, storage)
+}
+
+fn map(c: &mut Criterion) {
+    let mut group = c.benchmark_group("map_get");
+    for n in SIZES {
+        let (map, storage) = populated_map(n);
+        let key = format!("key{}", n / 2);
+
+        group.bench_with_input(BenchmarkId::from_parameter(n), &key, |b, key| {
+            b.iter(|| map.access(&storage).entry(black_box(key)).get().unwrap())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("map_iter");
+    for n in SIZES {
+        let (map, storage) = populated_map(n);
+
+        group.bench_function(BenchmarkId::from_parameter(n), |b| {
+            b.iter(|| {
+                map.access(&storage)
+                    .pairs()
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, item, map);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+#![no_main]
+
+// Run with `cargo fuzz run key_framing` from `packages/storey`.
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use mocks::backend::TestStorage;
+use mocks::encoding::TestEncoding;
+use storey::containers::map::FramingError;
+use storey::containers::{Item, IterableAccessor as _, Map, Storable};
+use storey::storage::{IterableStorage as _, StorageBranch};
+
+// A composite key, framed as a length-prefixed string, a fixed-size integer, and a trailing
+// byte string.
+type Nested = Map<String, Map<u32, Map<Vec<u8>, Item<u64, TestEncoding>>>>;
+
+const NESTED: Nested = Map::new(0);
+
+#[derive(Debug, Arbitrary)]
+enum Input {
+    /// Typed keys must be stored and read back exactly.
+    Roundtrip(Vec<(String, u32, Vec<u8>)>),
+    /// Arbitrary raw keys must decode cleanly or fail with an error, never panic. Keys that
+    /// decode must be re-encoded to the same bytes.
+    Decode(Vec<u8>),
+}
+
+fuzz_target!(|input: Input| match input {
+    Input::Roundtrip(mut keys) => {
+        let mut storage = TestStorage::new();
+        let mut access = NESTED.access(&mut storage);
+
+        for (a, b, c) in &keys {
+            match access.try_entry_mut(a) {
+                Ok(mut entry) => entry.entry_mut(b).entry_mut(c).set(&1).unwrap(),
+                // Longer length-prefixed keys can't be framed (see the `Map` docs).
+                Err(FramingError::Unrepresentable) => assert!(a.len() > 255),
+                Err(e) => panic!("unexpected framing error: {e}"),
+            }
+        }
+
+        // Keys that couldn't be written must read as absent, without touching other entries.
+        keys.retain(|(a, b, c)| {
+            let fits = a.len() <= 255;
+            if !fits {
+                assert_eq!(access.entry(a).entry(b).entry(c).get().unwrap(), None);
+                assert!(!access.contains_prefix(a));
+            }
+            fits
+        });
+
+        let mut expected = keys
+            .into_iter()
+            .map(|(a, b, c)| (a, (b, (c, ()))))
+            .collect::<Vec<_>>();
+        expected.sort_by_key(|(a, rest)| (Nested::encode_key(a.as_str()).unwrap(), rest.clone()));
+        expected.dedup();
+
+        let mut found = access
+            .keys()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("typed keys must decode");
+        found.sort_by_key(|(a, rest)| (Nested::encode_key(a.as_str()).unwrap(), rest.clone()));
+
+        assert_eq!(found, expected);
+    }
+    Input::Decode(raw) => {
+        if let Ok((a, (b, (c, ())))) = Nested::decode_key(&raw) {
+            let mut storage = TestStorage::new();
+            NESTED
+                .access(&mut storage)
+                .entry_mut(&a)
+                .entry_mut(&b)
+                .entry_mut(&c)
+                .set(&1)
+                .unwrap();
+
+            let stored = StorageBranch::new(&storage, vec![0])
+                .keys(std::ops::Bound::Unbounded, std::ops::Bound::Unbounded)
+                .collect::<Vec<_>>();
+            assert_eq!(stored, vec![raw]);
+        }
+    }
+});
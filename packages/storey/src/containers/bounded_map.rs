@@ -0,0 +1,551 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use std::ops::Bound;
+
+use crate::encoding::{DecodableWith, EncodableWith, Encoding};
+use crate::storage::{IterableStorage, Storage, StorageBranch, StorageMut};
+
+use super::column::LenError;
+use super::map::key::Key;
+use super::map::{KeyEncodingT, MapKeyDecodeError, OwnedKey};
+use super::{
+    BoundFor, BoundedIterableAccessor, CountedMap, CountedMapAccess, CountedMapError,
+    ItemKeyDecodeError, IterableAccessor, NonTerminal, Storable, Terminal,
+};
+
+// the entry count is kept by the inner counted map, under `[0]`
+const META_NEXT_SEQ: &[u8] = &[1];
+const META_OLDEST_SEQ: &[u8] = &[2];
+const META_SEQ_BY_KEY: u8 = 3;
+const META_KEY_BY_SEQ: u8 = 4;
+
+/// A map that holds at most `CAPACITY` entries, evicting the least recently used one when
+/// a new entry doesn't fit.
+///
+/// Inserting an entry (new or not) or [touching](BoundedMapAccess::touch) it marks it as the
+/// most recently used. Plain reads don't - they don't need mutable access.
+///
+/// The entries and their count are stored like those of a [`CountedMap`], so iterating yields
+/// them in key order, not in order of use, and the same caveat about writing to the namespace
+/// directly applies. The usage order is tracked in the metadata namespace: each use assigns the
+/// entry a new sequence number, and eviction walks the sequence numbers up from the oldest one
+/// still in use. This keeps every operation a constant number of reads and writes (amortized),
+/// without iterating.
+///
+/// Since the capacity is part of the type, a bounded map can be nested in a [`Map`], e.g. to
+/// keep a limited history per user.
+///
+/// [`Map`]: super::Map
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::BoundedMap;
+///
+/// let mut storage = TestStorage::new();
+/// let recent = BoundedMap::<String, u64, TestEncoding, 2>::new(0);
+/// let mut access = recent.access(&mut storage);
+///
+/// assert_eq!(access.insert("alice", &1).unwrap(), None);
+/// assert_eq!(access.insert("bob", &2).unwrap(), None);
+/// assert!(access.touch("alice").unwrap());
+///
+/// // bob is the least recently used entry now
+/// assert_eq!(access.insert("carol", &3).unwrap(), Some(("bob".to_string(), 2)));
+/// assert_eq!(access.len().unwrap(), 2);
+/// assert_eq!(access.get("bob").unwrap(), None);
+/// ```
+pub struct BoundedMap<K, T, E, const CAPACITY: u32> {
+    prefix: u8,
+    phantom: PhantomData<(K, T, E)>,
+}
+
+impl<K, T, E, const CAPACITY: u32> BoundedMap<K, T, E, CAPACITY>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    const NONZERO_CAPACITY: () =
+        assert!(CAPACITY > 0, "a bounded map needs a capacity of at least 1");
+
+    /// Creates a new bounded map with the given prefix.
+    ///
+    /// It is the responsibility of the caller to ensure that the prefix is unique and does not
+    /// conflict with other keys in the storage.
+    pub const fn new(prefix: u8) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::NONZERO_CAPACITY;
+
+        Self {
+            prefix,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Acquires an accessor for this bounded map.
+    pub fn access<S>(&self, storage: S) -> BoundedMapAccess<K, T, E, StorageBranch<S>, CAPACITY> {
+        Self::access_impl(StorageBranch::new(storage, vec![self.prefix]))
+    }
+
+    /// Acquires an accessor for the bounded map kept under `branch`. The capacity applies per
+    /// branch - inserting under one branch never evicts entries from another.
+    pub fn access_at<S>(
+        &self,
+        branch: StorageBranch<S>,
+    ) -> BoundedMapAccess<K, T, E, StorageBranch<S>, CAPACITY> {
+        Self::access_impl(branch.nest(&[self.prefix]))
+    }
+}
+
+impl<K, T, E, const CAPACITY: u32> Storable for BoundedMap<K, T, E, CAPACITY>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    type Kind = NonTerminal;
+    type Accessor<S> = BoundedMapAccess<K, T, E, S, CAPACITY>;
+    type Key = K;
+    type KeyDecodeError = MapKeyDecodeError<ItemKeyDecodeError>;
+    type Value = T;
+    type ValueDecodeError = E::DecodeError;
+
+    fn access_impl<S>(storage: S) -> BoundedMapAccess<K, T, E, S, CAPACITY> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::NONZERO_CAPACITY;
+
+        BoundedMapAccess {
+            inner: CountedMap::<K, T, E>::access_impl(storage),
+        }
+    }
+
+    fn decode_key(key: &[u8]) -> Result<K, MapKeyDecodeError<ItemKeyDecodeError>> {
+        CountedMap::<K, T, E>::decode_key(key)
+    }
+
+    fn decode_value(value: &[u8]) -> Result<T, E::DecodeError> {
+        CountedMap::<K, T, E>::decode_value(value)
+    }
+
+    fn clear_impl<S>(storage: &mut S)
+    where
+        S: Storage + IterableStorage + StorageMut,
+    {
+        let keys: Vec<_> = storage.keys(Bound::Unbounded, Bound::Unbounded).collect();
+        for key in keys {
+            if let Some(seq) = storage.get_meta(&seq_by_key(&key)) {
+                storage.remove_meta(&[&[META_KEY_BY_SEQ], &seq[..]].concat());
+            }
+            storage.remove_meta(&seq_by_key(&key));
+        }
+
+        CountedMap::<K, T, E>::clear_impl(storage);
+        storage.remove_meta(META_NEXT_SEQ);
+        storage.remove_meta(META_OLDEST_SEQ);
+    }
+}
+
+/// An accessor for a `BoundedMap`.
+///
+/// This type provides methods for reading and writing entries, and for getting the number of
+/// entries.
+pub struct BoundedMapAccess<K, T, E, S, const CAPACITY: u32> {
+    inner: CountedMapAccess<K, T, E, S>,
+}
+
+impl<K, T, E, S, const CAPACITY: u32> IterableAccessor for BoundedMapAccess<K, T, E, S, CAPACITY>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    type Storable = BoundedMap<K, T, E, CAPACITY>;
+    type Storage = S;
+
+    fn storage(&self) -> &Self::Storage {
+        self.inner.storage()
+    }
+}
+
+impl<K, T, E, S, const CAPACITY: u32> BoundedIterableAccessor
+    for BoundedMapAccess<K, T, E, S, CAPACITY>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+}
+
+impl<K, T, E, Q, const CAPACITY: u32> BoundFor<BoundedMap<K, T, E, CAPACITY>> for &Q
+where
+    K: Borrow<Q> + OwnedKey,
+    Q: Key + ?Sized,
+{
+    fn into_bytes(self) -> Vec<u8> {
+        BoundFor::<CountedMap<K, T, E>>::into_bytes(self)
+    }
+}
+
+impl<K, T, E, S, const CAPACITY: u32> BoundedMapAccess<K, T, E, S, CAPACITY>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    /// Returns the value stored under `key`, if any.
+    ///
+    /// This doesn't mark the entry as used - see [`touch`](Self::touch).
+    pub fn get<Q>(&self, key: &Q) -> Result<Option<T>, E::DecodeError>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        self.inner.get(key)
+    }
+
+    /// Returns whether an entry is stored under `key`.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        self.inner.contains(key)
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> Result<u32, LenError> {
+        self.inner.len()
+    }
+
+    /// Returns whether the map holds no entries.
+    pub fn is_empty(&self) -> Result<bool, LenError> {
+        self.inner.is_empty()
+    }
+
+    /// Returns the maximum number of entries.
+    pub fn capacity(&self) -> u32 {
+        CAPACITY
+    }
+
+    fn meta_int(&self, key: &[u8]) -> Result<u64, BoundedMapError<E::EncodeError, E::DecodeError>> {
+        match self.inner.storage.get_meta(key) {
+            None => Ok(0),
+            Some(bytes) => decode_int(&bytes),
+        }
+    }
+}
+
+impl<K, T, E, S, const CAPACITY: u32> BoundedMapAccess<K, T, E, S, CAPACITY>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    /// Stores `value` under `key` and marks the entry as the most recently used.
+    ///
+    /// If the map is full and `key` is new, the least recently used entry is removed and
+    /// returned. If that entry can't be evicted (e.g. its value doesn't decode), an error is
+    /// returned and nothing is written.
+    #[allow(clippy::type_complexity)]
+    pub fn insert<Q>(
+        &mut self,
+        key: &Q,
+        value: &T,
+    ) -> Result<Option<(K, T)>, BoundedMapError<E::EncodeError, E::DecodeError>>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        let len = self.len().map_err(|_| BoundedMapError::InconsistentState)?;
+        let evicted = if len >= CAPACITY && !self.inner.contains(key) {
+            // make room before writing anything, so that a failed eviction leaves the map
+            // as it was - and check the value encodes, so that it doesn't cost an entry
+            value.encode().map_err(BoundedMapError::Encode)?;
+            Some(self.evict()?)
+        } else {
+            None
+        };
+
+        self.inner.insert(key, value)?;
+        self.mark_used(&key.encode())?;
+
+        Ok(evicted)
+    }
+
+    /// Marks the entry under `key` as the most recently used, returning whether it exists.
+    pub fn touch<Q>(
+        &mut self,
+        key: &Q,
+    ) -> Result<bool, BoundedMapError<E::EncodeError, E::DecodeError>>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        if !self.inner.contains(key) {
+            return Ok(false);
+        }
+
+        self.mark_used(&key.encode())?;
+        Ok(true)
+    }
+
+    /// Removes the entry under `key`, returning whether it existed.
+    pub fn remove<Q>(
+        &mut self,
+        key: &Q,
+    ) -> Result<bool, BoundedMapError<E::EncodeError, E::DecodeError>>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        if !self.inner.remove(key)? {
+            return Ok(false);
+        }
+
+        let key = key.encode();
+        if let Some(seq) = self.inner.storage.get_meta(&seq_by_key(&key)) {
+            self.inner
+                .storage
+                .remove_meta(&[&[META_KEY_BY_SEQ], &seq[..]].concat());
+        }
+        self.inner.storage.remove_meta(&seq_by_key(&key));
+
+        if self.is_empty() == Ok(true) {
+            // nothing left to track, so the sequence numbers can start over
+            self.inner.storage.remove_meta(META_NEXT_SEQ);
+            self.inner.storage.remove_meta(META_OLDEST_SEQ);
+        }
+        Ok(true)
+    }
+
+    /// Assigns `key` the next sequence number.
+    fn mark_used(
+        &mut self,
+        key: &[u8],
+    ) -> Result<(), BoundedMapError<E::EncodeError, E::DecodeError>> {
+        let seq_key = seq_by_key(key);
+
+        if let Some(old_seq) = self.inner.storage.get_meta(&seq_key) {
+            self.inner
+                .storage
+                .remove_meta(&[&[META_KEY_BY_SEQ], &old_seq[..]].concat());
+        }
+
+        let seq = self.meta_int(META_NEXT_SEQ)?;
+        let storage = &mut self.inner.storage;
+        storage.set_meta(&seq_key, &seq.to_be_bytes());
+        storage.set_meta(&key_by_seq(seq), key);
+        storage.set_meta(META_NEXT_SEQ, &(seq + 1).to_be_bytes());
+
+        Ok(())
+    }
+
+    /// Removes and returns the least recently used entry.
+    fn evict(&mut self) -> Result<(K, T), BoundedMapError<E::EncodeError, E::DecodeError>> {
+        let next_seq = self.meta_int(META_NEXT_SEQ)?;
+        let mut seq = self.meta_int(META_OLDEST_SEQ)?;
+
+        // sequence numbers of entries used again since are gone, skip them
+        let raw_key = loop {
+            if seq >= next_seq {
+                return Err(BoundedMapError::InconsistentState);
+            }
+            if let Some(key) = self.inner.storage.get_meta(&key_by_seq(seq)) {
+                break key;
+            }
+            seq += 1;
+        };
+
+        // decode before removing anything, so that a corrupted entry isn't lost
+        let value = self
+            .inner
+            .storage
+            .get(&raw_key)
+            .ok_or(BoundedMapError::InconsistentState)?;
+        let key = K::from_bytes(&raw_key).map_err(|_| BoundedMapError::InconsistentState)?;
+        let value = T::decode(&value).map_err(BoundedMapError::Decode)?;
+
+        self.inner.remove(&key)?;
+        let storage = &mut self.inner.storage;
+        storage.remove_meta(&key_by_seq(seq));
+        storage.remove_meta(&seq_by_key(&raw_key));
+        storage.set_meta(META_OLDEST_SEQ, &(seq + 1).to_be_bytes());
+
+        Ok((key, value))
+    }
+}
+
+impl<K, T, E, S, const CAPACITY: u32> BoundedMapAccess<K, T, E, S, CAPACITY>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + IterableStorage + StorageMut,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        BoundedMap::<K, T, E, CAPACITY>::clear_impl(&mut self.inner.storage)
+    }
+}
+
+fn seq_by_key(key: &[u8]) -> Vec<u8> {
+    [&[META_SEQ_BY_KEY], key].concat()
+}
+
+fn key_by_seq(seq: u64) -> Vec<u8> {
+    [&[META_KEY_BY_SEQ][..], &seq.to_be_bytes()].concat()
+}
+
+fn decode_int<EE, DE>(bytes: &[u8]) -> Result<u64, BoundedMapError<EE, DE>> {
+    bytes
+        .try_into()
+        .map(u64::from_be_bytes)
+        .map_err(|_| BoundedMapError::InconsistentState)
+}
+
+/// An error that can occur when operating on a [`BoundedMap`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum BoundedMapError<EE, DE> {
+    #[error("failed to encode value: {0}")]
+    Encode(EE),
+    #[error("failed to decode evicted value: {0}")]
+    Decode(DE),
+    #[error("inconsistent bounded map bookkeeping")]
+    InconsistentState,
+}
+
+impl<EE, DE> From<CountedMapError<EE>> for BoundedMapError<EE, DE> {
+    fn from(error: CountedMapError<EE>) -> Self {
+        match error {
+            CountedMapError::Encode(e) => Self::Encode(e),
+            CountedMapError::InconsistentState => Self::InconsistentState,
+        }
+    }
+}
+
+impl<EE: std::fmt::Display, DE: std::fmt::Display> crate::error::StoreyError
+    for BoundedMapError<EE, DE>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::Map;
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut storage = TestStorage::new();
+
+        let map = BoundedMap::<u32, u64, TestEncoding, 3>::new(0);
+        let mut access = map.access(&mut storage);
+
+        for i in 1..=3 {
+            assert_eq!(access.insert(&i, &(i as u64 * 10)).unwrap(), None);
+        }
+
+        // 1 is used again, twice - its old sequence numbers have to be skipped on eviction
+        assert_eq!(access.insert(&1, &11).unwrap(), None);
+        assert!(access.touch(&1).unwrap());
+        assert!(!access.touch(&9).unwrap());
+        assert_eq!(access.len(), Ok(3));
+
+        assert_eq!(access.insert(&4, &40).unwrap(), Some((2, 20)));
+        assert_eq!(access.insert(&5, &50).unwrap(), Some((3, 30)));
+        assert_eq!(access.insert(&6, &60).unwrap(), Some((1, 11)));
+
+        let entries = access.pairs().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(entries, vec![(4, 40), (5, 50), (6, 60)]);
+
+        // removing frees a slot
+        assert!(access.remove(&5).unwrap());
+        assert!(!access.remove(&5).unwrap());
+        assert_eq!(access.insert(&7, &70).unwrap(), None);
+        assert_eq!(access.insert(&8, &80).unwrap(), Some((4, 40)));
+        assert_eq!(access.get(&6), Ok(Some(60)));
+        assert_eq!(access.len(), Ok(3));
+    }
+
+    #[test]
+    fn nested_and_cleared() {
+        let mut storage = TestStorage::new();
+
+        let history = Map::<String, BoundedMap<u32, u64, TestEncoding, 1>>::new(0);
+        let mut access = history.access(&mut storage);
+
+        access.entry_mut("alice").insert(&1, &1).unwrap();
+        access.entry_mut("bob").insert(&1, &2).unwrap();
+        assert_eq!(
+            access.entry_mut("alice").insert(&2, &3).unwrap(),
+            Some((1, 1))
+        );
+        assert_eq!(access.entry("bob").get(&1), Ok(Some(2)));
+
+        access.clear();
+        assert_eq!(access.entry("alice").len(), Ok(0));
+        assert_eq!(storage.export(), TestStorage::new().export());
+    }
+
+    #[test]
+    fn corrupted_entries_are_not_evicted() {
+        let mut storage = TestStorage::new();
+
+        let map = BoundedMap::<u32, u64, TestEncoding, 1>::new(0);
+        map.access(&mut storage).insert(&1, &10).unwrap();
+
+        // the oldest entry can't be decoded, so it's kept rather than evicted and lost
+        storey_storage::StorageMut::set(&mut storage, &[0, 0, 0, 0, 1], &[1]);
+        let before = storage.export();
+        let mut access = map.access(&mut storage);
+        for _ in 0..2 {
+            assert!(matches!(
+                access.insert(&2, &20),
+                Err(BoundedMapError::Decode(_))
+            ));
+        }
+        assert!(access.contains(&1));
+        assert!(!access.contains(&2));
+        assert_eq!(access.len(), Ok(1));
+
+        // the failed inserts left nothing behind
+        assert_eq!(storage.export(), before);
+
+        StorageBranch::new(&mut storage, vec![0]).set_meta(&[0], &[1]);
+        assert_eq!(map.access(&storage).len(), Err(LenError::InconsistentState));
+    }
+
+    #[test]
+    fn access_at() {
+        let mut storage = TestStorage::new();
+
+        let map = BoundedMap::<u32, u64, TestEncoding, 1>::new(0);
+        let mut a = map.access_at(StorageBranch::new(&mut storage, b"a/".to_vec()));
+        assert_eq!(a.insert(&1, &10).unwrap(), None);
+
+        // a full map under another branch doesn't evict anything here
+        let mut b = map.access_at(StorageBranch::new(&mut storage, b"b/".to_vec()));
+        assert_eq!(b.insert(&2, &20).unwrap(), None);
+        assert_eq!(b.insert(&3, &30).unwrap(), Some((2, 20)));
+
+        let a = map.access_at(StorageBranch::new(&storage, b"a/".to_vec()));
+        assert_eq!(a.get(&1), Ok(Some(10)));
+        assert_eq!(a.len(), Ok(1));
+    }
+}
@@ -0,0 +1,153 @@
+use std::cell::OnceCell;
+
+use crate::encoding::{DecodableWith, EncodableWith, Encoding};
+use crate::storage::{Storage, StorageMut};
+
+use super::ItemAccess;
+
+/// An [`ItemAccess`] wrapper that remembers the decoded value after the first read.
+///
+/// Subsequent calls to [`get`](Self::get) return the cached value without touching the storage
+/// or decoding anything again. Writing through this accessor ([`set`](Self::set) or
+/// [`remove`](Self::remove)) invalidates the cache.
+///
+/// The cache lives only as long as this accessor. It's not shared with other accessors
+/// and doesn't survive re-acquiring one. Writes made through other accessors while this
+/// one is alive are **not** observed.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::Item;
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<u64, TestEncoding>::new(0);
+/// item.access(&mut storage).set(&42).unwrap();
+///
+/// let mut access = item.access(&mut storage).cached();
+/// assert_eq!(access.get().unwrap(), Some(&42));
+/// assert_eq!(access.get().unwrap(), Some(&42)); // no decoding this time
+///
+/// access.set(&1337).unwrap();
+/// assert_eq!(access.get().unwrap(), Some(&1337));
+/// ```
+pub struct CachedItemAccess<E, T, S> {
+    inner: ItemAccess<E, T, S>,
+    cache: OnceCell<Option<T>>,
+}
+
+impl<E, T, S> ItemAccess<E, T, S> {
+    /// Wraps this accessor in one that caches the decoded value. See [`CachedItemAccess`].
+    pub fn cached(self) -> CachedItemAccess<E, T, S> {
+        CachedItemAccess {
+            inner: self,
+            cache: OnceCell::new(),
+        }
+    }
+}
+
+impl<E, T, S> CachedItemAccess<E, T, S> {
+    /// Returns the wrapped accessor, discarding the cache.
+    pub fn into_inner(self) -> ItemAccess<E, T, S> {
+        self.inner
+    }
+}
+
+impl<E, T, S> CachedItemAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage,
+{
+    /// Get the value of the item, reading and decoding it only on the first call.
+    ///
+    /// Returns `Ok(None)` if the item doesn't exist (has not been set yet). Decoding errors
+    /// are not cached.
+    pub fn get(&self) -> Result<Option<&T>, E::DecodeError> {
+        if let Some(value) = self.cache.get() {
+            return Ok(value.as_ref());
+        }
+
+        let value = self.inner.get()?;
+        Ok(self.cache.get_or_init(|| value).as_ref())
+    }
+}
+
+impl<E, T, S> CachedItemAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+{
+    /// Set the value of the item, invalidating the cache.
+    pub fn set(&mut self, value: &T) -> Result<(), E::EncodeError> {
+        self.cache.take();
+        self.inner.set(value)
+    }
+
+    /// Remove the value of the item, caching the fact that it doesn't exist.
+    pub fn remove(&mut self) {
+        self.inner.remove();
+        self.cache = OnceCell::from(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use crate::containers::Item;
+    use crate::storage::{Storage, StorageMut as _};
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+
+    #[test]
+    fn caches_until_written() {
+        let mut storage = TestStorage::new();
+
+        let item = Item::<u64, TestEncoding>::new(0);
+        let mut access = item.access(&mut storage).cached();
+
+        assert_eq!(access.get().unwrap(), None);
+
+        access.set(&7).unwrap();
+        assert_eq!(access.get().unwrap(), Some(&7));
+
+        access.remove();
+        assert_eq!(access.get().unwrap(), None);
+
+        access.set(&8).unwrap();
+        assert_eq!(access.get().unwrap(), Some(&8));
+        assert_eq!(access.into_inner().get().unwrap(), Some(8));
+    }
+
+    #[test]
+    fn decode_errors_are_not_cached() {
+        // lets the stored bytes change behind the cached accessor's back
+        struct Shared<'a>(&'a RefCell<TestStorage>);
+
+        impl Storage for Shared<'_> {
+            fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+                self.0.borrow().get(key)
+            }
+
+            fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
+                self.0.borrow().get_meta(key)
+            }
+        }
+
+        let storage = RefCell::new(TestStorage::new());
+        storage.borrow_mut().set(&[0], &[1, 2, 3]);
+
+        let item = Item::<u64, TestEncoding>::new(0);
+        let shared = Shared(&storage);
+        let access = item.access(&shared).cached();
+        assert!(access.get().is_err());
+
+        // the failed read left nothing behind, so the fixed value is read fresh
+        storage.borrow_mut().set(&[0], &5u64.to_le_bytes());
+        assert_eq!(access.get().unwrap(), Some(&5));
+    }
+}
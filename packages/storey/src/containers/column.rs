@@ -1,20 +1,24 @@
 use std::marker::PhantomData;
+use std::ops::RangeBounds;
 
 use thiserror::Error;
 
 use crate::encoding::Encoding;
 use crate::encoding::{DecodableWith, EncodableWith};
-use crate::storage::{IterableStorage, StorageBranch};
+use crate::storage::{IndexKey, IterableStorage, RevIterableStorage, StorageBranch};
 use crate::storage::{Storage, StorageMut};
 
 use super::common::TryGetError;
-use super::{BoundFor, BoundedIterableAccessor, IterableAccessor, NonTerminal, Storable};
+use super::{
+    BoundFor, BoundedIterableAccessor, BoundedRevIterableAccessor, IterableAccessor, NonTerminal,
+    Storable, StorableIter,
+};
 
 /// The first (lowest) ID that is pushed to the column.
 const FIRST_ID: u32 = 1;
 
 /// Storage keys for metadata.
-mod meta_keys {
+pub(super) mod meta_keys {
     /// The last ID that has been pushed to the column.
     /// This does not have to be the ID of the last element as it is
     /// not reset in case the last element is removed.
@@ -89,6 +93,12 @@ where
     pub fn access<S>(&self, storage: S) -> ColumnAccess<E, T, StorageBranch<S>> {
         Self::access_impl(StorageBranch::new(storage, vec![self.prefix]))
     }
+
+    /// Acquires an accessor for the column kept under `branch`. Ids and the length are tracked
+    /// per branch, so columns under different branches don't share a sequence.
+    pub fn access_at<S>(&self, branch: StorageBranch<S>) -> ColumnAccess<E, T, StorageBranch<S>> {
+        Self::access_impl(branch.nest(&[self.prefix]))
+    }
 }
 
 impl<T, E> Storable for Column<T, E>
@@ -119,6 +129,15 @@ where
     fn decode_value(value: &[u8]) -> Result<Self::Value, Self::ValueDecodeError> {
         T::decode(value)
     }
+
+    fn clear_impl<S>(storage: &mut S)
+    where
+        S: IterableStorage + StorageMut,
+    {
+        super::clear_namespace(storage);
+        storage.remove_meta(meta_keys::META_LAST_ID);
+        storage.remove_meta(meta_keys::META_LEN);
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
@@ -161,6 +180,87 @@ impl<T, E> BoundFor<Column<T, E>> for u32 {
     }
 }
 
+impl<E, T, S> ColumnAccess<E, T, StorageBranch<S>> {
+    /// Returns the prefix of the column's namespace. See [`StorageBranch::prefix`].
+    pub fn prefix(&self) -> &[u8] {
+        self.storage.prefix()
+    }
+}
+
+impl<E, T, S> ColumnAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+{
+    /// Iterate over the `(id, value)` pairs whose IDs fall within the given range.
+    ///
+    /// This is a shorthand for [`bounded_pairs`](BoundedIterableAccessor::bounded_pairs)
+    /// that accepts Rust range syntax.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Column;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let column = Column::<u64, TestEncoding>::new(0);
+    /// let mut access = column.access(&mut storage);
+    ///
+    /// for i in 0..10 {
+    ///     access.push(&(i * 100)).unwrap();
+    /// }
+    ///
+    /// let page = access.range(5..8).collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(page, vec![(5, 400), (6, 500), (7, 600)]);
+    /// ```
+    pub fn range<R>(&self, range: R) -> StorableIter<Column<T, E>, S::PairsIterator<'_>>
+    where
+        R: RangeBounds<u32>,
+    {
+        self.bounded_pairs(range.start_bound().cloned(), range.end_bound().cloned())
+    }
+}
+
+impl<E, T, S> ColumnAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage + RevIterableStorage,
+{
+    /// Iterate over the `(id, value)` pairs whose IDs fall within the given range,
+    /// from the highest ID to the lowest.
+    ///
+    /// This is a shorthand for
+    /// [`bounded_rev_pairs`](BoundedRevIterableAccessor::bounded_rev_pairs) that accepts
+    /// Rust range syntax. It's handy for "latest first" views.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Column;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let column = Column::<u64, TestEncoding>::new(0);
+    /// let mut access = column.access(&mut storage);
+    ///
+    /// for i in 0..10 {
+    ///     access.push(&(i * 100)).unwrap();
+    /// }
+    ///
+    /// let latest = access.rev_range(..).take(2).collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(latest, vec![(10, 900), (9, 800)]);
+    /// ```
+    pub fn rev_range<R>(&self, range: R) -> StorableIter<Column<T, E>, S::RevPairsIterator<'_>>
+    where
+        R: RangeBounds<u32>,
+    {
+        self.bounded_rev_pairs(range.start_bound().cloned(), range.end_bound().cloned())
+    }
+}
+
 impl<E, T, S> ColumnAccess<E, T, S>
 where
     E: Encoding,
@@ -296,20 +396,27 @@ where
     pub fn is_empty(&self) -> Result<bool, LenError> {
         self.len().map(|len| len == 0)
     }
-}
 
-fn decode_id(id: &[u8]) -> Result<u32, ColumnIdDecodeError> {
-    if id.len() != 4 {
-        return Err(ColumnIdDecodeError);
+    /// Returns the last ID pushed to the column, whether or not its entry still exists.
+    pub(super) fn last_id(&self) -> Result<Option<u32>, LenError> {
+        self.storage
+            .get_meta(meta_keys::META_LAST_ID)
+            .map(|bytes| {
+                bytes
+                    .try_into()
+                    .map(u32::from_be_bytes)
+                    .map_err(|_| LenError::InconsistentState)
+            })
+            .transpose()
     }
+}
 
-    let row_key = u32::from_be_bytes([id[0], id[1], id[2], id[3]]);
-
-    Ok(row_key)
+fn decode_id(id: &[u8]) -> Result<u32, ColumnIdDecodeError> {
+    u32::from_index_bytes(id).ok_or(ColumnIdDecodeError)
 }
 
-fn encode_id(id: u32) -> [u8; 4] {
-    id.to_be_bytes()
+fn encode_id(id: u32) -> Vec<u8> {
+    id.to_index_bytes()
 }
 
 impl<E, T, S> ColumnAccess<E, T, S>
@@ -466,6 +573,39 @@ where
     }
 }
 
+impl<E, T, S> ColumnAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + IterableStorage + StorageMut,
+{
+    /// Remove all values from the column, along with its metadata.
+    ///
+    /// Unlike [`remove`](Self::remove), this resets the column completely - the next
+    /// pushed value gets the ID `1` again.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Column;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let column = Column::<u64, TestEncoding>::new(0);
+    /// let mut access = column.access(&mut storage);
+    ///
+    /// access.push(&1337).unwrap();
+    /// access.push(&42).unwrap();
+    /// access.clear();
+    ///
+    /// assert_eq!(access.len().unwrap(), 0);
+    /// assert_eq!(access.push(&9001).unwrap(), 1);
+    /// ```
+    pub fn clear(&mut self) {
+        Column::<T, E>::clear_impl(&mut self.storage)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
 pub enum PushError<E> {
     #[error("ID overflow")]
@@ -757,6 +897,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn range_iteration() {
+        let mut storage = TestStorage::new();
+
+        let column = Column::<u64, TestEncoding>::new(0);
+        let mut access = column.access(&mut storage);
+
+        access.push(&1337).unwrap(); //1
+        access.push(&42).unwrap(); //2
+        access.push(&9001).unwrap(); //3 (removed)
+        access.push(&1).unwrap(); //4
+        access.push(&2).unwrap(); //5
+        access.remove(3).unwrap();
+
+        assert_eq!(
+            access.range(2..5).collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![(2, 42), (4, 1)]
+        );
+        assert_eq!(
+            access.range(2..=5).collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![(2, 42), (4, 1), (5, 2)]
+        );
+        assert_eq!(
+            access.range(..3).collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![(1, 1337), (2, 42)]
+        );
+        assert_eq!(
+            access.range(4..).collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![(4, 1), (5, 2)]
+        );
+
+        assert_eq!(
+            access
+                .rev_range(2..5)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![(4, 1), (2, 42)]
+        );
+        assert_eq!(
+            access.rev_range(..).collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![(5, 2), (4, 1), (2, 42), (1, 1337)]
+        );
+    }
+
     #[test]
     fn bounded_rev_iteration() {
         let mut storage = TestStorage::new();
@@ -810,4 +994,32 @@ mod tests {
             vec![(5, 2), (4, 1), (2, 42)]
         );
     }
+
+    #[test]
+    fn move_namespace_leaves_metadata_behind() {
+        let mut storage = TestStorage::new();
+
+        let old = Column::<u64, TestEncoding>::new(0);
+        let new = Column::<u64, TestEncoding>::new(1);
+        old.access(&mut storage).push(&10).unwrap();
+        old.access(&mut storage).push(&20).unwrap();
+
+        storey_storage::StorageMut::move_namespace(&mut storage, &[0], &[1]);
+
+        // the entries moved, the length and last ID didn't
+        let mut access = new.access(&mut storage);
+        assert_eq!(access.get(2).unwrap(), Some(20));
+        assert_eq!(access.len(), Ok(0));
+        assert_eq!(access.push(&30).unwrap(), 1);
+        assert_eq!(access.get(1).unwrap(), Some(30));
+        assert_eq!(old.access(&storage).len(), Ok(2));
+        assert_eq!(old.access(&storage).get(1).unwrap(), None);
+
+        // moving the known metadata keys by hand restores the bookkeeping
+        for key in [meta_keys::META_LAST_ID, meta_keys::META_LEN] {
+            let value = StorageBranch::new(&storage, vec![0]).get_meta(key).unwrap();
+            StorageBranch::new(&mut storage, vec![1]).set_meta(key, &value);
+        }
+        assert_eq!(new.access(&mut storage).push(&40).unwrap(), 3);
+    }
 }
@@ -1,9 +1,70 @@
+/// An error returned by `try_get` methods, e.g. [`ItemAccess::try_get`].
+///
+/// Absence and corruption are kept apart, so callers can e.g. fall back to a default when
+/// nothing is stored, but still treat a value that fails to decode as a hard error.
+///
+/// [`ItemAccess::try_get`]: crate::containers::ItemAccess::try_get
 #[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TryGetError<E> {
+    /// Nothing is stored.
     #[error("item is empty")]
     Empty,
+    /// A value is stored, but it couldn't be decoded.
     #[error(transparent)]
-    DecodeError(#[from] E),
+    Decode(#[from] E),
+}
+
+impl<E> TryGetError<E> {
+    /// Returns whether this error means nothing is stored.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, TryGetError::Empty)
+    }
+
+    /// Returns the decode error, or `None` if nothing is stored.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    ///
+    /// let fee = match item.access(&storage).try_get() {
+    ///     Ok(fee) => fee,
+    ///     Err(e) => match e.into_decode_error() {
+    ///         Some(e) => panic!("corrupt fee: {e}"),
+    ///         None => 30,
+    ///     },
+    /// };
+    /// assert_eq!(fee, 30);
+    /// ```
+    pub fn into_decode_error(self) -> Option<E> {
+        match self {
+            TryGetError::Empty => None,
+            TryGetError::Decode(e) => Some(e),
+        }
+    }
 }
 
 impl<T: std::fmt::Display> crate::error::StoreyError for TryGetError<T> {}
+
+/// The storage prefix of a top-level container.
+///
+/// Most containers are rooted under a single byte, which lets them be constructed in `const`
+/// contexts. Containers with a prefix computed at runtime store the whole byte string.
+pub(crate) enum Prefix {
+    Byte(u8),
+    Bytes(Vec<u8>),
+}
+
+impl Prefix {
+    pub(crate) fn to_vec(&self) -> Vec<u8> {
+        match self {
+            Prefix::Byte(byte) => vec![*byte],
+            Prefix::Bytes(bytes) => bytes.clone(),
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+
+use crate::encoding::{DecodableWith, EncodableWith, Encoding};
+use crate::storage::{IterableStorage, Storage, StorageBranch, StorageMut};
+
+use super::column::LenError;
+use super::map::key::Key;
+use super::map::{KeyEncodingT, MapKeyDecodeError, OwnedKey};
+use super::{
+    BoundFor, BoundedIterableAccessor, Item, ItemKeyDecodeError, IterableAccessor, Map,
+    NonTerminal, Storable, Terminal,
+};
+
+const META_LEN: &[u8] = &[0];
+
+/// A map of values that keeps track of how many entries it holds.
+///
+/// The storage layout of the entries is the same as that of a `Map<K, Item<T, E>>`. The number
+/// of entries is kept in the metadata namespace and updated on every insert and remove, so
+/// [`len`](CountedMapAccess::len) is a single read rather than a scan. Overwriting an existing
+/// entry or removing an absent one doesn't change the count.
+///
+/// Nested in a [`Map`], this gives a count per outer key, e.g. the number of positions held by
+/// each user. Entries must only be written through [`CountedMapAccess`] - writing to the same
+/// namespace by other means desynchronizes the count.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{CountedMap, Map};
+///
+/// let mut storage = TestStorage::new();
+/// let positions = Map::<String, CountedMap<u32, u64, TestEncoding>>::new(0);
+/// let mut access = positions.access(&mut storage);
+///
+/// access.entry_mut("alice").insert(&1, &100).unwrap();
+/// access.entry_mut("alice").insert(&2, &200).unwrap();
+/// access.entry_mut("bob").insert(&1, &50).unwrap();
+///
+/// assert_eq!(access.entry("alice").len().unwrap(), 2);
+/// assert_eq!(access.entry("bob").len().unwrap(), 1);
+/// assert_eq!(access.entry("carol").len().unwrap(), 0);
+/// ```
+pub struct CountedMap<K, T, E> {
+    prefix: u8,
+    phantom: PhantomData<(K, T, E)>,
+}
+
+impl<K, T, E> CountedMap<K, T, E>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    /// Creates a new counted map with the given prefix.
+    ///
+    /// It is the responsibility of the caller to ensure that the prefix is unique and does not
+    /// conflict with other keys in the storage.
+    pub const fn new(prefix: u8) -> Self {
+        Self {
+            prefix,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Acquires an accessor for this counted map.
+    pub fn access<S>(&self, storage: S) -> CountedMapAccess<K, T, E, StorageBranch<S>> {
+        Self::access_impl(StorageBranch::new(storage, vec![self.prefix]))
+    }
+
+    /// Acquires an accessor for the counted map kept under `branch`. The count lives in the
+    /// same namespace, so each branch counts only its own entries.
+    pub fn access_at<S>(
+        &self,
+        branch: StorageBranch<S>,
+    ) -> CountedMapAccess<K, T, E, StorageBranch<S>> {
+        Self::access_impl(branch.nest(&[self.prefix]))
+    }
+}
+
+impl<K, T, E> Storable for CountedMap<K, T, E>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    type Kind = NonTerminal;
+    type Accessor<S> = CountedMapAccess<K, T, E, S>;
+    type Key = K;
+    type KeyDecodeError = MapKeyDecodeError<ItemKeyDecodeError>;
+    type Value = T;
+    type ValueDecodeError = E::DecodeError;
+
+    fn access_impl<S>(storage: S) -> CountedMapAccess<K, T, E, S> {
+        CountedMapAccess {
+            storage,
+            phantom: PhantomData,
+        }
+    }
+
+    fn decode_key(key: &[u8]) -> Result<K, MapKeyDecodeError<ItemKeyDecodeError>> {
+        Map::<K, Item<T, E>>::decode_key(key).map(|(key, ())| key)
+    }
+
+    fn decode_value(value: &[u8]) -> Result<T, E::DecodeError> {
+        T::decode(value)
+    }
+
+    fn clear_impl<S>(storage: &mut S)
+    where
+        S: Storage + IterableStorage + StorageMut,
+    {
+        super::clear_namespace(storage);
+        storage.remove_meta(META_LEN);
+    }
+}
+
+/// An accessor for a `CountedMap`.
+///
+/// This type provides methods for reading and writing entries, and for getting the number of
+/// entries.
+pub struct CountedMapAccess<K, T, E, S> {
+    pub(super) storage: S,
+    phantom: PhantomData<(K, T, E)>,
+}
+
+impl<K, T, E, S> IterableAccessor for CountedMapAccess<K, T, E, S>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    type Storable = CountedMap<K, T, E>;
+    type Storage = S;
+
+    fn storage(&self) -> &Self::Storage {
+        &self.storage
+    }
+}
+
+impl<K, T, E, S> BoundedIterableAccessor for CountedMapAccess<K, T, E, S>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+}
+
+impl<K, T, E, Q> BoundFor<CountedMap<K, T, E>> for &Q
+where
+    K: Borrow<Q> + OwnedKey,
+    Q: Key + ?Sized,
+{
+    fn into_bytes(self) -> Vec<u8> {
+        // entries are terminal, so keys are never length-prefixed
+        self.encode()
+    }
+}
+
+impl<K, T, E, S> CountedMapAccess<K, T, E, S>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    /// Returns the value stored under `key`, if any.
+    pub fn get<Q>(&self, key: &Q) -> Result<Option<T>, E::DecodeError>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        self.storage
+            .get(&key.encode())
+            .map(|bytes| T::decode(&bytes))
+            .transpose()
+    }
+
+    /// Returns whether an entry is stored under `key`.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        self.storage.has(&key.encode())
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> Result<u32, LenError> {
+        match self.storage.get_meta(META_LEN) {
+            None => Ok(0),
+            Some(bytes) => bytes
+                .try_into()
+                .map(u32::from_be_bytes)
+                .map_err(|_| LenError::InconsistentState),
+        }
+    }
+
+    /// Returns whether the map holds no entries.
+    pub fn is_empty(&self) -> Result<bool, LenError> {
+        self.len().map(|len| len == 0)
+    }
+}
+
+impl<K, T, E, S> CountedMapAccess<K, T, E, S>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    /// Stores `value` under `key`, returning whether the entry is new.
+    ///
+    /// Overwriting an existing entry doesn't change the count.
+    pub fn insert<Q>(&mut self, key: &Q, value: &T) -> Result<bool, CountedMapError<E::EncodeError>>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        let bytes = value.encode().map_err(CountedMapError::Encode)?;
+        let key = key.encode();
+
+        let is_new = !self.storage.has(&key);
+        if is_new {
+            let len = self.stored_len()?.checked_add(1);
+            self.set_len(len.ok_or(CountedMapError::InconsistentState)?);
+        }
+        self.storage.set(&key, &bytes);
+
+        Ok(is_new)
+    }
+
+    /// Removes the entry under `key`, returning whether it existed.
+    ///
+    /// Removing an absent entry doesn't change the count.
+    pub fn remove<Q>(&mut self, key: &Q) -> Result<bool, CountedMapError<E::EncodeError>>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        let key = key.encode();
+        if !self.storage.has(&key) {
+            return Ok(false);
+        }
+
+        let len = self.stored_len()?.checked_sub(1);
+        self.set_len(len.ok_or(CountedMapError::InconsistentState)?);
+        self.storage.remove(&key);
+
+        Ok(true)
+    }
+
+    fn stored_len(&self) -> Result<u32, CountedMapError<E::EncodeError>> {
+        self.len().map_err(|_| CountedMapError::InconsistentState)
+    }
+
+    fn set_len(&mut self, len: u32) {
+        if len == 0 {
+            self.storage.remove_meta(META_LEN);
+        } else {
+            self.storage.set_meta(META_LEN, &len.to_be_bytes());
+        }
+    }
+}
+
+impl<K, T, E, S> CountedMapAccess<K, T, E, S>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + IterableStorage + StorageMut,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    /// Removes all entries and resets the count.
+    pub fn clear(&mut self) {
+        CountedMap::<K, T, E>::clear_impl(&mut self.storage)
+    }
+}
+
+/// An error that can occur when writing to a [`CountedMap`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum CountedMapError<EE> {
+    #[error("failed to encode value: {0}")]
+    Encode(EE),
+    #[error("counted map length out of sync with its entries")]
+    InconsistentState,
+}
+
+impl<EE: std::fmt::Display> crate::error::StoreyError for CountedMapError<EE> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+
+    #[test]
+    fn counts_per_outer_key() {
+        let mut storage = TestStorage::new();
+
+        let positions = Map::<String, CountedMap<u32, u64, TestEncoding>>::new(0);
+        let mut access = positions.access(&mut storage);
+
+        assert!(access.entry_mut("alice").insert(&1, &10).unwrap());
+        assert!(access.entry_mut("alice").insert(&2, &20).unwrap());
+        assert!(access.entry_mut("bob").insert(&1, &30).unwrap());
+
+        // overwrites and absent removes don't count
+        assert!(!access.entry_mut("alice").insert(&2, &21).unwrap());
+        assert_eq!(access.entry_mut("bob").remove(&2), Ok(false));
+        assert_eq!(access.entry("alice").len(), Ok(2));
+        assert_eq!(access.entry("bob").len(), Ok(1));
+        assert_eq!(access.entry("alice").get(&2), Ok(Some(21)));
+
+        assert_eq!(access.entry_mut("bob").remove(&1), Ok(true));
+        assert_eq!(access.entry("bob").len(), Ok(0));
+        assert!(access.entry("bob").is_empty().unwrap());
+        assert!(!access.entry("bob").contains(&1));
+
+        let entries = access
+            .entry("alice")
+            .pairs()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(entries, vec![(1, 10), (2, 21)]);
+
+        let nested = access.pairs().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            nested,
+            vec![
+                (("alice".to_string(), 1), 10),
+                (("alice".to_string(), 2), 21)
+            ]
+        );
+
+        access.clear();
+        assert_eq!(access.entry("alice").len(), Ok(0));
+        assert!(access.entry_mut("alice").insert(&1, &10).unwrap());
+        assert_eq!(access.entry("alice").len(), Ok(1));
+    }
+
+    #[test]
+    fn bounded_iteration() {
+        let mut storage = TestStorage::new();
+
+        let map = CountedMap::<String, u64, TestEncoding>::new(0);
+        let mut access = map.access(&mut storage);
+
+        for (i, key) in ["a", "b", "c"].into_iter().enumerate() {
+            access.insert(key, &(i as u64)).unwrap();
+        }
+
+        let keys = access
+            .bounded_keys(std::ops::Bound::Included("b"), std::ops::Bound::Unbounded)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, vec!["b".to_string(), "c".to_string()]);
+        assert_eq!(access.len(), Ok(3));
+    }
+
+    #[test]
+    fn out_of_sync_count() {
+        let mut storage = TestStorage::new();
+
+        let map = CountedMap::<u32, u64, TestEncoding>::new(0);
+        let mut access = map.access(&mut storage);
+        access.insert(&1, &10).unwrap();
+
+        // an entry written behind the map's back isn't counted, so removing both underflows
+        storey_storage::StorageMut::set(&mut storage, &[0, 0, 0, 0, 2], &20u64.to_le_bytes());
+        let mut access = map.access(&mut storage);
+        assert_eq!(access.remove(&1), Ok(true));
+        assert_eq!(access.remove(&2), Err(CountedMapError::InconsistentState));
+        assert!(access.contains(&2));
+
+        // a corrupted count fails inserts without writing the entry
+        StorageBranch::new(&mut storage, vec![0]).set_meta(META_LEN, &[1]);
+        let mut access = map.access(&mut storage);
+        assert_eq!(
+            access.insert(&3, &30),
+            Err(CountedMapError::InconsistentState)
+        );
+        assert!(!access.contains(&3));
+        assert_eq!(access.insert(&2, &21), Ok(false));
+    }
+
+    #[test]
+    fn access_at() {
+        let mut storage = TestStorage::new();
+
+        let map = CountedMap::<String, u64, TestEncoding>::new(0);
+        let mut v1 = map.access_at(StorageBranch::new(&mut storage, b"v1/".to_vec()));
+        v1.insert("a", &1).unwrap();
+        v1.insert("b", &2).unwrap();
+        let mut v2 = map.access_at(StorageBranch::new(&mut storage, b"v2/".to_vec()));
+        v2.insert("a", &3).unwrap();
+
+        let v1 = map.access_at(StorageBranch::new(&storage, b"v1/".to_vec()));
+        let v2 = map.access_at(StorageBranch::new(&storage, b"v2/".to_vec()));
+        assert_eq!(v1.len(), Ok(2));
+        assert_eq!(v2.len(), Ok(1));
+        assert_eq!(v2.get("a"), Ok(Some(3)));
+        assert_eq!(map.access(&storage).len(), Ok(0));
+    }
+}
@@ -0,0 +1,226 @@
+use std::marker::PhantomData;
+
+use crate::encoding::{DecodableWith, EncodableWith, Encoding};
+use crate::storage::{Storage, StorageBranch, StorageMut};
+
+use super::item::UpdateError;
+use super::{Item, ItemKeyDecodeError, Storable, Terminal};
+
+/// Provides the default value of a [`DefaultItem`].
+///
+/// Implement this on a marker type to bake a default into the item's type. For types
+/// implementing [`Default`], [`StdDefault`] can be used.
+pub trait ItemDefault<T> {
+    /// Returns the default value.
+    fn default() -> T;
+}
+
+/// An [`ItemDefault`] that uses the value's [`Default`] implementation.
+pub struct StdDefault;
+
+impl<T: Default> ItemDefault<T> for StdDefault {
+    fn default() -> T {
+        T::default()
+    }
+}
+
+/// A single value in the storage that falls back to a default when it's not set.
+///
+/// This works just like an [`Item`] (and has the same storage layout), but `get` returns the
+/// default provided by `D` instead of `None` when no value is stored. Unlike
+/// [`ItemAccess::get_or`](super::ItemAccess::get_or), the default is part of the type, so
+/// every call site agrees on it.
+///
+/// The default is never written to storage - it's only materialized when reading.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{DefaultItem, ItemDefault};
+///
+/// struct DefaultFeeBps;
+///
+/// impl ItemDefault<u64> for DefaultFeeBps {
+///     fn default() -> u64 {
+///         30
+///     }
+/// }
+///
+/// const FEE_BPS: DefaultItem<u64, TestEncoding, DefaultFeeBps> = DefaultItem::new(0);
+///
+/// let mut storage = TestStorage::new();
+/// assert_eq!(FEE_BPS.access(&storage).get().unwrap(), 30);
+///
+/// FEE_BPS.access(&mut storage).set(&50).unwrap();
+/// assert_eq!(FEE_BPS.access(&storage).get().unwrap(), 50);
+///
+/// FEE_BPS.access(&mut storage).reset();
+/// assert_eq!(FEE_BPS.access(&storage).get().unwrap(), 30);
+/// ```
+pub struct DefaultItem<T, E, D> {
+    key: u8,
+    phantom: PhantomData<(T, E, D)>,
+}
+
+impl<T, E, D> DefaultItem<T, E, D>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    D: ItemDefault<T>,
+{
+    /// Create a new item with the given key.
+    ///
+    /// It is the responsibility of the caller to ensure that the key is unique.
+    pub const fn new(key: u8) -> Self {
+        Self {
+            key,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Acquire an accessor to the item.
+    pub fn access<S>(&self, storage: S) -> DefaultItemAccess<E, T, D, StorageBranch<S>> {
+        Self::access_impl(StorageBranch::new(storage, vec![self.key]))
+    }
+
+    /// Acquire an accessor to the item, nested under `branch`. Under a branch where the item
+    /// was never set, reads return the default.
+    pub fn access_at<S>(
+        &self,
+        branch: StorageBranch<S>,
+    ) -> DefaultItemAccess<E, T, D, StorageBranch<S>> {
+        Self::access_impl(branch.nest(&[self.key]))
+    }
+}
+
+impl<T, E, D> Storable for DefaultItem<T, E, D>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    D: ItemDefault<T>,
+{
+    type Kind = Terminal;
+    type Accessor<S> = DefaultItemAccess<E, T, D, S>;
+    type Key = ();
+    type KeyDecodeError = ItemKeyDecodeError;
+    type Value = T;
+    type ValueDecodeError = E::DecodeError;
+
+    fn access_impl<S>(storage: S) -> DefaultItemAccess<E, T, D, S> {
+        DefaultItemAccess {
+            storage,
+            phantom: PhantomData,
+        }
+    }
+
+    fn decode_key(key: &[u8]) -> Result<(), ItemKeyDecodeError> {
+        Item::<T, E>::decode_key(key)
+    }
+
+    fn decode_value(value: &[u8]) -> Result<T, E::DecodeError> {
+        Item::<T, E>::decode_value(value)
+    }
+}
+
+/// An accessor for a `DefaultItem`.
+///
+/// This type provides methods to get and set the value of the item.
+pub struct DefaultItemAccess<E, T, D, S> {
+    storage: S,
+    phantom: PhantomData<(E, T, D)>,
+}
+
+impl<E, T, D, S> DefaultItemAccess<E, T, D, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    D: ItemDefault<T>,
+    S: Storage,
+{
+    /// Get the value of the item, or the default if it's not set.
+    pub fn get(&self) -> Result<T, E::DecodeError> {
+        match self.storage.get(&[]) {
+            Some(bytes) => T::decode(&bytes),
+            None => Ok(D::default()),
+        }
+    }
+
+    /// Returns whether a value is explicitly stored, as opposed to the default being used.
+    pub fn is_set(&self) -> bool {
+        self.storage.has(&[])
+    }
+}
+
+impl<E, T, D, S> DefaultItemAccess<E, T, D, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    D: ItemDefault<T>,
+    S: Storage + StorageMut,
+{
+    /// Set the value of the item.
+    pub fn set(&mut self, value: &T) -> Result<(), E::EncodeError> {
+        let bytes = value.encode()?;
+        self.storage.set(&[], &bytes);
+        Ok(())
+    }
+
+    /// Update the value of the item with `f`, which receives the current value (or the
+    /// default).
+    pub fn update<F>(&mut self, f: F) -> Result<(), UpdateError<E::DecodeError, E::EncodeError>>
+    where
+        F: FnOnce(T) -> T,
+    {
+        let value = f(self.get().map_err(UpdateError::Decode)?);
+        self.set(&value).map_err(UpdateError::Encode)
+    }
+
+    /// Remove the stored value, so that reads return the default again.
+    pub fn reset(&mut self) {
+        self.storage.remove(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+
+    #[test]
+    fn std_default() {
+        let mut storage = TestStorage::new();
+
+        let item = DefaultItem::<u64, TestEncoding, StdDefault>::new(0);
+        let mut access = item.access(&mut storage);
+
+        assert_eq!(access.get(), Ok(0));
+        assert!(!access.is_set());
+
+        access.update(|v| v + 5).unwrap();
+        assert_eq!(access.get(), Ok(5));
+        assert!(access.is_set());
+
+        // same layout as a plain item
+        let plain = Item::<u64, TestEncoding>::new(0);
+        assert_eq!(plain.access(&storage).get(), Ok(Some(5)));
+    }
+
+    #[test]
+    fn access_at() {
+        let mut storage = TestStorage::new();
+
+        let item = DefaultItem::<u64, TestEncoding, StdDefault>::new(0);
+        let v2 = StorageBranch::new(&mut storage, b"v2/".to_vec());
+        item.access_at(v2).set(&7).unwrap();
+
+        assert_eq!(storage.get(b"v2/\x00"), Some(7u64.to_le_bytes().to_vec()));
+
+        let v1 = StorageBranch::new(&storage, b"v1/".to_vec());
+        assert_eq!(item.access_at(v1).get(), Ok(0));
+        let v2 = StorageBranch::new(&storage, b"v2/".to_vec());
+        assert_eq!(item.access_at(v2).get(), Ok(7));
+    }
+}
@@ -0,0 +1,295 @@
+use std::marker::PhantomData;
+
+use thiserror::Error;
+
+use crate::encoding::{DecodableWith, EncodableWith, Encoding};
+use crate::storage::StorageBranch;
+use crate::storage::{Storage, StorageMut};
+
+use super::{ItemKeyDecodeError, Storable, Terminal};
+
+/// A single value in the storage that expires at a given height.
+///
+/// This works just like an [`Item`](crate::containers::Item), but every value is stored
+/// along with the height (or any other monotonic counter, e.g. a timestamp) at which it
+/// expires. Reads take the current height and treat expired values as absent. Expired values
+/// stay in storage until they're overwritten or removed, e.g. with
+/// [`purge_expired`](ExpiringItemAccess::purge_expired).
+///
+/// A value expires *at* its expiry height - it's visible at heights lower than that.
+///
+/// The expiry is stored in front of the encoded value as a big-endian `u64`.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{ExpiringItem, Map};
+///
+/// let mut storage = TestStorage::new();
+/// let sessions = Map::<String, ExpiringItem<u64, TestEncoding>>::new(0);
+/// let mut access = sessions.access(&mut storage);
+///
+/// access.entry_mut("alice").set(&1, 100).unwrap();
+/// access.entry_mut("bob").set(&2, 200).unwrap();
+///
+/// assert_eq!(access.entry("alice").get(99).unwrap(), Some(1));
+/// assert_eq!(access.entry("alice").get(100).unwrap(), None);
+///
+/// assert_eq!(access.purge_expired(150), 1);
+/// assert_eq!(access.entry("bob").get(150).unwrap(), Some(2));
+/// ```
+pub struct ExpiringItem<T, E> {
+    key: u8,
+    phantom: PhantomData<(T, E)>,
+}
+
+impl<T, E> ExpiringItem<T, E>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+{
+    /// Create a new expiring item with the given key.
+    ///
+    /// It is the responsibility of the caller to ensure that the key is unique.
+    pub const fn new(key: u8) -> Self {
+        Self {
+            key,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Acquire an accessor to the expiring item.
+    pub fn access<S>(&self, storage: S) -> ExpiringItemAccess<E, T, StorageBranch<S>> {
+        Self::access_impl(StorageBranch::new(storage, vec![self.key]))
+    }
+
+    /// Acquire an accessor to the expiring item, nested under `branch`. The expiry is stored
+    /// alongside the value, so it's scoped to the branch as well.
+    pub fn access_at<S>(
+        &self,
+        branch: StorageBranch<S>,
+    ) -> ExpiringItemAccess<E, T, StorageBranch<S>> {
+        Self::access_impl(branch.nest(&[self.key]))
+    }
+}
+
+impl<T, E> Storable for ExpiringItem<T, E>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+{
+    type Kind = Terminal;
+    type Accessor<S> = ExpiringItemAccess<E, T, S>;
+    type Key = ();
+    type KeyDecodeError = ItemKeyDecodeError;
+    type Value = Expiring<T>;
+    type ValueDecodeError = ExpiringDecodeError<E::DecodeError>;
+
+    fn access_impl<S>(storage: S) -> ExpiringItemAccess<E, T, S> {
+        ExpiringItemAccess {
+            storage,
+            phantom: PhantomData,
+        }
+    }
+
+    fn decode_key(key: &[u8]) -> Result<(), ItemKeyDecodeError> {
+        if key.is_empty() {
+            Ok(())
+        } else {
+            Err(ItemKeyDecodeError { key: key.to_vec() })
+        }
+    }
+
+    fn decode_value(value: &[u8]) -> Result<Expiring<T>, ExpiringDecodeError<E::DecodeError>> {
+        let expires_at = decode_expiry(value).ok_or(ExpiringDecodeError::MissingExpiry)?;
+        let value = T::decode(&value[EXPIRY_LEN..]).map_err(ExpiringDecodeError::Value)?;
+
+        Ok(Expiring { expires_at, value })
+    }
+}
+
+const EXPIRY_LEN: usize = std::mem::size_of::<u64>();
+
+/// Reads the expiry height of a raw value, without decoding the value itself.
+pub(crate) fn decode_expiry(value: &[u8]) -> Option<u64> {
+    let expiry = value.get(..EXPIRY_LEN)?;
+    Some(u64::from_be_bytes(expiry.try_into().unwrap()))
+}
+
+/// A value stored in an [`ExpiringItem`], along with its expiry height.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Expiring<T> {
+    /// The height at which the value expires.
+    pub expires_at: u64,
+    /// The value.
+    pub value: T,
+}
+
+impl<T> Expiring<T> {
+    /// Returns whether the value is expired at `height`.
+    pub fn is_expired(&self, height: u64) -> bool {
+        height >= self.expires_at
+    }
+}
+
+/// An error that can occur when decoding an [`ExpiringItem`] value.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum ExpiringDecodeError<E> {
+    #[error("value too short to hold an expiry height")]
+    MissingExpiry,
+    #[error("failed to decode value: {0}")]
+    Value(E),
+}
+
+impl<E: std::fmt::Display> crate::error::StoreyError for ExpiringDecodeError<E> {}
+
+/// An accessor for an `ExpiringItem`.
+///
+/// This type provides methods to get and set the value of the expiring item.
+pub struct ExpiringItemAccess<E, T, S> {
+    storage: S,
+    phantom: PhantomData<(E, T)>,
+}
+
+impl<E, T, S> ExpiringItemAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage,
+{
+    /// Get the value of the item, if it exists and isn't expired at `height`.
+    pub fn get(&self, height: u64) -> Result<Option<T>, ExpiringDecodeError<E::DecodeError>> {
+        Ok(self
+            .get_with_expiry()?
+            .filter(|entry| !entry.is_expired(height))
+            .map(|entry| entry.value))
+    }
+
+    /// Get the value of the item along with its expiry height, whether it's expired or not.
+    pub fn get_with_expiry(
+        &self,
+    ) -> Result<Option<Expiring<T>>, ExpiringDecodeError<E::DecodeError>> {
+        self.storage
+            .get(&[])
+            .map(|bytes| ExpiringItem::<T, E>::decode_value(&bytes))
+            .transpose()
+    }
+}
+
+impl<E, T, S> ExpiringItemAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+{
+    /// Set the value of the item, expiring at `expires_at`.
+    pub fn set(&mut self, value: &T, expires_at: u64) -> Result<(), E::EncodeError> {
+        let payload = value.encode()?;
+
+        let mut bytes = Vec::with_capacity(EXPIRY_LEN + payload.len());
+        bytes.extend_from_slice(&expires_at.to_be_bytes());
+        bytes.extend_from_slice(&payload);
+
+        self.storage.set(&[], &bytes);
+        Ok(())
+    }
+
+    /// Remove the value of the item.
+    pub fn remove(&mut self) {
+        self.storage.remove(&[]);
+    }
+
+    /// Remove the value of the item if it's expired at `height`. Returns whether it was
+    /// removed.
+    ///
+    /// Only the expiry height is read - the value itself isn't decoded.
+    pub fn purge_expired(&mut self, height: u64) -> bool {
+        let expired = self
+            .storage
+            .get(&[])
+            .and_then(|bytes| decode_expiry(&bytes))
+            .is_some_and(|expires_at| height >= expires_at);
+
+        if expired {
+            self.storage.remove(&[]);
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+
+    #[test]
+    fn expiry() {
+        let mut storage = TestStorage::new();
+
+        let item = ExpiringItem::<u64, TestEncoding>::new(0);
+        let mut access = item.access(&mut storage);
+
+        assert_eq!(access.get(0), Ok(None));
+        assert!(!access.purge_expired(0));
+
+        access.set(&42, 10).unwrap();
+        assert_eq!(access.get(9), Ok(Some(42)));
+        assert_eq!(access.get(10), Ok(None));
+        assert_eq!(
+            access.get_with_expiry(),
+            Ok(Some(Expiring {
+                expires_at: 10,
+                value: 42
+            }))
+        );
+
+        assert!(!access.purge_expired(9));
+        assert!(access.purge_expired(10));
+        assert_eq!(access.get_with_expiry(), Ok(None));
+
+        assert_eq!(
+            storage.get(&[0]),
+            None,
+            "purging removes the value from storage"
+        );
+    }
+
+    #[test]
+    fn layout() {
+        let mut storage = TestStorage::new();
+
+        let item = ExpiringItem::<u32, TestEncoding>::new(0);
+        item.access(&mut storage).set(&7, 0x0102).unwrap();
+
+        assert_eq!(
+            storage.get(&[0]),
+            Some(vec![0, 0, 0, 0, 0, 0, 1, 2, 7, 0, 0, 0])
+        );
+
+        storage.set(&[0], &[1, 2, 3]);
+        assert_eq!(
+            item.access(&storage).get(0),
+            Err(ExpiringDecodeError::MissingExpiry)
+        );
+    }
+
+    #[test]
+    fn access_at() {
+        let mut storage = TestStorage::new();
+
+        let item = ExpiringItem::<u64, TestEncoding>::new(0);
+        let epoch = StorageBranch::new(&mut storage, b"epoch-7/".to_vec());
+        item.access_at(epoch).set(&42, 10).unwrap();
+
+        assert!(storage.get(b"epoch-7/\x00").is_some());
+        assert_eq!(item.access(&storage).get(0), Ok(None));
+
+        let epoch = StorageBranch::new(&storage, b"epoch-7/".to_vec());
+        let access = item.access_at(epoch);
+        assert_eq!(access.get(9), Ok(Some(42)));
+        assert_eq!(access.get(10), Ok(None));
+    }
+}
@@ -0,0 +1,123 @@
+use super::{KVDecodeError, Storable, StorableIter};
+
+/// A [`Storable::Key`] that can be collapsed into a flat tuple.
+///
+/// Keys of nested containers are right-nested: the key of a
+/// `Map<A, Map<B, Map<C, Item<_, _>>>>` is `(A, (B, (C, ())))`. Flattening turns that into
+/// `(A, B, C)`. A key ending in a [`Column`](super::Column) index, like `(A, u32)`, becomes
+/// `(A, u32)`.
+///
+/// Flat tuples are supported up to 8 elements.
+///
+/// # Example
+/// ```
+/// use storey::containers::FlattenKey;
+///
+/// let key = ("alice".to_string(), (3u8, (7u32, ())));
+/// assert_eq!(key.flatten(), ("alice".to_string(), 3u8, 7u32));
+/// ```
+pub trait FlattenKey {
+    /// The flat tuple this key collapses into.
+    type Flat;
+
+    /// Collapses the key into a flat tuple.
+    fn flatten(self) -> Self::Flat;
+}
+
+impl FlattenKey for () {
+    type Flat = ();
+
+    fn flatten(self) -> Self::Flat {}
+}
+
+impl FlattenKey for u32 {
+    type Flat = (u32,);
+
+    fn flatten(self) -> Self::Flat {
+        (self,)
+    }
+}
+
+impl<A, R> FlattenKey for (A, R)
+where
+    R: FlattenKey,
+    R::Flat: Prepend<A>,
+{
+    type Flat = <R::Flat as Prepend<A>>::Output;
+
+    fn flatten(self) -> Self::Flat {
+        self.1.flatten().prepend(self.0)
+    }
+}
+
+/// A flat tuple that can have an element added at the front.
+///
+/// This is a helper for [`FlattenKey`] and is implemented for tuples of up to 7 elements.
+pub trait Prepend<A> {
+    /// The tuple with the element prepended.
+    type Output;
+
+    /// Adds `head` at the front of the tuple.
+    fn prepend(self, head: A) -> Self::Output;
+}
+
+macro_rules! impl_prepend {
+    ($($t:ident),*) => {
+        impl<A, $($t),*> Prepend<A> for ($($t,)*) {
+            type Output = (A, $($t,)*);
+
+            #[allow(non_snake_case)]
+            fn prepend(self, head: A) -> Self::Output {
+                let ($($t,)*) = self;
+                (head, $($t,)*)
+            }
+        }
+    };
+}
+
+impl_prepend!();
+impl_prepend!(T1);
+impl_prepend!(T1, T2);
+impl_prepend!(T1, T2, T3);
+impl_prepend!(T1, T2, T3, T4);
+impl_prepend!(T1, T2, T3, T4, T5);
+impl_prepend!(T1, T2, T3, T4, T5, T6);
+impl_prepend!(T1, T2, T3, T4, T5, T6, T7);
+
+/// The iterator over key-value pairs in a collection, with keys flattened.
+///
+/// See [`IterableAccessor::flat_pairs`](super::IterableAccessor::flat_pairs).
+pub struct FlatPairs<S, I> {
+    pub(super) inner: StorableIter<S, I>,
+}
+
+impl<S, I> Iterator for FlatPairs<S, I>
+where
+    S: Storable,
+    S::Key: FlattenKey,
+    I: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+{
+    type Item = Result<
+        (<S::Key as FlattenKey>::Flat, S::Value),
+        KVDecodeError<S::KeyDecodeError, S::ValueDecodeError>,
+    >;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|res| res.map(|(key, value)| (key.flatten(), value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flatten() {
+        assert_eq!(().flatten(), ());
+        assert_eq!((1u8, ()).flatten(), (1u8,));
+        assert_eq!((1u8, (2u16, (3u64, ()))).flatten(), (1u8, 2u16, 3u64));
+        assert_eq!((1u8, (2u16, 3u32)).flatten(), (1u8, 2u16, 3u32));
+    }
+}
@@ -1,8 +1,8 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::Bound};
 
 use crate::encoding::{DecodableWith, EncodableWith, Encoding};
 use crate::storage::StorageBranch;
-use crate::storage::{Storage, StorageMut};
+use crate::storage::{IterableStorage, Storage, StorageMut};
 
 use super::common::TryGetError;
 use super::{Storable, Terminal};
@@ -37,6 +37,7 @@ where
     /// Create a new item with the given key.
     ///
     /// It is the responsibility of the caller to ensure that the key is unique.
+    /// The [`namespace!`](crate::namespace) macro can be used to have the compiler check it.
     pub const fn new(key: u8) -> Self {
         Self {
             key,
@@ -64,6 +65,48 @@ where
     pub fn access<S>(&self, storage: S) -> ItemAccess<E, T, StorageBranch<S>> {
         Self::access_impl(StorageBranch::new(storage, vec![self.key]))
     }
+
+    /// Acquires an accessor for the item, rooted under an existing [`StorageBranch`].
+    ///
+    /// The item's own key is applied on top of the branch's prefix. This makes it possible
+    /// to place containers under computed namespaces (e.g. versions or epochs) without going
+    /// through a parent [`Map`](crate::containers::Map).
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Item;
+    /// use storey::storage::{Storage as _, StorageBranch};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    ///
+    /// let epoch = StorageBranch::new(&mut storage, b"epoch-7/".to_vec());
+    /// item.access_at(epoch).set(&1337).unwrap();
+    ///
+    /// assert_eq!(storage.get(b"epoch-7/\x00"), Some(1337u64.to_le_bytes().to_vec()));
+    /// ```
+    pub fn access_at<S>(&self, branch: StorageBranch<S>) -> ItemAccess<E, T, StorageBranch<S>> {
+        Self::access_impl(branch.nest(&[self.key]))
+    }
+
+    /// Encodes a value the same way the item does when storing it.
+    ///
+    /// This is the counterpart of [`Storable::decode_value`], and can be used to construct
+    /// raw storage entries without a storage backend.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// use storey::containers::{Item, Storable as _};
+    ///
+    /// let bytes = Item::<u64, TestEncoding>::encode_value(&42).unwrap();
+    /// assert_eq!(Item::<u64, TestEncoding>::decode_value(&bytes).unwrap(), 42);
+    /// ```
+    pub fn encode_value(value: &T) -> Result<Vec<u8>, E::EncodeError> {
+        value.encode()
+    }
 }
 
 impl<T, E> Storable for Item<T, E>
@@ -89,7 +132,7 @@ where
         if key.is_empty() {
             Ok(())
         } else {
-            Err(ItemKeyDecodeError)
+            Err(ItemKeyDecodeError { key: key.to_vec() })
         }
     }
 
@@ -98,18 +141,95 @@ where
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
-#[error("invalid key length, expected empty key")]
-pub struct ItemKeyDecodeError;
+/// An error returned when an [`Item`] is found under a non-empty key.
+///
+/// The offending key (relative to the item's namespace) is included for diagnostics.
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[error("invalid key length, expected empty key, got {} bytes", key.len())]
+pub struct ItemKeyDecodeError {
+    /// The key that failed to decode.
+    pub key: Vec<u8>,
+}
+
+/// The state of an [`Item`] in storage, as returned by [`ItemAccess::get_state`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ItemState<T, E> {
+    /// Nothing is stored.
+    Absent,
+    /// A value is stored and decodes fine.
+    Present(T),
+    /// A value is stored, but it can't be decoded.
+    Corrupt(E),
+}
 
 /// An accessor for an `Item`.
 ///
 /// This type provides methods to get and set the value of the item.
+///
+/// # Threading an accessor through helpers
+///
+/// An accessor owns its (borrowed) storage, so it can be moved into helper functions and
+/// returned from them. The `and_*` methods take and return the accessor by value, so a
+/// sequence of mutations can be chained. Once done, [`into_storage`](Self::into_storage)
+/// hands the storage back.
+///
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{Item, ItemAccess};
+/// use storey::storage::StorageBranch;
+///
+/// type Counter<'a> = ItemAccess<TestEncoding, u64, StorageBranch<&'a mut TestStorage>>;
+///
+/// fn bump(counter: Counter<'_>) -> Counter<'_> {
+///     counter.and_update(|v| Some(v.unwrap_or(0) + 1)).unwrap()
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// let item = Item::<u64, TestEncoding>::new(0);
+///
+/// let counter = item.access(&mut storage).and_set(&10).unwrap();
+/// let counter = bump(bump(counter));
+/// assert_eq!(counter.get().unwrap(), Some(12));
+///
+/// let storage = counter.into_storage().into_inner();
+/// assert_eq!(item.access(&*storage).get().unwrap(), Some(12));
+/// ```
 pub struct ItemAccess<E, T, S> {
     storage: S,
     phantom: PhantomData<(E, T)>,
 }
 
+impl<E, T, S> ItemAccess<E, T, S> {
+    /// Consumes the accessor, returning the storage it wraps.
+    pub fn into_storage(self) -> S {
+        self.storage
+    }
+}
+
+impl<E, T, S> ItemAccess<E, T, StorageBranch<S>> {
+    /// Returns the key the item is stored under. See [`StorageBranch::prefix`].
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(3);
+    /// assert_eq!(item.access(&storage).prefix(), &[3]);
+    ///
+    /// // inside a map, the key is relative to the map's namespace
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// assert_eq!(map.access(&storage).entry("foo").prefix(), b"foo");
+    /// ```
+    pub fn prefix(&self) -> &[u8] {
+        self.storage.prefix()
+    }
+}
+
 impl<E, T, S> ItemAccess<E, T, S>
 where
     E: Encoding,
@@ -120,6 +240,9 @@ where
     ///
     /// Returns `Ok(None)` if the item doesn't exist (has not been set yet).
     ///
+    /// Only the item's own key is read. Use [`check_layout`](Self::check_layout) to make
+    /// sure no other data ended up in the item's namespace.
+    ///
     /// # Examples
     /// ```
     /// # use mocks::encoding::TestEncoding;
@@ -151,6 +274,94 @@ where
             .transpose()
     }
 
+    /// Get the state of the item: absent, present or corrupt.
+    ///
+    /// Unlike [`get`](Self::get), a value that fails to decode isn't an error, but just one of
+    /// the possible outcomes. This is useful when corruption should be handled rather than
+    /// propagated with `?`, e.g. during a migration.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, ItemState};
+    /// use storey::storage::StorageMut as _;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    ///
+    /// assert_eq!(item.access(&storage).get_state(), ItemState::Absent);
+    ///
+    /// item.access(&mut storage).set(&42).unwrap();
+    /// assert_eq!(item.access(&storage).get_state(), ItemState::Present(42));
+    ///
+    /// storage.set(&[0], b"junk");
+    /// assert!(matches!(item.access(&storage).get_state(), ItemState::Corrupt(_)));
+    /// ```
+    pub fn get_state(&self) -> ItemState<T, E::DecodeError> {
+        match self.storage.get(&[]) {
+            None => ItemState::Absent,
+            Some(bytes) => match T::decode(&bytes) {
+                Ok(value) => ItemState::Present(value),
+                Err(e) => ItemState::Corrupt(e),
+            },
+        }
+    }
+
+    /// Get the value of the item, decoding it with the encoding `E2` instead of the item's own.
+    ///
+    /// This is meant for encoding migrations: legacy data can be read with the old encoding
+    /// and written back with [`set`](Self::set), which always uses the current one.
+    ///
+    /// Returns `Ok(None)` if the item doesn't exist (has not been set yet).
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// # use storey::encoding::{Cover, DecodableWithImpl, EncodableWithImpl, Encoding};
+    /// use storey::containers::Item;
+    ///
+    /// // an older encoding, storing numbers big-endian
+    /// struct LegacyEncoding;
+    /// # impl Encoding for LegacyEncoding {
+    /// #     type EncodeError = String;
+    /// #     type DecodeError = String;
+    /// # }
+    /// # impl EncodableWithImpl<LegacyEncoding> for Cover<&u64> {
+    /// #     fn encode_impl(self) -> Result<Vec<u8>, String> {
+    /// #         Ok(self.0.to_be_bytes().to_vec())
+    /// #     }
+    /// # }
+    /// # impl DecodableWithImpl<LegacyEncoding> for Cover<u64> {
+    /// #     fn decode_impl(data: &[u8]) -> Result<Self, String> {
+    /// #         let bytes = data.try_into().map_err(|_| "expected 8 bytes".to_string())?;
+    /// #         Ok(Cover(u64::from_be_bytes(bytes)))
+    /// #     }
+    /// # }
+    ///
+    /// let mut storage = TestStorage::new();
+    /// Item::<u64, LegacyEncoding>::new(0).access(&mut storage).set(&42).unwrap();
+    ///
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    /// let mut access = item.access(&mut storage);
+    /// assert_ne!(access.get().unwrap(), Some(42)); // garbled
+    ///
+    /// let legacy = access.get_with::<LegacyEncoding>().unwrap().unwrap();
+    /// access.set(&legacy).unwrap();
+    /// assert_eq!(access.get().unwrap(), Some(42));
+    /// ```
+    pub fn get_with<E2>(&self) -> Result<Option<T>, E2::DecodeError>
+    where
+        E2: Encoding,
+        T: DecodableWith<E2>,
+    {
+        self.storage
+            .get(&[])
+            .map(|bytes| <T as DecodableWith<E2>>::decode(&bytes))
+            .transpose()
+    }
+
     /// Get the value of the item.
     ///
     /// Returns [`TryGetError::Empty`] if the item doesn't exist (has not been
@@ -206,6 +417,202 @@ where
     pub fn get_or(&self, default: T) -> Result<T, E::DecodeError> {
         self.get().map(|opt| opt.unwrap_or(default))
     }
+
+    /// Get the value of the item or `T::default()` if it doesn't exist.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    ///
+    /// assert_eq!(item.access(&storage).get_or_default().unwrap(), 0);
+    /// ```
+    pub fn get_or_default(&self) -> Result<T, E::DecodeError>
+    where
+        T: Default,
+    {
+        self.get().map(Option::unwrap_or_default)
+    }
+
+    /// Get the value of the item, transformed with `f`.
+    ///
+    /// This is a shorthand for `get()` followed by [`Option::map`], handy for extracting a
+    /// single field of a larger value.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    ///
+    /// assert_eq!(item.access(&storage).get_map(|v| v * 2).unwrap(), None);
+    ///
+    /// item.access(&mut storage).set(&21).unwrap();
+    /// assert_eq!(item.access(&storage).get_map(|v| v * 2).unwrap(), Some(42));
+    /// ```
+    pub fn get_map<U, F>(&self, f: F) -> Result<Option<U>, E::DecodeError>
+    where
+        F: FnOnce(T) -> U,
+    {
+        self.get().map(|opt| opt.map(f))
+    }
+
+    /// Get the value of the item, transformed with `f`.
+    ///
+    /// Like [`try_get`](Self::try_get), this returns [`TryGetError::Empty`] if the item
+    /// doesn't exist.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    ///
+    /// assert!(item.access(&storage).try_get_map(|v| v * 2).is_err());
+    ///
+    /// item.access(&mut storage).set(&21).unwrap();
+    /// assert_eq!(item.access(&storage).try_get_map(|v| v * 2).unwrap(), 42);
+    /// ```
+    pub fn try_get_map<U, F>(&self, f: F) -> Result<U, TryGetError<E::DecodeError>>
+    where
+        F: FnOnce(T) -> U,
+    {
+        self.try_get().map(f)
+    }
+
+    /// Returns the number of bytes the stored value takes up, or `None` if the item isn't set.
+    ///
+    /// This doesn't decode the value, and backends that track value lengths can answer without
+    /// fetching it at all. See [`Storage::value_len`].
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    ///
+    /// assert_eq!(item.access(&storage).byte_len(), None);
+    ///
+    /// item.access(&mut storage).set(&1337).unwrap();
+    /// assert_eq!(item.access(&storage).byte_len(), Some(8));
+    /// ```
+    pub fn byte_len(&self) -> Option<usize> {
+        self.storage.value_len(&[])
+    }
+
+    /// Returns the number of bytes `value` would take up once stored, if the encoding can
+    /// tell without actually encoding it.
+    ///
+    /// This is meant for budgeting (e.g. gas estimation) before committing a write.
+    /// `None` means the size is unknown, in which case the value has to be encoded
+    /// to find out.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    ///
+    /// assert_eq!(item.access(&storage).size_hint(&1337), Some(8));
+    /// ```
+    pub fn size_hint(&self, value: &T) -> Option<usize> {
+        value.encoded_len()
+    }
+
+    /// Hash the raw stored bytes of the item with the digest `D`.
+    ///
+    /// The bytes are hashed as they are in the backend, without decoding them, so the result
+    /// only depends on what's stored. Returns `None` if the item doesn't exist.
+    ///
+    /// Only available with the `digest` feature enabled.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use sha2::{Digest, Sha256};
+    /// use storey::containers::Item;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    ///
+    /// assert_eq!(item.access(&storage).hash::<Sha256>(), None);
+    ///
+    /// item.access(&mut storage).set(&42).unwrap();
+    /// assert_eq!(
+    ///     item.access(&storage).hash::<Sha256>(),
+    ///     Some(Sha256::digest(42u64.to_le_bytes()))
+    /// );
+    /// ```
+    #[cfg(feature = "digest")]
+    pub fn hash<D: digest::Digest>(&self) -> Option<digest::Output<D>> {
+        self.storage.get(&[]).map(D::digest)
+    }
+}
+
+impl<E, T, S> ItemAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+{
+    /// Checks that nothing but the item itself is stored in its namespace.
+    ///
+    /// An item only ever uses the empty key under its prefix. Any other key found there
+    /// means the storage layout is broken - most likely another container was given
+    /// an overlapping prefix. The first such key is returned in the error.
+    ///
+    /// [`get`](Self::get) doesn't perform this check, since it would require iteration.
+    ///
+    /// The check can only tell stray keys apart when no other entry's namespace starts with
+    /// the item's prefix - which holds for a top-level item, and for an item in a map with
+    /// fixed-size or framed keys. An item in a map whose dynamic keys are stored unframed, like
+    /// `Map<String, Item<_, _>>`, isn't covered: the entry `"foo"` sees its sibling `"foobar"`
+    /// as the stray key `"bar"`.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0); // oops, same prefix
+    ///
+    /// item.access(&mut storage).set(&1).unwrap();
+    /// assert!(item.access(&storage).check_layout().is_ok());
+    ///
+    /// map.access(&mut storage).entry_mut("foo").set(&2).unwrap();
+    /// let err = item.access(&storage).check_layout().unwrap_err();
+    /// assert_eq!(err.key, b"foo");
+    /// ```
+    pub fn check_layout(&self) -> Result<(), ItemKeyDecodeError> {
+        match self
+            .storage
+            .keys(Bound::Excluded(&[]), Bound::Unbounded)
+            .next()
+        {
+            Some(key) => Err(ItemKeyDecodeError { key }),
+            None => Ok(()),
+        }
+    }
 }
 
 impl<E, T, S> ItemAccess<E, T, S>
@@ -234,6 +641,70 @@ where
         Ok(())
     }
 
+    /// Set the value of the item, but only if its current value is `expected`.
+    ///
+    /// An `expected` value of `None` means the item must not exist yet. Returns whether the
+    /// value was written.
+    ///
+    /// Values are compared in their encoded form, so this relies on the encoding being
+    /// deterministic.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    /// let mut access = item.access(&mut storage);
+    ///
+    /// assert!(access.compare_and_set(None, &1).unwrap());
+    /// assert!(!access.compare_and_set(None, &2).unwrap());
+    /// assert!(access.compare_and_set(Some(&1), &3).unwrap());
+    /// assert_eq!(access.get().unwrap(), Some(3));
+    /// ```
+    pub fn compare_and_set(
+        &mut self,
+        expected: Option<&T>,
+        new: &T,
+    ) -> Result<bool, E::EncodeError> {
+        let expected = expected.map(|value| value.encode()).transpose()?;
+        let new = new.encode()?;
+
+        Ok(self.storage.compare_and_set(&[], expected.as_deref(), &new))
+    }
+
+    /// Set the value of the item, but only if it isn't set yet. Returns whether the value was
+    /// written.
+    ///
+    /// This is meant for one-time initialization (e.g. of a contract's config), where running
+    /// the initialization again must not overwrite existing state. The stored value is never
+    /// decoded, so this works even if it's corrupt.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    /// let mut access = item.access(&mut storage);
+    ///
+    /// assert!(access.set_if_absent(&1).unwrap());
+    /// assert!(!access.set_if_absent(&2).unwrap());
+    /// assert_eq!(access.get().unwrap(), Some(1));
+    /// ```
+    pub fn set_if_absent(&mut self, value: &T) -> Result<bool, E::EncodeError> {
+        if self.storage.has(&[]) {
+            return Ok(false);
+        }
+
+        self.set(value)?;
+        Ok(true)
+    }
+
     /// Update the value of the item.
     ///
     /// The function `f` is called with the current value of the item, if it exists.
@@ -285,9 +756,56 @@ where
     pub fn remove(&mut self) {
         self.storage.remove(&[]);
     }
+
+    /// Like [`set`](Self::set), but takes and returns the accessor for chaining.
+    pub fn and_set(mut self, value: &T) -> Result<Self, E::EncodeError> {
+        self.set(value)?;
+        Ok(self)
+    }
+
+    /// Like [`update`](Self::update), but takes and returns the accessor for chaining.
+    pub fn and_update<F>(
+        mut self,
+        f: F,
+    ) -> Result<Self, UpdateError<E::DecodeError, E::EncodeError>>
+    where
+        F: FnOnce(Option<T>) -> Option<T>,
+    {
+        self.update(f)?;
+        Ok(self)
+    }
+
+    /// Like [`remove`](Self::remove), but takes and returns the accessor for chaining.
+    pub fn and_remove(mut self) -> Self {
+        self.remove();
+        self
+    }
+
+    /// Remove the value of the item, returning whether there was a value to remove.
+    ///
+    /// This costs an extra read compared to [`remove`](Self::remove).
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Item;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    /// let mut access = item.access(&mut storage);
+    ///
+    /// access.set(&42).unwrap();
+    /// assert!(access.remove_existing());
+    /// assert!(!access.remove_existing());
+    /// ```
+    pub fn remove_existing(&mut self) -> bool {
+        self.storage.remove_existing(&[])
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UpdateError<D, E> {
     #[error("decode error: {0}")]
     Decode(D),
@@ -299,6 +817,8 @@ pub enum UpdateError<D, E> {
 mod tests {
     use super::*;
 
+    use crate::containers::Map;
+
     use mocks::backend::TestStorage;
     use mocks::encoding::TestEncoding;
 
@@ -333,4 +853,136 @@ mod tests {
         item.access(&mut storage).update(|_| None).unwrap();
         assert_eq!(item.access(&storage).get().unwrap(), None);
     }
+
+    #[test]
+    fn key_decode_error() {
+        assert_eq!(Item::<u64, TestEncoding>::decode_key(&[]), Ok(()));
+
+        let err = Item::<u64, TestEncoding>::decode_key(&[1, 2, 3]).unwrap_err();
+        assert_eq!(err.key, vec![1, 2, 3]);
+        assert_eq!(
+            err.to_string(),
+            "invalid key length, expected empty key, got 3 bytes"
+        );
+    }
+
+    #[test]
+    fn check_layout() {
+        let mut storage = TestStorage::new();
+
+        let item = Item::<u64, TestEncoding>::new(0);
+        assert_eq!(item.access(&storage).check_layout(), Ok(()));
+
+        item.access(&mut storage).set(&42).unwrap();
+        storage.set(&[1, 0], b"unrelated");
+        assert_eq!(item.access(&storage).check_layout(), Ok(()));
+
+        storage.set(&[0, 0], b"stray");
+        assert_eq!(
+            item.access(&storage).check_layout(),
+            Err(ItemKeyDecodeError { key: vec![0] })
+        );
+    }
+
+    #[test]
+    fn check_layout_in_map() {
+        let mut storage = TestStorage::new();
+
+        // fixed-size keys - neighbouring entries never share a namespace
+        let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+        access.entry_mut(&1).set(&1).unwrap();
+        access.entry_mut(&2).set(&2).unwrap();
+        assert_eq!(map.access(&storage).entry(&1).check_layout(), Ok(()));
+
+        // unframed dynamic keys - a sibling sharing the prefix can't be told apart
+        let map = Map::<String, Item<u64, TestEncoding>>::new(1);
+        let mut access = map.access(&mut storage);
+        access.entry_mut("foo").set(&1).unwrap();
+        access.entry_mut("foobar").set(&2).unwrap();
+        assert_eq!(
+            map.access(&storage).entry("foo").check_layout(),
+            Err(ItemKeyDecodeError {
+                key: b"bar".to_vec()
+            })
+        );
+        assert_eq!(map.access(&storage).entry("foobar").check_layout(), Ok(()));
+    }
+
+    #[test]
+    fn compare_and_set() {
+        let mut storage = TestStorage::new();
+
+        let item = Item::<u64, TestEncoding>::new(0);
+        let mut access = item.access(&mut storage);
+
+        assert!(!access.compare_and_set(Some(&1), &2).unwrap());
+        assert_eq!(access.get().unwrap(), None);
+
+        assert!(access.compare_and_set(None, &1).unwrap());
+        assert!(!access.compare_and_set(Some(&5), &2).unwrap());
+        assert!(!access.compare_and_set(None, &2).unwrap());
+        assert_eq!(access.get().unwrap(), Some(1));
+
+        assert!(access.compare_and_set(Some(&1), &2).unwrap());
+        assert_eq!(access.get().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn set_if_absent() {
+        let mut storage = TestStorage::new();
+
+        let item = Item::<u64, TestEncoding>::new(0);
+        let mut access = item.access(&mut storage);
+
+        assert!(access.set_if_absent(&1).unwrap());
+        assert!(!access.set_if_absent(&2).unwrap());
+        assert_eq!(access.get().unwrap(), Some(1));
+
+        // a corrupt value still counts as present
+        access.storage.set(&[], &[1, 2, 3]);
+        assert!(!access.set_if_absent(&3).unwrap());
+        assert_eq!(access.storage.get(&[]), Some(vec![1, 2, 3]));
+
+        access.remove();
+        assert!(access.set_if_absent(&4).unwrap());
+        assert_eq!(access.get().unwrap(), Some(4));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn errors_roundtrip_through_serde() {
+        use crate::containers::KVDecodeError;
+
+        let err: TryGetError<String> = TryGetError::Decode("bad".to_string());
+        let json = serde_json::to_string(&err).unwrap();
+        assert_eq!(json, r#"{"Decode":"bad"}"#);
+        assert_eq!(
+            serde_json::from_str::<TryGetError<String>>(&json).unwrap(),
+            err
+        );
+
+        let err = ItemKeyDecodeError { key: vec![1, 2] };
+        let json = serde_json::to_string(&err).unwrap();
+        assert_eq!(json, r#"{"key":[1,2]}"#);
+        assert_eq!(
+            serde_json::from_str::<ItemKeyDecodeError>(&json).unwrap(),
+            err
+        );
+
+        let err: KVDecodeError<ItemKeyDecodeError, String> =
+            KVDecodeError::Value("bad".to_string());
+        let json = serde_json::to_string(&err).unwrap();
+        assert_eq!(
+            serde_json::from_str::<KVDecodeError<ItemKeyDecodeError, String>>(&json).unwrap(),
+            err
+        );
+
+        let err: UpdateError<String, String> = UpdateError::Encode("bad".to_string());
+        let json = serde_json::to_string(&err).unwrap();
+        assert_eq!(
+            serde_json::from_str::<UpdateError<String, String>>(&json).unwrap(),
+            err
+        );
+    }
 }
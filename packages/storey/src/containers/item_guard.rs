@@ -0,0 +1,155 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::encoding::{DecodableWith, EncodableWith, Encoding};
+use crate::storage::{Storage, StorageMut};
+
+use super::ItemAccess;
+
+/// A decoded item value that's written back to storage when the guard is dropped.
+///
+/// The guard dereferences to the value. Only a mutable dereference marks it as modified -
+/// if the value was merely read, dropping the guard doesn't write anything.
+///
+/// # Panics
+///
+/// Dropping a modified guard panics if the value fails to encode. To handle encoding errors,
+/// call [`save`](Self::save) instead of letting the guard go out of scope. Nothing is written
+/// if the guard is dropped while the thread is already panicking.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{Item, Map};
+///
+/// let mut storage = TestStorage::new();
+/// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+/// let mut access = map.access(&mut storage);
+///
+/// access.entry_mut("foo").set(&1).unwrap();
+///
+/// *access.get_mut("foo").unwrap().unwrap() += 1;
+/// assert_eq!(access.entry("foo").get().unwrap(), Some(2));
+///
+/// let mut value = access.get_mut("foo").unwrap().unwrap();
+/// *value *= 10;
+/// value.save().unwrap();
+/// assert_eq!(access.entry("foo").get().unwrap(), Some(20));
+/// ```
+pub struct ItemGuard<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+{
+    access: ItemAccess<E, T, S>,
+    value: T,
+    dirty: bool,
+}
+
+impl<E, T, S> ItemGuard<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+{
+    /// Reads the item, returning `Ok(None)` if it doesn't exist.
+    pub(crate) fn new(access: ItemAccess<E, T, S>) -> Result<Option<Self>, E::DecodeError> {
+        Ok(access.get()?.map(|value| Self {
+            access,
+            value,
+            dirty: false,
+        }))
+    }
+
+    /// Writes the value back if it was modified, consuming the guard.
+    pub fn save(mut self) -> Result<(), E::EncodeError> {
+        if self.dirty {
+            self.dirty = false;
+            self.access.set(&self.value)?;
+        }
+        Ok(())
+    }
+
+    /// Drops the guard without writing anything, discarding any modifications.
+    pub fn discard(mut self) {
+        self.dirty = false;
+    }
+}
+
+impl<E, T, S> Deref for ItemGuard<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<E, T, S> DerefMut for ItemGuard<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        self.dirty = true;
+        &mut self.value
+    }
+}
+
+impl<E, T, S> Drop for ItemGuard<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+{
+    fn drop(&mut self) {
+        if self.dirty && !std::thread::panicking() {
+            self.access
+                .set(&self.value)
+                .unwrap_or_else(|_| panic!("failed to encode a modified item value"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::containers::{Item, Map};
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+
+    #[test]
+    fn writes_back_on_drop() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+        assert!(access.get_mut("foo").unwrap().is_none());
+
+        access.entry_mut("foo").set(&1).unwrap();
+
+        {
+            let mut guard = access.get_mut("foo").unwrap().unwrap();
+            assert_eq!(*guard, 1);
+            *guard += 1;
+            assert_eq!(*guard, 2);
+        }
+        assert_eq!(access.entry("foo").get().unwrap(), Some(2));
+
+        let mut guard = access.get_mut("foo").unwrap().unwrap();
+        *guard = 9;
+        guard.discard();
+        assert_eq!(access.entry("foo").get().unwrap(), Some(2));
+
+        let mut guard = access.get_mut("foo").unwrap().unwrap();
+        *guard = 9;
+        guard.save().unwrap();
+        assert_eq!(access.entry("foo").get().unwrap(), Some(9));
+    }
+}
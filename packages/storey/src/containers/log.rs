@@ -0,0 +1,319 @@
+use std::marker::PhantomData;
+use std::ops::Bound;
+
+use crate::encoding::{DecodableWith, EncodableWith, Encoding};
+use crate::storage::{IterableStorage, Storage, StorageBranch, StorageMut};
+
+use super::column::{meta_keys, ColumnIdDecodeError, LenError, PushError, RemoveError};
+use super::{
+    BoundFor, BoundedIterableAccessor, Column, ColumnAccess, IterableAccessor, NonTerminal,
+    Storable, StorableIter,
+};
+
+/// An append-only log of entries, each assigned a sequence number.
+///
+/// This is a good fit for audit trails and event logs. Sequence numbers start at `1` and
+/// strictly increase - they're never reused, even after old entries are pruned with
+/// [`prune_through`](LogAccess::prune_through) or the log is cleared as part of a parent
+/// container. Readers can remember the last sequence number they've seen and pick up from
+/// there with [`iter_since`](LogAccess::iter_since).
+///
+/// The storage layout is the same as that of a [`Column`], so sequence numbers are `u32`s.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::Log;
+///
+/// let mut storage = TestStorage::new();
+/// let log = Log::<u64, TestEncoding>::new(0);
+/// let mut access = log.access(&mut storage);
+///
+/// assert_eq!(access.append(&100).unwrap(), 1);
+/// assert_eq!(access.append(&200).unwrap(), 2);
+/// assert_eq!(access.append(&300).unwrap(), 3);
+///
+/// let since = access.iter_since(1).collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(since, vec![(2, 200), (3, 300)]);
+/// assert_eq!(access.tip().unwrap(), Some(3));
+/// ```
+pub struct Log<T, E> {
+    prefix: u8,
+    phantom: PhantomData<(T, E)>,
+}
+
+impl<T, E> Log<T, E>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+{
+    /// Create a new log associated with the given storage prefix.
+    ///
+    /// It is the responsibility of the user to ensure the prefix is unique and does not conflict
+    /// with other keys in the storage.
+    pub const fn new(prefix: u8) -> Self {
+        Self {
+            prefix,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Acquire an accessor for this log.
+    pub fn access<S>(&self, storage: S) -> LogAccess<E, T, StorageBranch<S>> {
+        Self::access_impl(StorageBranch::new(storage, vec![self.prefix]))
+    }
+
+    /// Acquire an accessor for the log kept under `branch`.
+    ///
+    /// Logs under different branches are independent - each one numbers its entries from 1.
+    pub fn access_at<S>(&self, branch: StorageBranch<S>) -> LogAccess<E, T, StorageBranch<S>> {
+        Self::access_impl(branch.nest(&[self.prefix]))
+    }
+}
+
+impl<T, E> Storable for Log<T, E>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+{
+    type Kind = NonTerminal;
+    type Accessor<S> = LogAccess<E, T, S>;
+    type Key = u32;
+    type KeyDecodeError = ColumnIdDecodeError;
+    type Value = T;
+    type ValueDecodeError = E::DecodeError;
+
+    fn access_impl<S>(storage: S) -> LogAccess<E, T, S> {
+        LogAccess {
+            inner: Column::<T, E>::access_impl(storage),
+        }
+    }
+
+    fn decode_key(key: &[u8]) -> Result<u32, ColumnIdDecodeError> {
+        Column::<T, E>::decode_key(key)
+    }
+
+    fn decode_value(value: &[u8]) -> Result<T, E::DecodeError> {
+        Column::<T, E>::decode_value(value)
+    }
+
+    /// Removes every entry, but keeps the last sequence number so that it's never reused.
+    fn clear_impl<S>(storage: &mut S)
+    where
+        S: Storage + IterableStorage + StorageMut,
+    {
+        super::clear_namespace(storage);
+        storage.remove_meta(meta_keys::META_LEN);
+    }
+}
+
+/// An accessor for a `Log`.
+///
+/// This type provides methods for appending to and reading the log.
+pub struct LogAccess<E, T, S> {
+    inner: ColumnAccess<E, T, S>,
+}
+
+impl<E, T, S> IterableAccessor for LogAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+{
+    type Storable = Log<T, E>;
+    type Storage = S;
+
+    fn storage(&self) -> &Self::Storage {
+        self.inner.storage()
+    }
+}
+
+impl<E, T, S> BoundedIterableAccessor for LogAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+{
+}
+
+impl<T, E> BoundFor<Log<T, E>> for u32 {
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+}
+
+impl<E, T, S> LogAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage,
+{
+    /// Get the entry with the given sequence number, if it exists.
+    pub fn get(&self, seq: u32) -> Result<Option<T>, E::DecodeError> {
+        self.inner.get(seq)
+    }
+
+    /// Returns the sequence number of the last appended entry, or `None` if nothing has been
+    /// appended yet.
+    ///
+    /// The entry itself might have been pruned since.
+    pub fn tip(&self) -> Result<Option<u32>, LenError> {
+        self.inner.last_id()
+    }
+
+    /// Returns the number of entries currently in the log.
+    pub fn len(&self) -> Result<u32, LenError> {
+        self.inner.len()
+    }
+
+    /// Returns whether the log currently holds no entries.
+    pub fn is_empty(&self) -> Result<bool, LenError> {
+        self.inner.is_empty()
+    }
+}
+
+impl<E, T, S> LogAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+{
+    /// Iterate over the `(seq, entry)` pairs appended after `seq`, oldest first.
+    ///
+    /// Pass `0` to iterate over the whole log.
+    pub fn iter_since(&self, seq: u32) -> StorableIter<Log<T, E>, S::PairsIterator<'_>> {
+        self.bounded_pairs(Bound::Excluded(seq), Bound::Unbounded)
+    }
+}
+
+impl<E, T, S> LogAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+{
+    /// Append an entry to the log, returning its sequence number.
+    pub fn append(&mut self, entry: &T) -> Result<u32, PushError<E::EncodeError>> {
+        self.inner.push(entry)
+    }
+}
+
+impl<E, T, S> LogAccess<E, T, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + IterableStorage + StorageMut,
+{
+    /// Remove all entries with sequence numbers up to and including `seq`, returning the
+    /// number of entries removed.
+    ///
+    /// This doesn't affect the sequence numbers of entries appended later. Fails if the
+    /// log's bookkeeping doesn't match its entries.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Log;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let log = Log::<u64, TestEncoding>::new(0);
+    /// let mut access = log.access(&mut storage);
+    ///
+    /// access.append(&100).unwrap();
+    /// access.append(&200).unwrap();
+    ///
+    /// assert_eq!(access.prune_through(2).unwrap(), 2);
+    /// assert_eq!(access.len().unwrap(), 0);
+    /// assert_eq!(access.append(&300).unwrap(), 3);
+    /// ```
+    pub fn prune_through(&mut self, seq: u32) -> Result<usize, RemoveError> {
+        let pruned = self
+            .inner
+            .storage()
+            .keys(Bound::Unbounded, Bound::Included(&seq.to_be_bytes()))
+            .collect::<Vec<_>>();
+
+        for key in &pruned {
+            let seq =
+                Column::<T, E>::decode_key(key).map_err(|_| RemoveError::InconsistentState)?;
+            self.inner.remove(seq)?;
+        }
+
+        Ok(pruned.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::Map;
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+
+    #[test]
+    fn sequence_survives_pruning_and_clearing() {
+        let mut storage = TestStorage::new();
+
+        let logs = Map::<String, Log<u64, TestEncoding>>::new(0);
+        let mut access = logs.access(&mut storage);
+        let mut log = access.entry_mut("alice");
+
+        assert_eq!(log.tip(), Ok(None));
+        assert_eq!(log.append(&1).unwrap(), 1);
+        assert_eq!(log.append(&2).unwrap(), 2);
+        assert_eq!(log.append(&3).unwrap(), 3);
+
+        assert_eq!(log.prune_through(1), Ok(1));
+        assert_eq!(log.prune_through(1), Ok(0));
+        assert_eq!(log.len().unwrap(), 2);
+        assert_eq!(log.get(1).unwrap(), None);
+
+        let entries = log.iter_since(0).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(entries, vec![(2, 2), (3, 3)]);
+        assert_eq!(log.iter_since(3).count(), 0);
+
+        access.clear();
+        let mut log = access.entry_mut("alice");
+        assert!(log.is_empty().unwrap());
+        assert_eq!(log.tip(), Ok(Some(3)));
+        assert_eq!(log.append(&4).unwrap(), 4);
+    }
+
+    #[test]
+    fn corrupted_bookkeeping() {
+        let mut storage = TestStorage::new();
+
+        let log = Log::<u64, TestEncoding>::new(0);
+        log.access(&mut storage).append(&1).unwrap();
+
+        let mut branch = StorageBranch::new(&mut storage, vec![0]);
+        branch.remove_meta(meta_keys::META_LEN);
+        branch.set_meta(meta_keys::META_LAST_ID, &[1]);
+
+        let mut access = log.access(&mut storage);
+        assert_eq!(access.tip(), Err(LenError::InconsistentState));
+        assert_eq!(access.prune_through(1), Err(RemoveError::InconsistentState));
+    }
+
+    #[test]
+    fn access_at() {
+        let mut storage = TestStorage::new();
+
+        let log = Log::<u64, TestEncoding>::new(0);
+        let mut a = log.access_at(StorageBranch::new(&mut storage, b"a/".to_vec()));
+        assert_eq!(a.append(&10).unwrap(), 1);
+        assert_eq!(a.append(&20).unwrap(), 2);
+
+        let mut b = log.access_at(StorageBranch::new(&mut storage, b"b/".to_vec()));
+        assert_eq!(b.append(&30).unwrap(), 1);
+
+        let a = log.access_at(StorageBranch::new(&storage, b"a/".to_vec()));
+        assert_eq!(a.len(), Ok(2));
+        assert_eq!(a.get(1), Ok(Some(10)));
+        assert!(log.access(&storage).is_empty().unwrap());
+    }
+}
@@ -0,0 +1,330 @@
+use std::{marker::PhantomData, ops::Bound};
+
+use crate::containers::Storable;
+use crate::storage::{prefix_successor, IterableStorage, StorageBranch};
+
+use super::key_encoding::{KeyEncoding, KeyEncodingT};
+use super::{split_key, ByteLenPrefix, KeyFraming, MapAccess, MapKeyDecodeError, OwnedKey};
+
+impl<K, V, S, F> MapAccess<K, V, S, F>
+where
+    K: OwnedKey,
+    V: Storable,
+    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    S: IterableStorage,
+    F: KeyFraming,
+    (K::Kind, V::Kind): KeyEncodingT,
+{
+    /// Iterate over the distinct keys of this map, without visiting every entry
+    /// of the inner containers.
+    ///
+    /// For a map of maps (or other non-terminal containers), this yields each outer key once,
+    /// no matter how many entries are stored under it. After a key is found, the iterator
+    /// seeks directly past its whole namespace, so the cost is proportional to the number of
+    /// distinct keys rather than the total number of entries.
+    ///
+    /// For a map of terminal values (e.g. [`Item`](crate::containers::Item)), this is
+    /// equivalent to iterating over keys.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Map<u64, Item<u64, TestEncoding>>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("alice").entry_mut(&1).set(&10).unwrap();
+    /// access.entry_mut("alice").entry_mut(&2).set(&20).unwrap();
+    /// access.entry_mut("carol").entry_mut(&1).set(&30).unwrap();
+    ///
+    /// let owners = access.group_keys().collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(owners, vec!["alice".to_string(), "carol".to_string()]);
+    /// ```
+    pub fn group_keys(&self) -> GroupKeys<'_, K, V, S, F> {
+        GroupKeys {
+            storage: &self.storage,
+            start: Some(Bound::Unbounded),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Iterate over the distinct keys of this map, along with an accessor for the inner
+    /// container stored under each one.
+    ///
+    /// This visits keys the same way [`group_keys`](Self::group_keys) does. The accessors
+    /// borrow the storage immutably - to modify the inner containers, use
+    /// [`for_each_entry_mut`](Self::for_each_entry_mut).
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Column, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let queues = Map::<String, Column<u64, TestEncoding>>::new(0);
+    /// let mut access = queues.access(&mut storage);
+    ///
+    /// access.entry_mut("alice").push(&1).unwrap();
+    /// access.entry_mut("alice").push(&2).unwrap();
+    /// access.entry_mut("carol").push(&3).unwrap();
+    ///
+    /// let lens = access
+    ///     .entries()
+    ///     .map(|entry| entry.map(|(user, queue)| (user, queue.len().unwrap())))
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(lens, vec![("alice".to_string(), 2), ("carol".to_string(), 1)]);
+    /// ```
+    pub fn entries(&self) -> Entries<'_, K, V, S, F> {
+        Entries {
+            keys: self.group_keys(),
+        }
+    }
+
+    /// Call `f` with every distinct key of this map and a mutable accessor for the inner
+    /// container stored under it.
+    ///
+    /// The keys are all collected before `f` is first called, so `f` is free to modify (or
+    /// clear) the inner containers. If a key can't be decoded, the error is returned and `f`
+    /// is never called.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Column, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let queues = Map::<String, Column<u64, TestEncoding>>::new(0);
+    /// let mut access = queues.access(&mut storage);
+    ///
+    /// access.entry_mut("alice").push(&1).unwrap();
+    /// access.entry_mut("carol").push(&2).unwrap();
+    ///
+    /// let mut drained = Vec::new();
+    /// access
+    ///     .for_each_entry_mut(|user, mut queue| {
+    ///         drained.push((user, queue.get(1).unwrap().unwrap()));
+    ///         queue.clear();
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(drained, vec![("alice".to_string(), 1), ("carol".to_string(), 2)]);
+    /// assert!(access.entry("alice").is_empty().unwrap());
+    /// ```
+    pub fn for_each_entry_mut<M>(
+        &mut self,
+        mut f: M,
+    ) -> Result<(), MapKeyDecodeError<V::KeyDecodeError>>
+    where
+        M: FnMut(K, V::Accessor<StorageBranch<&mut S>>),
+    {
+        let mut keys = self.group_keys();
+        let entries =
+            std::iter::from_fn(|| keys.next_namespace()).collect::<Result<Vec<_>, _>>()?;
+
+        for (key, namespace) in entries {
+            f(
+                key,
+                V::access_impl(StorageBranch::new(&mut self.storage, namespace)),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// An iterator over the distinct keys of a map. See [`MapAccess::group_keys`].
+pub struct GroupKeys<'a, K, V, S, F = ByteLenPrefix> {
+    storage: &'a S,
+    start: Option<Bound<Vec<u8>>>,
+    phantom: PhantomData<(K, V, F)>,
+}
+
+impl<'a, K, V, S, F> GroupKeys<'a, K, V, S, F>
+where
+    K: OwnedKey,
+    V: Storable,
+    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    S: IterableStorage,
+    F: KeyFraming,
+    (K::Kind, V::Kind): KeyEncodingT,
+{
+    /// Finds the next distinct key, returning it along with the raw (framed) key that
+    /// prefixes its inner container.
+    #[allow(clippy::type_complexity)]
+    fn next_namespace(
+        &mut self,
+    ) -> Option<Result<(K, Vec<u8>), MapKeyDecodeError<V::KeyDecodeError>>> {
+        let start = self.start.take()?;
+        let key = self
+            .storage
+            .keys(start.as_ref().map(Vec::as_slice), Bound::Unbounded)
+            .next()?;
+
+        let (map_key, rest) = match split_key::<F, _>(<(K::Kind, V::Kind)>::BEHAVIOR, &key) {
+            Ok(split) => split,
+            Err(e) => {
+                self.start = Some(Bound::Excluded(key.clone()));
+                return Some(Err(e));
+            }
+        };
+        let namespace = &key[..key.len() - rest.len()];
+
+        self.start = match <(K::Kind, V::Kind)>::BEHAVIOR {
+            // The key is not self-delimiting, so there's no namespace to skip.
+            KeyEncoding::UseRest | KeyEncoding::UseExact(_) => Some(Bound::Excluded(key.clone())),
+            _ => prefix_successor(namespace).map(Bound::Included),
+        };
+
+        Some(
+            K::from_bytes(map_key)
+                .map(|map_key| (map_key, namespace.to_vec()))
+                .map_err(|e| MapKeyDecodeError::InvalidKey(e.to_string())),
+        )
+    }
+}
+
+impl<'a, K, V, S, F> Iterator for GroupKeys<'a, K, V, S, F>
+where
+    K: OwnedKey,
+    V: Storable,
+    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    S: IterableStorage,
+    F: KeyFraming,
+    (K::Kind, V::Kind): KeyEncodingT,
+{
+    type Item = Result<K, MapKeyDecodeError<V::KeyDecodeError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_namespace().map(|entry| entry.map(|(key, _)| key))
+    }
+}
+
+/// An iterator over the distinct keys of a map and accessors for the inner containers.
+/// See [`MapAccess::entries`].
+pub struct Entries<'a, K, V, S, F = ByteLenPrefix> {
+    keys: GroupKeys<'a, K, V, S, F>,
+}
+
+impl<'a, K, V, S, F> Iterator for Entries<'a, K, V, S, F>
+where
+    K: OwnedKey,
+    V: Storable,
+    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    S: IterableStorage,
+    F: KeyFraming,
+    (K::Kind, V::Kind): KeyEncodingT,
+{
+    type Item =
+        Result<(K, V::Accessor<StorageBranch<&'a S>>), MapKeyDecodeError<V::KeyDecodeError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let storage = self.keys.storage;
+        self.keys.next_namespace().map(|entry| {
+            entry.map(|(key, namespace)| {
+                (key, V::access_impl(StorageBranch::new(storage, namespace)))
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::containers::{Item, IterableAccessor as _, Map};
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+
+    #[test]
+    fn group_keys_dynamic() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Map<u64, Item<u64, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut("a").entry_mut(&1).set(&1).unwrap();
+        access.entry_mut("alice").entry_mut(&1).set(&2).unwrap();
+        access.entry_mut("alice").entry_mut(&2).set(&3).unwrap();
+        access.entry_mut("alice").entry_mut(&3).set(&4).unwrap();
+        access.entry_mut("bob").entry_mut(&1).set(&5).unwrap();
+
+        let keys = access.group_keys().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(keys, vec!["a", "bob", "alice"]);
+    }
+
+    #[test]
+    fn group_keys_fixed() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<u8, Map<String, Item<u64, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut(&0).entry_mut("foo").set(&1).unwrap();
+        access.entry_mut(&0).entry_mut("bar").set(&2).unwrap();
+        access.entry_mut(&254).entry_mut("foo").set(&3).unwrap();
+        access.entry_mut(&255).entry_mut("foo").set(&4).unwrap();
+        access.entry_mut(&255).entry_mut("bar").set(&5).unwrap();
+
+        let keys = access.group_keys().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(keys, vec![0, 254, 255]);
+    }
+
+    #[test]
+    fn group_keys_terminal() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut("a").set(&1).unwrap();
+        access.entry_mut("ab").set(&2).unwrap();
+        access.entry_mut("b").set(&3).unwrap();
+
+        let keys = access.group_keys().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(keys, vec!["a", "ab", "b"]);
+    }
+
+    #[test]
+    fn entries() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Map<u64, Item<u64, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut("alice").entry_mut(&1).set(&1).unwrap();
+        access.entry_mut("alice").entry_mut(&2).set(&2).unwrap();
+        access.entry_mut("bob").entry_mut(&1).set(&3).unwrap();
+
+        let sums = access
+            .entries()
+            .map(|entry| {
+                let (key, inner) = entry.unwrap();
+                let sum = inner.values().map(Result::unwrap).sum::<u64>();
+                (key, sum)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sums, vec![("bob".to_string(), 3), ("alice".to_string(), 3)]);
+
+        access
+            .for_each_entry_mut(|key, mut inner| {
+                inner.entry_mut(&0).set(&(key.len() as u64)).unwrap();
+                inner.entry_mut(&1).remove();
+            })
+            .unwrap();
+
+        let pairs = access.pairs().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                (("bob".to_string(), (0, ())), 3),
+                (("alice".to_string(), (0, ())), 5),
+                (("alice".to_string(), (2, ())), 2),
+            ]
+        );
+    }
+}
@@ -1,8 +1,30 @@
 /// A key that can be used with a [`Map`](super::Map).
+///
+/// # Integer keys
+///
+/// Integer keys are always encoded as fixed-size big-endian integers, so that the byte order
+/// of encoded keys matches their numeric order and iteration is sorted. Signed integers
+/// additionally have their sign bit flipped, so that negative keys sort before non-negative
+/// ones.
+///
+/// This is fixed and independent of the [`Encoding`](crate::encoding::Encoding) used for
+/// values - an encoding is free to store integer *values* with any endianness, and that
+/// choice never affects key order.
+///
+/// `char` keys are encoded as their code point, the same way as a `u32` key.
+///
+/// For descending order, wrap fixed-size keys in [`DescendingKey`].
 pub trait Key {
     /// The kind of key, meaning either fixed size or dynamic size.
     type Kind: KeyKind;
 
+    /// The length of every encoded key of this type, if it's fixed.
+    ///
+    /// This follows from [`Kind`](Self::Kind) and shouldn't be overridden. Fixed-size keys
+    /// are never length-prefixed, even when another key follows them (e.g. the outer key of a
+    /// map of maps) - only dynamic keys need framing.
+    const FIXED_LEN: Option<usize> = Self::Kind::FIXED_LEN;
+
     /// Encode the key into a byte vector.
     fn encode(&self) -> Vec<u8>;
 }
@@ -10,7 +32,9 @@ pub trait Key {
 /// An owned key that can be used with a [`Map`](super::Map).
 pub trait OwnedKey: Key {
     /// The error type that can occur when decoding the key.
-    type Error;
+    ///
+    /// A map reports it as [`MapKeyDecodeError::InvalidKey`](super::MapKeyDecodeError::InvalidKey).
+    type Error: std::fmt::Display;
 
     /// Decode the key from a byte slice.
     fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
@@ -108,7 +132,7 @@ impl<const N: usize> Key for [u8; N] {
 }
 
 impl OwnedKey for Vec<u8> {
-    type Error = ();
+    type Error = std::convert::Infallible;
 
     fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
     where
@@ -119,7 +143,7 @@ impl OwnedKey for Vec<u8> {
 }
 
 impl OwnedKey for Box<[u8]> {
-    type Error = ();
+    type Error = std::convert::Infallible;
 
     fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
     where
@@ -162,7 +186,10 @@ impl<const N: usize> OwnedKey for [u8; N] {
 ///
 /// This trait is [sealed](https://rust-lang.github.io/api-guidelines/future-proofing.html#sealed-traits)
 /// and cannot be implemented outside of this crate.
-pub trait KeyKind: sealed::KeyKindSeal {}
+pub trait KeyKind: sealed::KeyKindSeal {
+    /// The length of encoded keys of this kind, or `None` for dynamic keys.
+    const FIXED_LEN: Option<usize>;
+}
 
 /// A marker type representing a fixed-size key.
 pub struct FixedSizeKey<const L: usize>;
@@ -170,8 +197,13 @@ pub struct FixedSizeKey<const L: usize>;
 /// A marker type representing a dynamic-size key.
 pub struct DynamicKey;
 
-impl<const L: usize> KeyKind for FixedSizeKey<L> {}
-impl KeyKind for DynamicKey {}
+impl<const L: usize> KeyKind for FixedSizeKey<L> {
+    const FIXED_LEN: Option<usize> = Some(L);
+}
+
+impl KeyKind for DynamicKey {
+    const FIXED_LEN: Option<usize> = None;
+}
 
 mod sealed {
     pub trait KeyKindSeal {}
@@ -255,6 +287,134 @@ macro_rules! impl_key_for_signed {
 
 impl_key_for_signed!(i8 : u8, i16 : u16, i32 : u32, i64 : u64, i128 : u128);
 
+impl Key for bool {
+    type Kind = FixedSizeKey<1>;
+
+    fn encode(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+}
+
+impl OwnedKey for bool {
+    type Error = BoolKeyDecodeError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            [byte] => Err(BoolKeyDecodeError::InvalidValue(*byte)),
+            _ => Err(BoolKeyDecodeError::InvalidLength),
+        }
+    }
+}
+
+/// An error type for decoding `bool` keys.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum BoolKeyDecodeError {
+    #[error("invalid length")]
+    InvalidLength,
+    #[error("invalid bool byte: {0:#x}")]
+    InvalidValue(u8),
+}
+
+impl crate::error::StoreyError for BoolKeyDecodeError {}
+
+impl Key for char {
+    type Kind = FixedSizeKey<4>;
+
+    fn encode(&self) -> Vec<u8> {
+        (*self as u32).to_be_bytes().to_vec()
+    }
+}
+
+impl OwnedKey for char {
+    type Error = CharKeyDecodeError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        let code = u32::from_bytes(bytes).map_err(|_| CharKeyDecodeError::InvalidLength)?;
+        char::from_u32(code).ok_or(CharKeyDecodeError::InvalidCodePoint(code))
+    }
+}
+
+/// An error type for decoding `char` keys.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum CharKeyDecodeError {
+    #[error("invalid length")]
+    InvalidLength,
+    #[error("invalid code point: {0:#x}")]
+    InvalidCodePoint(u32),
+}
+
+impl crate::error::StoreyError for CharKeyDecodeError {}
+
+/// A wrapper for fixed-size keys that reverses their order.
+///
+/// The encoded key is the bitwise NOT of the wrapped key's encoding, so iterating over a map
+/// with `DescendingKey` keys in the usual (ascending) direction yields the wrapped keys in
+/// descending order. This keeps "latest first" queries cheap even on backends that don't
+/// support reverse iteration.
+///
+/// Only fixed-size keys can be wrapped - for variable-length keys, inverting the bytes
+/// doesn't reverse the order (a key would still sort right before the keys it's a prefix of).
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{IterableAccessor as _, Item, Map};
+/// use storey::containers::map::DescendingKey;
+///
+/// let mut storage = TestStorage::new();
+/// let log = Map::<DescendingKey<u64>, Item<String, TestEncoding>>::new(0);
+/// let mut access = log.access(&mut storage);
+///
+/// for height in [10, 30, 20] {
+///     access
+///         .entry_mut(&DescendingKey(height))
+///         .set(&format!("event at {height}"))
+///         .unwrap();
+/// }
+///
+/// let latest = access.keys().next().unwrap().unwrap();
+/// assert_eq!(latest, (DescendingKey(30), ()));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct DescendingKey<K>(pub K);
+
+impl<K, const L: usize> Key for DescendingKey<K>
+where
+    K: Key<Kind = FixedSizeKey<L>>,
+{
+    type Kind = FixedSizeKey<L>;
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.0.encode();
+        bytes.iter_mut().for_each(|b| *b = !*b);
+        bytes
+    }
+}
+
+impl<K, const L: usize> OwnedKey for DescendingKey<K>
+where
+    K: OwnedKey<Kind = FixedSizeKey<L>>,
+{
+    type Error = K::Error;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        let bytes = bytes.iter().map(|b| !b).collect::<Vec<_>>();
+        K::from_bytes(&bytes).map(DescendingKey)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -289,4 +449,108 @@ mod tests {
         assert_eq!(2i32.encode(), [0b10000000, 0x00, 0x00, 0x02]);
         assert_eq!(i32::MAX.encode(), [0b11111111, 0xff, 0xff, 0xff]);
     }
+
+    #[test]
+    fn unsigned_int_encoding() {
+        assert_eq!(0x12u8.encode(), [0x12]);
+        assert_eq!(0x1234u16.encode(), [0x12, 0x34]);
+        assert_eq!(0x12345678u32.encode(), [0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(
+            0x0102030405060708u64.encode(),
+            [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]
+        );
+
+        assert_eq!(u32::from_bytes(&[0x12, 0x34, 0x56, 0x78]), Ok(0x12345678));
+        assert_eq!(
+            u32::from_bytes(&[0x12, 0x34]),
+            Err(NumericKeyDecodeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn bool_encoding() {
+        assert_eq!(false.encode(), [0]);
+        assert_eq!(true.encode(), [1]);
+        assert_eq!(bool::FIXED_LEN, Some(1));
+
+        assert_eq!(bool::from_bytes(&[0]), Ok(false));
+        assert_eq!(bool::from_bytes(&[1]), Ok(true));
+        assert_eq!(
+            bool::from_bytes(&[2]),
+            Err(BoolKeyDecodeError::InvalidValue(2))
+        );
+        assert_eq!(
+            bool::from_bytes(&[]),
+            Err(BoolKeyDecodeError::InvalidLength)
+        );
+        assert_eq!(
+            bool::from_bytes(&[0, 1]),
+            Err(BoolKeyDecodeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn char_encoding() {
+        assert_eq!('a'.encode(), [0, 0, 0, 0x61]);
+        assert_eq!('€'.encode(), [0, 0, 0x20, 0xac]);
+
+        assert_eq!(char::from_bytes(&[0, 0x01, 0xf6, 0x00]), Ok('😀'));
+        assert_eq!(
+            char::from_bytes(&[0, 0, 0xd8, 0x00]),
+            Err(CharKeyDecodeError::InvalidCodePoint(0xd800))
+        );
+        assert_eq!(
+            char::from_bytes(&[0, 0x11, 0, 0]),
+            Err(CharKeyDecodeError::InvalidCodePoint(0x110000))
+        );
+        assert_eq!(
+            char::from_bytes(&[0x61]),
+            Err(CharKeyDecodeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn descending_encoding() {
+        assert_eq!(DescendingKey(0x0102u16).encode(), [0xfe, 0xfd]);
+        assert_eq!(DescendingKey(-1i8).encode(), [0x80]);
+
+        assert_eq!(
+            DescendingKey::<u16>::from_bytes(&[0xfe, 0xfd]),
+            Ok(DescendingKey(0x0102))
+        );
+        assert_eq!(
+            DescendingKey::<u16>::from_bytes(&[0xfe]),
+            Err(NumericKeyDecodeError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn fixed_len() {
+        use crate::containers::{Item, Map};
+        use mocks::encoding::TestEncoding;
+
+        assert_eq!(u32::FIXED_LEN, Some(4));
+        assert_eq!(i128::FIXED_LEN, Some(16));
+        assert_eq!(char::FIXED_LEN, Some(4));
+        assert_eq!(<[u8; 20]>::FIXED_LEN, Some(20));
+        assert_eq!(DescendingKey::<u64>::FIXED_LEN, Some(8));
+        assert_eq!(String::FIXED_LEN, None);
+        assert_eq!(<[u8]>::FIXED_LEN, None);
+
+        // fixed-size outer keys of a map of maps take exactly `FIXED_LEN` bytes, while dynamic
+        // ones pay for a length prefix
+        type Inner = Map<String, Item<u64, TestEncoding>>;
+        assert_eq!(Map::<u32, Inner>::encode_key(&7).unwrap().len(), 4);
+        assert_eq!(
+            Map::<[u8; 20], Inner>::encode_key(&[1; 20]).unwrap().len(),
+            20
+        );
+        assert_eq!(Map::<String, Inner>::encode_key("abcd").unwrap().len(), 5);
+        assert_eq!(
+            Map::<Vec<u8>, Inner>::encode_key(&vec![1; 20])
+                .unwrap()
+                .len(),
+            21
+        );
+    }
 }
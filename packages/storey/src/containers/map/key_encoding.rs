@@ -13,7 +13,10 @@ use super::key::{DynamicKey, FixedSizeKey};
 ///   length prefixed - otherwise, we would not know where the key ends and the key for the inner
 ///   container starts.
 /// - If the key is dynamic and the container is terminal, then the key is the rest of the string.
-/// - If the key is fixed size, then we statically provide the number of bytes to read/write.
+/// - If the key is fixed size and the container is terminal, then the key is the rest of the
+///   string, which must be exactly as long as the key.
+/// - If the key is fixed size and the container is nonterminal, then we statically provide the
+///   number of bytes to read/write.
 pub trait KeyEncodingT {
     const BEHAVIOR: KeyEncoding;
 }
@@ -23,7 +26,7 @@ impl KeyEncodingT for (DynamicKey, NonTerminal) {
 }
 
 impl<const L: usize> KeyEncodingT for (FixedSizeKey<L>, Terminal) {
-    const BEHAVIOR: KeyEncoding = KeyEncoding::UseRest;
+    const BEHAVIOR: KeyEncoding = KeyEncoding::UseExact(L);
 }
 
 impl KeyEncodingT for (DynamicKey, Terminal) {
@@ -42,4 +45,7 @@ pub enum KeyEncoding {
     UseRest,
     /// The key is of fixed size.
     UseN(usize),
+    /// The key is of fixed size and nothing follows it. The rest of the string is the key,
+    /// and must be exactly this long.
+    UseExact(usize),
 }
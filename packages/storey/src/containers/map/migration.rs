@@ -0,0 +1,669 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+
+use crate::containers::Storable;
+use crate::storage::{IterableStorage, Storage, StorageBranch, StorageMut};
+
+use super::key_encoding::KeyEncodingT;
+use super::{
+    split_key, ByteLenPrefix, FramingError, KeyFraming, Map, MapKeyDecodeError, OwnedKey,
+    VarintLenPrefix,
+};
+
+impl<K, V, F> Map<K, V, F>
+where
+    K: OwnedKey,
+    V: Storable,
+    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    F: KeyFraming,
+    (K::Kind, V::Kind): KeyEncodingT,
+{
+    /// Copies every entry of this map into `target`, mapping each key with `f`.
+    ///
+    /// This is meant for migrations that change the key type of a map. Only the map's own
+    /// key is decoded and re-encoded - everything stored under it (a value, or the whole
+    /// namespace of a nested container) is copied over byte for byte. The key framing
+    /// (e.g. length prefixes) of both the old and the new key type is handled here.
+    ///
+    /// Entries are collected before anything is written, so `target` may share a backend
+    /// with this map. The old entries are left in place.
+    ///
+    /// Data stored in the metadata namespace (e.g. [`Column`](crate::containers::Column)
+    /// bookkeeping) is not copied.
+    ///
+    /// If two old keys map to the same new key, the entry written last wins. If a new key
+    /// can't be represented by the target's key framing, nothing is written.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let old = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let new = Map::<Vec<u8>, Item<u64, TestEncoding>>::new(1);
+    ///
+    /// old.access(&mut storage).entry_mut("foo").set(&1337).unwrap();
+    ///
+    /// old.rekey(&new, &mut storage, |key| key.into_bytes()).unwrap();
+    /// assert_eq!(new.access(&storage).entry(b"foo".as_slice()).get().unwrap(), Some(1337));
+    /// ```
+    pub fn rekey<K2, F2, S, M>(
+        &self,
+        target: &Map<K2, V, F2>,
+        storage: &mut S,
+        f: M,
+    ) -> Result<(), RekeyError<K, K2, V::KeyDecodeError>>
+    where
+        K2: OwnedKey,
+        (K2::Kind, V::Kind): KeyEncodingT,
+        S: Storage + IterableStorage + StorageMut,
+        F2: KeyFraming,
+        M: FnMut(K) -> K2,
+    {
+        let plan = self.plan_rekey::<K2, F2, _, _>(&*storage, f)?;
+
+        let mut branch = StorageBranch::new(&mut *storage, target.prefix.to_vec());
+        for (_, new_key, value) in plan.moves {
+            branch.set(&new_key, &value);
+        }
+
+        Ok(())
+    }
+
+    /// Moves every entry of this map to `target`, mapping each key with `f`.
+    ///
+    /// Unlike [`rekey`](Self::rekey), the old entries are removed, and the migration is
+    /// refused if two old keys map to the same new key. In that case nothing is written and
+    /// every collision is reported in [`RekeyError::Collisions`]. Use
+    /// [`migrate_keys_dry_run`](Self::migrate_keys_dry_run) to check for collisions up front.
+    ///
+    /// `target` may be this map (or share its prefix) - all entries are read before anything
+    /// is written. Entries already present in `target` under a new key are overwritten.
+    ///
+    /// As with [`rekey`](Self::rekey), only the map's own key is re-encoded, and the metadata
+    /// namespace is not touched.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    /// use storey::containers::map::RekeyError;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let old = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let new = Map::<u32, Item<u64, TestEncoding>>::new(1);
+    ///
+    /// old.access(&mut storage).entry_mut("foo").set(&1).unwrap();
+    /// old.access(&mut storage).entry_mut("bar").set(&2).unwrap();
+    ///
+    /// // both keys are 3 bytes long
+    /// let err = old.migrate_keys(&new, &mut storage, |key| key.len() as u32).unwrap_err();
+    /// assert!(matches!(err, RekeyError::Collisions(c) if c[0].new_key == 3));
+    ///
+    /// old.migrate_keys(&new, &mut storage, |key| key.as_bytes()[0] as u32).unwrap();
+    /// assert_eq!(old.access(&storage).entry("foo").get().unwrap(), None);
+    /// assert_eq!(new.access(&storage).entry(&(b'f' as u32)).get().unwrap(), Some(1));
+    /// ```
+    pub fn migrate_keys<K2, F2, S, M>(
+        &self,
+        target: &Map<K2, V, F2>,
+        storage: &mut S,
+        f: M,
+    ) -> Result<(), RekeyError<K, K2, V::KeyDecodeError>>
+    where
+        K2: OwnedKey,
+        (K2::Kind, V::Kind): KeyEncodingT,
+        S: Storage + IterableStorage + StorageMut,
+        F2: KeyFraming,
+        M: FnMut(K) -> K2,
+    {
+        let RekeyPlan { moves, targets } = self.plan_rekey::<K2, F2, _, _>(&*storage, f)?;
+        let collisions = Self::collisions(targets.into_values())?;
+        if !collisions.is_empty() {
+            return Err(RekeyError::Collisions(collisions));
+        }
+
+        let mut branch = StorageBranch::new(&mut *storage, self.prefix.to_vec());
+        for (old_key, _, _) in &moves {
+            branch.remove(old_key);
+        }
+
+        let mut branch = StorageBranch::new(&mut *storage, target.prefix.to_vec());
+        for (_, new_key, value) in moves {
+            branch.set(&new_key, &value);
+        }
+
+        Ok(())
+    }
+
+    /// Reports what [`migrate_keys`](Self::migrate_keys) would run into, without writing
+    /// anything.
+    ///
+    /// Besides the collisions that make the migration fail, this lists the new keys under
+    /// which `target` already holds entries that the migration would overwrite. Entries of
+    /// this map don't count, since they're moved away first.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let old = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let new = Map::<u32, Item<u64, TestEncoding>>::new(1);
+    ///
+    /// old.access(&mut storage).entry_mut("foo").set(&1).unwrap();
+    /// old.access(&mut storage).entry_mut("bar").set(&2).unwrap();
+    /// new.access(&mut storage).entry_mut(&(b'f' as u32)).set(&3).unwrap();
+    ///
+    /// let report = old
+    ///     .migrate_keys_dry_run(&new, &storage, |key| key.as_bytes()[0] as u32)
+    ///     .unwrap();
+    /// assert!(report.collisions.is_empty());
+    /// assert_eq!(report.overwrites, vec![b'f' as u32]);
+    /// ```
+    pub fn migrate_keys_dry_run<K2, F2, S, M>(
+        &self,
+        target: &Map<K2, V, F2>,
+        storage: &S,
+        f: M,
+    ) -> Result<MigrationReport<K, K2>, RekeyError<K, K2, V::KeyDecodeError>>
+    where
+        K2: OwnedKey + Clone,
+        (K2::Kind, V::Kind): KeyEncodingT,
+        S: Storage + IterableStorage,
+        F2: KeyFraming,
+        M: FnMut(K) -> K2,
+    {
+        let RekeyPlan { moves, targets } = self.plan_rekey::<K2, F2, _, _>(storage, f)?;
+
+        // the entries `migrate_keys` would move away, as full keys under `storage`
+        let own_prefix = self.prefix.to_vec();
+        let own_keys = moves
+            .iter()
+            .map(|(old_key, _, _)| [&own_prefix[..], old_key].concat())
+            .collect::<BTreeSet<_>>();
+
+        let target_prefix = target.prefix.to_vec();
+        let target_branch = StorageBranch::new(storage, target_prefix.clone());
+        let mut overwrites = Vec::new();
+        for (new_framed, (new_key, _)) in &targets {
+            let end = Map::<K2, V, F2>::entry_end(new_framed);
+
+            let occupied = target_branch
+                .keys(Bound::Included(new_framed), end.as_ref().map(Vec::as_slice))
+                .any(|key| !own_keys.contains(&[&target_prefix[..], &key].concat()));
+            if occupied {
+                overwrites.push(new_key.clone());
+            }
+        }
+
+        Ok(MigrationReport {
+            collisions: Self::collisions(targets.into_values())?,
+            overwrites,
+        })
+    }
+
+    /// Computes the raw moves of a key migration, shared by [`rekey`](Self::rekey) and
+    /// [`migrate_keys`](Self::migrate_keys).
+    fn plan_rekey<K2, F2, S, M>(
+        &self,
+        storage: &S,
+        mut f: M,
+    ) -> Result<RekeyPlan<K2>, RekeyError<K, K2, V::KeyDecodeError>>
+    where
+        K2: OwnedKey,
+        (K2::Kind, V::Kind): KeyEncodingT,
+        S: Storage + IterableStorage,
+        F2: KeyFraming,
+        M: FnMut(K) -> K2,
+    {
+        let behavior = || <(K::Kind, V::Kind)>::BEHAVIOR;
+
+        let mut moves = Vec::new();
+        let mut targets: BTreeMap<Vec<u8>, (K2, Vec<Vec<u8>>)> = BTreeMap::new();
+        // entries of a nested container are contiguous, so the last mapping can be reused
+        let mut last: Option<(Vec<u8>, Vec<u8>)> = None;
+
+        for (key, value) in StorageBranch::new(storage, self.prefix.to_vec())
+            .pairs(Bound::Unbounded, Bound::Unbounded)
+        {
+            let (map_key, rest) = split_key::<F, V::KeyDecodeError>(behavior(), &key)?;
+            let old_framed = key[..key.len() - rest.len()].to_vec();
+
+            let new_framed = match &last {
+                Some((old, new)) if *old == old_framed => new.clone(),
+                _ => {
+                    let map_key = K::from_bytes(map_key)
+                        .map_err(|e| MapKeyDecodeError::InvalidKey(e.to_string()))?;
+                    let new_key = f(map_key);
+                    let new_framed = Map::<K2, V, F2>::encode_key(&new_key)?;
+
+                    targets
+                        .entry(new_framed.clone())
+                        .or_insert_with(|| (new_key, Vec::new()))
+                        .1
+                        .push(old_framed.clone());
+                    last = Some((old_framed, new_framed.clone()));
+                    new_framed
+                }
+            };
+
+            moves.push((key.clone(), [&new_framed[..], rest].concat(), value));
+        }
+
+        Ok(RekeyPlan { moves, targets })
+    }
+
+    /// Picks the new keys several old keys map to out of a plan's targets.
+    fn collisions<K2>(
+        targets: impl IntoIterator<Item = (K2, Vec<Vec<u8>>)>,
+    ) -> Result<Vec<KeyCollision<K, K2>>, MapKeyDecodeError<V::KeyDecodeError>> {
+        let behavior = || <(K::Kind, V::Kind)>::BEHAVIOR;
+
+        targets
+            .into_iter()
+            .filter(|(_, old_keys)| old_keys.len() > 1)
+            .map(|(new_key, old_keys)| {
+                let old_keys = old_keys
+                    .iter()
+                    .map(|framed| {
+                        let (map_key, _) = split_key::<F, V::KeyDecodeError>(behavior(), framed)?;
+                        K::from_bytes(map_key)
+                            .map_err(|e| MapKeyDecodeError::InvalidKey(e.to_string()))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(KeyCollision { new_key, old_keys })
+            })
+            .collect()
+    }
+}
+
+impl<K, V> Map<K, V, ByteLenPrefix>
+where
+    K: OwnedKey,
+    V: Storable,
+    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    (K::Kind, V::Kind): KeyEncodingT,
+{
+    /// Rewrites the map's keys from [`ByteLenPrefix`] to [`VarintLenPrefix`] framing, in place.
+    ///
+    /// This is [`migrate_keys`](Self::migrate_keys) into a map with the same prefix and keys,
+    /// so the same caveats apply. Keys shorter than 128 bytes are framed the same way by both,
+    /// so only entries with longer keys actually change. Once this has run, the map should
+    /// only be accessed as a `Map<K, V, VarintLenPrefix>`.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::map::VarintLenPrefix;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let old = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+    /// let new = Map::<String, Map<u32, Item<u64, TestEncoding>>, VarintLenPrefix>::new(0);
+    ///
+    /// let long_key = "x".repeat(200);
+    /// old.access(&mut storage).entry_mut(&long_key).entry_mut(&1).set(&7).unwrap();
+    ///
+    /// old.migrate_key_framing(&mut storage).unwrap();
+    /// assert_eq!(new.access(&storage).entry(&long_key).entry(&1).get().unwrap(), Some(7));
+    /// ```
+    pub fn migrate_key_framing<S>(
+        &self,
+        storage: &mut S,
+    ) -> Result<(), RekeyError<K, K, V::KeyDecodeError>>
+    where
+        S: Storage + IterableStorage + StorageMut,
+    {
+        let target = Map::<K, V, VarintLenPrefix>::with_prefix(self.prefix.to_vec());
+        self.migrate_keys(&target, storage, |key| key)
+    }
+}
+
+/// The raw effect of a key migration.
+struct RekeyPlan<K2> {
+    /// Every entry as `(old key, new key, value)`, relative to the maps' prefixes.
+    moves: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>,
+    /// Each new (framed) map key, with the new key and the old (framed) map keys mapping to it.
+    targets: BTreeMap<Vec<u8>, (K2, Vec<Vec<u8>>)>,
+}
+
+/// What a key migration would run into, as reported by [`Map::migrate_keys_dry_run`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MigrationReport<K, K2> {
+    /// Old keys sharing a new key. The migration is refused unless this is empty.
+    pub collisions: Vec<KeyCollision<K, K2>>,
+    /// New keys under which the target already holds entries, which would be overwritten.
+    pub overwrites: Vec<K2>,
+}
+
+/// Several old keys that a key migration would map to the same new key.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct KeyCollision<K, K2> {
+    /// The new key.
+    pub new_key: K2,
+    /// The old keys mapping to it.
+    pub old_keys: Vec<K>,
+}
+
+/// An error that can occur during [`Map::rekey`] or [`Map::migrate_keys`].
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+pub enum RekeyError<K, K2, E: std::fmt::Display> {
+    #[error("failed to decode key: {0}")]
+    Decode(#[from] MapKeyDecodeError<E>),
+    #[error("new key can't be stored: {0}")]
+    Framing(#[from] FramingError),
+    #[error("{} new keys would each be shared by several old keys", .0.len())]
+    Collisions(Vec<KeyCollision<K, K2>>),
+}
+
+impl<K, K2, E: std::fmt::Display> crate::error::StoreyError for RekeyError<K, K2, E> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::containers::map::NullTerminated;
+    use crate::containers::{Item, IterableAccessor as _};
+
+    use super::*;
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+
+    #[test]
+    fn rekey_map_of_items() {
+        let mut storage = TestStorage::new();
+
+        let old = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let new = Map::<u32, Item<u64, TestEncoding>>::new(1);
+
+        let mut access = old.access(&mut storage);
+        access.entry_mut("a").set(&1).unwrap();
+        access.entry_mut("bbb").set(&3).unwrap();
+
+        old.rekey(&new, &mut storage, |key| key.len() as u32)
+            .unwrap();
+
+        assert_eq!(
+            new.access(&storage)
+                .pairs()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![((1, ()), 1), ((3, ()), 3)]
+        );
+
+        // the old map is left intact
+        assert_eq!(old.access(&storage).entry("a").get().unwrap(), Some(1));
+    }
+
+    #[test]
+    fn rekey_map_of_maps() {
+        let mut storage = TestStorage::new();
+
+        let old = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+        let new = Map::<u32, Map<String, Item<u64, TestEncoding>>>::new(1);
+        let new_dyn = Map::<Vec<u8>, Map<String, Item<u64, TestEncoding>>>::new(2);
+
+        let mut access = old.access(&mut storage);
+        access.entry_mut("a").entry_mut("x").set(&1).unwrap();
+        access.entry_mut("a").entry_mut("y").set(&2).unwrap();
+        access.entry_mut("bb").entry_mut("z").set(&3).unwrap();
+
+        old.rekey(&new, &mut storage, |key| key.len() as u32)
+            .unwrap();
+        old.rekey(&new_dyn, &mut storage, |key| key.repeat(2).into_bytes())
+            .unwrap();
+
+        assert_eq!(
+            new.access(&storage)
+                .pairs()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![
+                ((1, ("x".to_string(), ())), 1),
+                ((1, ("y".to_string(), ())), 2),
+                ((2, ("z".to_string(), ())), 3),
+            ]
+        );
+        assert_eq!(
+            new_dyn
+                .access(&storage)
+                .pairs()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![
+                ((b"aa".to_vec(), ("x".to_string(), ())), 1),
+                ((b"aa".to_vec(), ("y".to_string(), ())), 2),
+                ((b"bbbb".to_vec(), ("z".to_string(), ())), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn rekey_to_another_framing() {
+        let mut storage = TestStorage::new();
+
+        let old = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+        let new = Map::<String, Map<u32, Item<u64, TestEncoding>>, NullTerminated>::new(1);
+
+        let mut access = old.access(&mut storage);
+        access.entry_mut("bob").entry_mut(&1).set(&1).unwrap();
+        access.entry_mut("alice").entry_mut(&2).set(&2).unwrap();
+
+        old.migrate_keys(&new, &mut storage, |key| key).unwrap();
+
+        assert_eq!(
+            new.access(&storage)
+                .pairs()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![
+                (("alice".to_string(), (2, ())), 2),
+                (("bob".to_string(), (1, ())), 1)
+            ]
+        );
+        assert_eq!(old.access(&storage).pairs().count(), 0);
+    }
+
+    #[test]
+    fn migrate_key_framing() {
+        let mut storage = TestStorage::new();
+
+        let old = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+        let new = Map::<String, Map<u32, Item<u64, TestEncoding>>, VarintLenPrefix>::new(0);
+
+        let short = "a".to_string();
+        let long = "b".repeat(200);
+        let mut access = old.access(&mut storage);
+        access.entry_mut(&short).entry_mut(&1).set(&1).unwrap();
+        access.entry_mut(&long).entry_mut(&2).set(&2).unwrap();
+        access.entry_mut(&long).entry_mut(&3).set(&3).unwrap();
+
+        old.migrate_key_framing(&mut storage).unwrap();
+
+        let expected = vec![
+            ((short.clone(), (1, ())), 1),
+            ((long.clone(), (2, ())), 2),
+            ((long.clone(), (3, ())), 3),
+        ];
+        assert_eq!(
+            new.access(&storage)
+                .pairs()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            expected
+        );
+
+        // and back again
+        new.migrate_keys(&old, &mut storage, |key| key).unwrap();
+        assert_eq!(
+            old.access(&storage)
+                .pairs()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn migrate_keys_in_place() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+
+        let mut access = map.access(&mut storage);
+        access.entry_mut("a").entry_mut("x").set(&1).unwrap();
+        access.entry_mut("a").entry_mut("y").set(&2).unwrap();
+        access.entry_mut("B").entry_mut("z").set(&3).unwrap();
+        access.entry_mut("b").entry_mut("z").set(&4).unwrap();
+
+        // lowercasing makes "B" and "b" collide; all entries of "a" map to one key,
+        // which is not a collision
+        // the map's own entries are moved away first, so nothing is overwritten
+        let report = map
+            .migrate_keys_dry_run(&map, &storage, |key| key.to_lowercase())
+            .unwrap();
+        assert_eq!(
+            report.collisions,
+            vec![KeyCollision {
+                new_key: "b".to_string(),
+                old_keys: vec!["B".to_string(), "b".to_string()],
+            }]
+        );
+        assert_eq!(report.overwrites, Vec::<String>::new());
+        assert_eq!(
+            map.migrate_keys(&map, &mut storage, |key| key.to_lowercase()),
+            Err(RekeyError::Collisions(report.collisions))
+        );
+        // nothing was written
+        assert_eq!(
+            map.access(&storage).entry("B").entry("z").get().unwrap(),
+            Some(3)
+        );
+
+        map.migrate_keys(&map, &mut storage, |key| key.repeat(2))
+            .unwrap();
+        assert_eq!(
+            map.access(&storage)
+                .pairs()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![
+                (("BB".to_string(), ("z".to_string(), ())), 3),
+                (("aa".to_string(), ("x".to_string(), ())), 1),
+                (("aa".to_string(), ("y".to_string(), ())), 2),
+                (("bb".to_string(), ("z".to_string(), ())), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn dry_run_reports_overwrites() {
+        let mut storage = TestStorage::new();
+
+        let old = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+        let new = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(1);
+
+        old.access(&mut storage)
+            .entry_mut("a")
+            .entry_mut("x")
+            .set(&1)
+            .unwrap();
+        old.access(&mut storage)
+            .entry_mut("b")
+            .entry_mut("x")
+            .set(&2)
+            .unwrap();
+        // "aa" would be merged into, while "bbb" merely shares a prefix with "bb"
+        new.access(&mut storage)
+            .entry_mut("aa")
+            .entry_mut("y")
+            .set(&3)
+            .unwrap();
+        new.access(&mut storage)
+            .entry_mut("bbb")
+            .entry_mut("x")
+            .set(&4)
+            .unwrap();
+
+        let report = old
+            .migrate_keys_dry_run(&new, &storage, |key| key.repeat(2))
+            .unwrap();
+        assert_eq!(
+            report,
+            MigrationReport {
+                collisions: vec![],
+                overwrites: vec!["aa".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn dry_run_under_nested_prefix() {
+        let mut storage = TestStorage::new();
+
+        let old = Map::<String, Map<String, Item<u64, TestEncoding>>>::with_prefix(b"v1".to_vec());
+        let new = Map::<String, Map<String, Item<u64, TestEncoding>>>::with_prefix(b"v2".to_vec());
+
+        // the same target key outside the namespace isn't overwritten
+        new.access(&mut storage)
+            .entry_mut("bb")
+            .entry_mut("x")
+            .set(&0)
+            .unwrap();
+
+        let mut ns = StorageBranch::new(&mut storage, b"ns/".to_vec());
+        old.access(&mut ns)
+            .entry_mut("a")
+            .entry_mut("x")
+            .set(&1)
+            .unwrap();
+        old.access(&mut ns)
+            .entry_mut("b")
+            .entry_mut("x")
+            .set(&2)
+            .unwrap();
+        new.access(&mut ns)
+            .entry_mut("aa")
+            .entry_mut("y")
+            .set(&3)
+            .unwrap();
+
+        let report = old
+            .migrate_keys_dry_run(&new, &ns, |key| key.repeat(2))
+            .unwrap();
+        assert_eq!(
+            report,
+            MigrationReport {
+                collisions: vec![],
+                overwrites: vec!["aa".to_string()],
+            }
+        );
+
+        // the real run sees the same entries
+        old.migrate_keys(&new, &mut ns, |key| key.repeat(2))
+            .unwrap();
+        let entries = new
+            .access(&ns)
+            .pairs()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                (("aa".to_string(), ("x".to_string(), ())), 1),
+                (("aa".to_string(), ("y".to_string(), ())), 3),
+                (("bb".to_string(), ("x".to_string(), ())), 2),
+            ]
+        );
+        assert_eq!(
+            new.access(&storage).entry("bb").entry("x").get().unwrap(),
+            Some(0)
+        );
+    }
+}
@@ -1,32 +1,64 @@
+mod group;
 pub mod key;
 mod key_encoding;
+mod migration;
 
-pub use key::{Key, OwnedKey};
+pub use crate::storage::{
+    ByteLenPrefix, FramingError, KeyFraming, NullTerminated, VarintLenPrefix,
+};
+pub use group::{Entries, GroupKeys};
+pub use key::{DescendingKey, Key, OwnedKey};
 use key_encoding::KeyEncoding;
-use key_encoding::KeyEncodingT;
+pub(crate) use key_encoding::KeyEncodingT;
+pub use migration::{KeyCollision, MigrationReport, RekeyError};
 
-use std::{borrow::Borrow, marker::PhantomData};
+use std::{borrow::Borrow, marker::PhantomData, ops::Bound};
 
+use crate::encoding::{DecodableWith, EncodableWith, Encoding};
+use crate::storage::prefix_successor;
+use crate::storage::ImportError;
 use crate::storage::IterableStorage;
+use crate::storage::RevIterableStorage;
+use crate::storage::Storage;
 use crate::storage::StorageBranch;
+use crate::storage::StorageMut;
 
 use self::key::DynamicKey;
 use self::key::FixedSizeKey;
 
+use super::common::Prefix;
+use super::expiring_item::decode_expiry;
 use super::BoundFor;
 use super::BoundedIterableAccessor;
+use super::ExpiringItem;
+use super::Item;
+use super::ItemGuard;
+use super::ItemKeyDecodeError;
 use super::IterableAccessor;
+use super::KVDecodeError;
 use super::NonTerminal;
 use super::Storable;
 use super::Terminal;
 
 /// A map that stores values of type `V` under keys of type `K`.
 ///
-/// The subkeys managed by the map are length-prefixed and appended to the map's prefix.
+/// The subkeys managed by the map are framed (if necessary) and appended to the map's prefix.
 ///
 /// A map does not directly manage the storage of its values. Instead, it doles out access to
 /// a collection of other containers.
 ///
+/// # Key length
+///
+/// Dynamically sized keys (e.g. strings) of maps holding non-terminal containers (e.g. maps of
+/// maps) have to be framed, so that they can be told apart from the keys of the inner
+/// container. How that's done is determined by the `F` parameter (see [`KeyFraming`]).
+///
+/// By default ([`ByteLenPrefix`]), such keys are prefixed with their length as a single byte.
+/// They can't be longer than 255 bytes - such keys read as absent, and writes to them are
+/// discarded (see [`MapAccess::try_entry_mut`]). Use [`VarintLenPrefix`] for longer keys - an existing
+/// map can be switched over with [`Map::migrate_key_framing`] - or [`NullTerminated`] to
+/// iterate over keys in their natural order.
+///
 /// # Examples
 ///
 /// ```
@@ -56,16 +88,38 @@ use super::Terminal;
 /// assert_eq!(access.entry("foo").entry("bar").get().unwrap(), Some(1337));
 /// assert_eq!(access.entry("foo").entry("baz").get().unwrap(), None);
 /// ```
-pub struct Map<K: ?Sized, V> {
-    prefix: u8,
-    phantom: PhantomData<(*const K, V)>,
+///
+/// Composite keys are expressed by nesting maps rather than with tuple keys. Iterating over
+/// every entry sharing the first component is then a matter of iterating the inner map - the
+/// framing of the outer key is taken care of:
+///
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{IterableAccessor as _, Item, Map};
+///
+/// let mut storage = TestStorage::new();
+/// let map = Map::<String, Map<u64, Item<u64, TestEncoding>>>::new(0);
+/// let mut access = map.access(&mut storage);
+///
+/// access.entry_mut("alice").entry_mut(&1).set(&10).unwrap();
+/// access.entry_mut("alice").entry_mut(&2).set(&20).unwrap();
+/// access.entry_mut("alicia").entry_mut(&1).set(&30).unwrap();
+///
+/// let alice = access.entry("alice").pairs().collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(alice, vec![((1, ()), 10), ((2, ()), 20)]);
+/// ```
+pub struct Map<K: ?Sized, V, F = ByteLenPrefix> {
+    prefix: Prefix,
+    phantom: PhantomData<(*const K, V, F)>,
 }
 
-impl<K, V> Map<K, V>
+impl<K, V, F> Map<K, V, F>
 where
     K: OwnedKey,
     V: Storable,
     <V as Storable>::KeyDecodeError: std::fmt::Display,
+    F: KeyFraming,
     (K::Kind, V::Kind): KeyEncodingT,
 {
     /// Creates a new map with the given prefix.
@@ -74,9 +128,47 @@ where
     /// with other keys in the storage.
     ///
     /// The key provided here is used as a prefix for all keys managed by the map.
+    /// The [`namespace!`](crate::namespace) macro can be used to have the compiler check it.
     pub const fn new(prefix: u8) -> Self {
         Self {
-            prefix,
+            prefix: Prefix::Byte(prefix),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates a new map under a prefix computed at runtime.
+    ///
+    /// This is useful for placing maps in dynamically built namespaces, e.g. one per tenant.
+    /// Unlike [`new`](Self::new), this can't be used in `const` contexts.
+    ///
+    /// It is the responsibility of the caller to ensure that the prefix is unique and does not
+    /// conflict with other keys in the storage. In particular, a prefix starting with the same
+    /// byte as another top-level container will overlap with that container's namespace.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// const TENANTS: u8 = 0;
+    ///
+    /// fn balances(tenant: &str) -> Map<String, Item<u64, TestEncoding>> {
+    ///     let mut prefix = vec![TENANTS, tenant.len() as u8];
+    ///     prefix.extend_from_slice(tenant.as_bytes());
+    ///     Map::with_prefix(prefix)
+    /// }
+    ///
+    /// let mut storage = TestStorage::new();
+    ///
+    /// balances("acme").access(&mut storage).entry_mut("alice").set(&100).unwrap();
+    ///
+    /// assert_eq!(balances("acme").access(&storage).entry("alice").get().unwrap(), Some(100));
+    /// assert_eq!(balances("initech").access(&storage).entry("alice").get().unwrap(), None);
+    /// ```
+    pub fn with_prefix(prefix: Vec<u8>) -> Self {
+        Self {
+            prefix: Prefix::Bytes(prefix),
             phantom: PhantomData,
         }
     }
@@ -99,26 +191,125 @@ where
     /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
     /// let mut access = map.access(&mut storage);
     /// ```
-    pub fn access<S>(&self, storage: S) -> MapAccess<K, V, StorageBranch<S>> {
-        Self::access_impl(StorageBranch::new(storage, vec![self.prefix]))
+    pub fn access<S>(&self, storage: S) -> MapAccess<K, V, StorageBranch<S>, F> {
+        Self::access_impl(StorageBranch::new(storage, self.prefix.to_vec()))
+    }
+
+    /// Acquires an accessor for the map, rooted under an existing [`StorageBranch`].
+    ///
+    /// The map's own prefix is applied on top of the branch's prefix. This makes it possible
+    /// to place containers under computed namespaces (e.g. versions or epochs) without going
+    /// through a parent map.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    /// use storey::storage::StorageBranch;
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    ///
+    /// let v1 = StorageBranch::new(&mut storage, b"v1/".to_vec());
+    /// map.access_at(v1).entry_mut("foo").set(&1).unwrap();
+    /// let v2 = StorageBranch::new(&mut storage, b"v2/".to_vec());
+    /// map.access_at(v2).entry_mut("foo").set(&2).unwrap();
+    ///
+    /// let v1 = StorageBranch::new(&storage, b"v1/".to_vec());
+    /// assert_eq!(map.access_at(v1).entry("foo").get().unwrap(), Some(1));
+    /// ```
+    pub fn access_at<S>(&self, branch: StorageBranch<S>) -> MapAccess<K, V, StorageBranch<S>, F> {
+        Self::access_impl(branch.nest(&self.prefix.to_vec()))
+    }
+}
+
+impl<K, V, F> Map<K, V, F>
+where
+    K: Key,
+    V: Storable,
+    F: KeyFraming,
+    (K::Kind, V::Kind): KeyEncodingT,
+{
+    /// Encodes a key the same way the map does when storing entries.
+    ///
+    /// The result is relative to the map's namespace, i.e. it doesn't include the map's
+    /// prefix. Any sub-keys managed by the value type would follow these bytes.
+    ///
+    /// This can be used alongside [`Storable::decode_key`] and [`Storable::decode_value`]
+    /// to construct or parse raw storage entries without a storage backend.
+    ///
+    /// Fails if the key has to be framed and the framing `F` can't represent it.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// use storey::containers::{Item, Map, Storable as _};
+    ///
+    /// type M = Map<String, Item<u64, TestEncoding>>;
+    ///
+    /// let key = M::encode_key("foo").unwrap();
+    /// let value = M::encode_value(&1337).unwrap();
+    ///
+    /// assert_eq!(M::decode_key(&key).unwrap(), ("foo".to_string(), ()));
+    /// assert_eq!(M::decode_value(&value).unwrap(), 1337);
+    /// ```
+    pub fn encode_key<Q>(key: &Q) -> Result<Vec<u8>, FramingError>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        let behavior = <(K::Kind, V::Kind)>::BEHAVIOR;
+
+        match behavior {
+            KeyEncoding::LenPrefix => F::frame(&key.encode()),
+            _ => Ok(key.encode()),
+        }
+    }
+
+    /// The end of the range of raw keys holding whatever is stored under the encoded `key`,
+    /// with the range starting at `key` itself.
+    fn entry_end(key: &[u8]) -> Bound<Vec<u8>> {
+        // Terminal keys aren't framed, so a prefix scan would also match longer keys.
+        match <(K::Kind, V::Kind)>::BEHAVIOR {
+            KeyEncoding::UseRest | KeyEncoding::UseExact(_) => Bound::Included(key.to_vec()),
+            KeyEncoding::LenPrefix | KeyEncoding::UseN(_) => {
+                prefix_successor(key).map_or(Bound::Unbounded, Bound::Excluded)
+            }
+        }
     }
 }
 
-impl<K, V> Storable for Map<K, V>
+impl<K, T, E, F> Map<K, Item<T, E>, F>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    F: KeyFraming,
+{
+    /// Encodes a value the same way the map does when storing entries.
+    ///
+    /// This is the counterpart of [`Storable::decode_value`].
+    pub fn encode_value(value: &T) -> Result<Vec<u8>, E::EncodeError> {
+        Item::<T, E>::encode_value(value)
+    }
+}
+
+impl<K, V, F> Storable for Map<K, V, F>
 where
     K: OwnedKey,
     V: Storable,
     <V as Storable>::KeyDecodeError: std::fmt::Display,
+    F: KeyFraming,
     (K::Kind, V::Kind): KeyEncodingT,
 {
     type Kind = NonTerminal;
-    type Accessor<S> = MapAccess<K, V, S>;
+    type Accessor<S> = MapAccess<K, V, S, F>;
     type Key = (K, V::Key);
     type KeyDecodeError = MapKeyDecodeError<V::KeyDecodeError>;
     type Value = V::Value;
     type ValueDecodeError = V::ValueDecodeError;
 
-    fn access_impl<S>(storage: S) -> MapAccess<K, V, S> {
+    fn access_impl<S>(storage: S) -> MapAccess<K, V, S, F> {
         MapAccess {
             storage,
             phantom: PhantomData,
@@ -126,54 +317,62 @@ where
     }
 
     fn decode_key(key: &[u8]) -> Result<Self::Key, MapKeyDecodeError<V::KeyDecodeError>> {
-        let behavior = <(K::Kind, V::Kind)>::BEHAVIOR;
-
-        match behavior {
-            KeyEncoding::LenPrefix => {
-                let len = *key.first().ok_or(MapKeyDecodeError::EmptyKey)? as usize;
-
-                if key.len() < len + 1 {
-                    return Err(MapKeyDecodeError::KeyTooShort(len));
-                }
+        let (map_key, rest) = split_key::<F, _>(<(K::Kind, V::Kind)>::BEHAVIOR, key)?;
 
-                let map_key =
-                    K::from_bytes(&key[1..len + 1]).map_err(|_| MapKeyDecodeError::InvalidUtf8)?;
-                let rest = V::decode_key(&key[len + 1..]).map_err(MapKeyDecodeError::Inner)?;
+        let map_key =
+            K::from_bytes(map_key).map_err(|e| MapKeyDecodeError::InvalidKey(e.to_string()))?;
+        let rest = V::decode_key(rest).map_err(MapKeyDecodeError::Inner)?;
 
-                Ok((map_key, rest))
-            }
-            KeyEncoding::UseRest => {
-                let map_key = K::from_bytes(key).map_err(|_| MapKeyDecodeError::InvalidUtf8)?;
-                let rest = V::decode_key(&[]).map_err(MapKeyDecodeError::Inner)?;
+        Ok((map_key, rest))
+    }
 
-                Ok((map_key, rest))
-            }
-            KeyEncoding::UseN(n) => {
-                let map_key =
-                    K::from_bytes(&key[..n]).map_err(|_| MapKeyDecodeError::InvalidUtf8)?;
-                let rest = V::decode_key(&key[n..]).map_err(MapKeyDecodeError::Inner)?;
+    fn decode_value(value: &[u8]) -> Result<Self::Value, Self::ValueDecodeError> {
+        V::decode_value(value)
+    }
 
-                Ok((map_key, rest))
+    fn clear_impl<S>(storage: &mut S)
+    where
+        S: Storage + IterableStorage + StorageMut,
+    {
+        // Collect the namespace of every entry, so that each inner container can clean up
+        // after itself (e.g. remove its metadata).
+        let mut namespaces: Vec<Vec<u8>> = Vec::new();
+        for key in storage.keys(Bound::Unbounded, Bound::Unbounded) {
+            let namespace =
+                match split_key::<F, V::KeyDecodeError>(<(K::Kind, V::Kind)>::BEHAVIOR, &key) {
+                    Ok((_, rest)) => key[..key.len() - rest.len()].to_vec(),
+                    // Malformed key - it doesn't belong to any entry, but it's in our namespace.
+                    Err(_) => key,
+                };
+
+            if namespaces.last() != Some(&namespace) {
+                namespaces.push(namespace);
             }
         }
-    }
 
-    fn decode_value(value: &[u8]) -> Result<Self::Value, Self::ValueDecodeError> {
-        V::decode_value(value)
+        for namespace in namespaces {
+            V::clear_impl(&mut StorageBranch::new(&mut *storage, namespace));
+        }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
 #[error("invalid key length, expected empty key")]
 pub enum MapKeyDecodeError<I: std::fmt::Display> {
     #[error("empty key, expected length prefix (1 byte)")]
     EmptyKey,
 
-    #[error("key too short, expected {0} bytes after length prefix")]
+    #[error("key too short, expected {0} bytes of key")]
     KeyTooShort(usize),
 
-    #[error("invalid UTF8")]
-    InvalidUtf8,
+    #[error("malformed key frame")]
+    MalformedFrame,
+
+    #[error("invalid key length, expected {expected} bytes, found {found}")]
+    KeyLengthMismatch { expected: usize, found: usize },
+
+    #[error("invalid key: {0}")]
+    InvalidKey(String),
 
     #[error("sub key decode error: {0}")]
     Inner(I),
@@ -184,19 +383,46 @@ impl<I: std::fmt::Display> crate::error::StoreyError for MapKeyDecodeError<I> {}
 /// An accessor for a map.
 ///
 /// The accessor provides methods for interacting with the map in storage.
-pub struct MapAccess<K: ?Sized, V, S> {
+pub struct MapAccess<K: ?Sized, V, S, F = ByteLenPrefix> {
     storage: S,
-    phantom: PhantomData<(*const K, V)>,
+    phantom: PhantomData<(*const K, V, F)>,
+}
+
+impl<K, V, S, F> MapAccess<K, V, StorageBranch<S>, F> {
+    /// Returns the prefix of the map's namespace. See [`StorageBranch::prefix`].
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let storage = TestStorage::new();
+    /// let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(1);
+    ///
+    /// assert_eq!(map.access(&storage).prefix(), &[1]);
+    /// assert_eq!(map.access(&storage).entry("foo").prefix(), b"\x03foo");
+    /// ```
+    pub fn prefix(&self) -> &[u8] {
+        self.storage.prefix()
+    }
 }
 
-impl<K, V, S> MapAccess<K, V, S>
+impl<K, V, S, F> MapAccess<K, V, S, F>
 where
     K: Key,
     V: Storable,
+    F: KeyFraming,
     (K::Kind, V::Kind): KeyEncodingT,
 {
     /// Returns an immutable accessor for the inner container of this map.
     ///
+    /// For nested containers, the returned accessor is scoped to `key`. Iterating over it
+    /// yields only the remaining part of the key, fully decoded.
+    ///
+    /// A key the map's [`KeyFraming`] can't represent can't have been written, so its entry
+    /// reads as absent.
+    ///
     /// # Examples
     ///
     /// ```
@@ -227,14 +453,10 @@ where
         K: Borrow<Q>,
         Q: Key<Kind = K::Kind> + ?Sized,
     {
-        let behavior = <(K::Kind, V::Kind)>::BEHAVIOR;
-
-        let key = match behavior {
-            KeyEncoding::LenPrefix => len_prefix(key.encode()),
-            _ => key.encode(),
-        };
-
-        V::access_impl(StorageBranch::new(&self.storage, key))
+        match Map::<K, V, F>::encode_key(key) {
+            Ok(key) => V::access_impl(StorageBranch::new(&self.storage, key)),
+            Err(_) => V::access_impl(StorageBranch::detached(&self.storage)),
+        }
     }
 
     /// Returns a mutable accessor for the inner container of this map.
@@ -266,39 +488,127 @@ where
     /// access.entry_mut("foo").entry_mut("bar").set(&1337).unwrap();
     /// assert_eq!(access.entry("foo").entry("bar").get().unwrap(), Some(1337));
     /// ```
+    ///
+    /// The key is encoded once, when the accessor is created. For repeated operations on the
+    /// same entry, keep the accessor around rather than calling `entry_mut` again - operations
+    /// on an [`Item`] accessor then don't allocate a new key each time.
+    ///
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// let mut counter = access.entry_mut("foo");
+    /// for _ in 0..10 {
+    ///     let value = counter.get().unwrap().unwrap_or(0);
+    ///     counter.set(&(value + 1)).unwrap();
+    /// }
+    ///
+    /// assert_eq!(access.entry("foo").get().unwrap(), Some(10));
+    /// ```
+    ///
+    /// If the key has to be framed and the map's [`KeyFraming`] can't represent it (e.g. a key
+    /// longer than 255 bytes with [`ByteLenPrefix`]), the entry can't hold any data. Just like
+    /// with [`entry`](Self::entry), reads through the accessor find nothing - and writes are
+    /// discarded. Use [`try_entry_mut`](Self::try_entry_mut) for keys that may not fit.
     pub fn entry_mut<Q>(&mut self, key: &Q) -> V::Accessor<StorageBranch<&mut S>>
     where
         K: Borrow<Q>,
         Q: Key<Kind = K::Kind> + ?Sized,
     {
-        let behavior = <(K::Kind, V::Kind)>::BEHAVIOR;
+        match Map::<K, V, F>::encode_key(key) {
+            Ok(key) => V::access_impl(StorageBranch::new(&mut self.storage, key)),
+            Err(_) => V::access_impl(StorageBranch::detached(&mut self.storage)),
+        }
+    }
 
-        let key = match behavior {
-            KeyEncoding::LenPrefix => len_prefix(key.encode()),
-            _ => key.encode(),
-        };
+    /// Returns a mutable accessor for the inner container of this map, or an error if the key
+    /// has to be framed and the map's [`KeyFraming`] can't represent it.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::map::FramingError;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.try_entry_mut("foo").unwrap().entry_mut(&1).set(&1337).unwrap();
+    /// assert!(matches!(
+    ///     access.try_entry_mut(&"x".repeat(256)),
+    ///     Err(FramingError::Unrepresentable)
+    /// ));
+    /// ```
+    pub fn try_entry_mut<Q>(
+        &mut self,
+        key: &Q,
+    ) -> Result<V::Accessor<StorageBranch<&mut S>>, FramingError>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        let key = Map::<K, V, F>::encode_key(key)?;
 
-        V::access_impl(StorageBranch::new(&mut self.storage, key))
+        Ok(V::access_impl(StorageBranch::new(&mut self.storage, key)))
     }
 }
 
-fn len_prefix<T: AsRef<[u8]>>(bytes: T) -> Vec<u8> {
-    let len = bytes.as_ref().len();
-    let mut result = Vec::with_capacity(len + 1);
-    result.extend_from_slice(&(len as u8).to_be_bytes());
-    result.extend_from_slice(bytes.as_ref());
-    result
+/// Splits a raw key into the part encoding this map's key and the part belonging to
+/// the inner container.
+///
+/// The framing is validated: a declared length must fit within the key, and a fixed-size
+/// key must be present in full - or, if nothing follows it, consume the key exactly.
+/// Whatever is left over is validated by the inner container's own `decode_key`.
+fn split_key<F: KeyFraming, I: std::fmt::Display>(
+    behavior: KeyEncoding,
+    key: &[u8],
+) -> Result<(&[u8], &[u8]), MapKeyDecodeError<I>> {
+    match behavior {
+        KeyEncoding::LenPrefix => F::split(key).map_err(|e| match e {
+            FramingError::Empty => MapKeyDecodeError::EmptyKey,
+            FramingError::TooShort(len) => MapKeyDecodeError::KeyTooShort(len),
+            FramingError::Malformed | FramingError::Unrepresentable => {
+                MapKeyDecodeError::MalformedFrame
+            }
+        }),
+        KeyEncoding::UseRest => Ok((key, &[])),
+        KeyEncoding::UseN(n) => {
+            if key.len() < n {
+                return Err(MapKeyDecodeError::KeyTooShort(n));
+            }
+
+            Ok(key.split_at(n))
+        }
+        KeyEncoding::UseExact(n) => {
+            if key.len() != n {
+                return Err(MapKeyDecodeError::KeyLengthMismatch {
+                    expected: n,
+                    found: key.len(),
+                });
+            }
+
+            Ok((key, &[]))
+        }
+    }
 }
 
-impl<K, V, S> IterableAccessor for MapAccess<K, V, S>
+impl<K, V, S, F> IterableAccessor for MapAccess<K, V, S, F>
 where
     K: OwnedKey,
     V: Storable,
     <V as Storable>::KeyDecodeError: std::fmt::Display,
     S: IterableStorage,
+    F: KeyFraming,
     (K::Kind, V::Kind): KeyEncodingT,
 {
-    type Storable = Map<K, V>;
+    type Storable = Map<K, V, F>;
     type Storage = S;
 
     fn storage(&self) -> &Self::Storage {
@@ -306,205 +616,1792 @@ where
     }
 }
 
-// The following dance is necessary to make bounded iteration unavailable for maps
-// that have both dynamic keys and "non-terminal" values (i.e. maps of maps, maps of columns, etc).
-//
-// This is because in cases where the key is dynamically size **and** there's another key
-// after it, we have to length-prefix the key. This makes bounded iteration behave differently
-// than in other cases (and rather unintuitively).
-
-impl<K, V, S> BoundedIterableAccessor for MapAccess<K, V, S>
+impl<K, V, S, F> MapAccess<K, V, S, F>
 where
     K: OwnedKey,
     V: Storable,
     <V as Storable>::KeyDecodeError: std::fmt::Display,
     S: IterableStorage,
-    (K::Kind, V::Kind): BoundedIterationAllowed + KeyEncodingT,
-{
-}
-
-trait BoundedIterationAllowed {}
-
-impl<const L: usize> BoundedIterationAllowed for (FixedSizeKey<L>, Terminal) {}
-impl<const L: usize> BoundedIterationAllowed for (FixedSizeKey<L>, NonTerminal) {}
-impl BoundedIterationAllowed for (DynamicKey, Terminal) {}
-
-impl<K, V, Q> BoundFor<Map<K, V>> for &Q
-where
-    K: Borrow<Q> + OwnedKey,
-    V: Storable,
-    Q: Key + ?Sized,
+    F: KeyFraming,
     (K::Kind, V::Kind): KeyEncodingT,
 {
-    fn into_bytes(self) -> Vec<u8> {
-        let behavior = <(K::Kind, V::Kind)>::BEHAVIOR;
-
-        match behavior {
-            KeyEncoding::LenPrefix => len_prefix(self.encode()),
-            _ => self.encode(),
-        }
+    /// Iterate over the raw key-value pairs stored in this map's namespace.
+    ///
+    /// Nothing is decoded - keys are returned relative to the map's prefix, framing
+    /// (e.g. length prefixes) included, and values are returned exactly as stored. The bounds
+    /// are raw keys too. This is meant for tooling like replication and state dumps, and never
+    /// fails on malformed data.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use std::ops::Bound;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").set(&1337).unwrap();
+    ///
+    /// let raw = access
+    ///     .iter_raw_bytes(Bound::Unbounded, Bound::Unbounded)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(raw, vec![(b"foo".to_vec(), 1337u64.to_le_bytes().to_vec())]);
+    /// ```
+    pub fn iter_raw_bytes(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> S::PairsIterator<'_> {
+        self.storage.pairs(start, end)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::ops::Bound;
-
-    use super::*;
-
-    use crate::containers::Item;
-
-    use mocks::backend::TestStorage;
-    use mocks::encoding::TestEncoding;
-    use storey_storage::Storage as _;
+    /// Write the raw contents of the map to `writer`, returning the number of entries written.
+    ///
+    /// The stream can be restored into a map with the same layout (not necessarily under the
+    /// same prefix, or in the same storage) with [`import`](Self::import). See
+    /// [`storage::export`](crate::storage::export) for the format.
+    ///
+    /// Metadata of nested containers (e.g. the length of a
+    /// [`Column`](crate::containers::Column)) isn't part of the stream.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// map.access(&mut storage).entry_mut("foo").set(&1337).unwrap();
+    ///
+    /// let mut backup = Vec::new();
+    /// map.access(&storage).export(&mut backup).unwrap();
+    ///
+    /// let mut restored = TestStorage::new();
+    /// let copy = Map::<String, Item<u64, TestEncoding>>::new(7);
+    /// copy.access(&mut restored).import(&backup[..]).unwrap();
+    ///
+    /// assert_eq!(copy.access(&restored).entry("foo").get().unwrap(), Some(1337));
+    /// ```
+    pub fn export<W: std::io::Write>(&self, writer: W) -> std::io::Result<usize> {
+        crate::storage::export(&self.storage, writer)
+    }
 
-    #[test]
-    fn map() {
-        let mut storage = TestStorage::new();
+    /// Hash the raw contents of the map with the digest `D`.
+    ///
+    /// Entries are hashed in storage order, without decoding anything. Each entry is fed
+    /// to the hasher as the big-endian `u32` length of the raw key, the raw key, the
+    /// big-endian `u32` length of the raw value and the raw value. For nested containers,
+    /// every entry of every inner container is included.
+    ///
+    /// The result is deterministic: two maps with the same stored bytes hash the same,
+    /// regardless of the encoding used.
+    ///
+    /// Only available with the `digest` feature enabled.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use sha2::Sha256;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    ///
+    /// let empty = map.access(&storage).hash::<Sha256>();
+    ///
+    /// map.access(&mut storage).entry_mut("foo").set(&1337).unwrap();
+    /// assert_ne!(map.access(&storage).hash::<Sha256>(), empty);
+    /// ```
+    #[cfg(feature = "digest")]
+    pub fn hash<D: digest::Digest>(&self) -> digest::Output<D> {
+        let mut hasher = D::new();
+
+        for (key, value) in self.storage.pairs(Bound::Unbounded, Bound::Unbounded) {
+            hasher.update((key.len() as u32).to_be_bytes());
+            hasher.update(&key);
+            hasher.update((value.len() as u32).to_be_bytes());
+            hasher.update(&value);
+        }
 
-        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        hasher.finalize()
+    }
 
-        map.access(&mut storage)
+    /// Count the entries stored in this map.
+    ///
+    /// For maps of non-terminal containers (e.g. maps of maps), this counts the entries of all
+    /// nested containers, rather than the number of keys of this map. Nothing is decoded, and
+    /// backends that can count entries natively do so without iterating (see
+    /// [`IterableStorage::count_range`]).
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("alice").entry_mut("eur").set(&1).unwrap();
+    /// access.entry_mut("alice").entry_mut("usd").set(&2).unwrap();
+    /// access.entry_mut("bob").entry_mut("eur").set(&3).unwrap();
+    ///
+    /// assert_eq!(access.count_entries(), 3);
+    /// assert_eq!(access.entry("alice").count_entries(), 2);
+    /// ```
+    pub fn count_entries(&self) -> usize {
+        self.storage.count_range(Bound::Unbounded, Bound::Unbounded)
+    }
+
+    /// Returns whether anything is stored under `key`.
+    ///
+    /// For maps of non-terminal containers (e.g. maps of maps), this checks whether the
+    /// nested container under `key` has any entries - the equivalent of checking a partial
+    /// composite key. At most one key is read and nothing is decoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Map<u64, Item<u64, TestEncoding>>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("alice").entry_mut(&1).set(&100).unwrap();
+    ///
+    /// assert!(access.contains_prefix("alice"));
+    /// assert!(!access.contains_prefix("bob"));
+    /// assert!(!access.contains_prefix("al"));
+    /// ```
+    pub fn contains_prefix<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        let Ok(key) = Map::<K, V, F>::encode_key(key) else {
+            // keys that can't be framed can't have been written
+            return false;
+        };
+
+        let end = Map::<K, V, F>::entry_end(&key);
+        self.storage
+            .keys(Bound::Included(&key), end.as_ref().map(Vec::as_slice))
+            .next()
+            .is_some()
+    }
+
+    /// Returns the smallest key in this map, or `None` if the map is empty.
+    ///
+    /// Only the key is decoded - the value is never read. For maps of non-terminal
+    /// containers (e.g. maps of maps), this is the outer key of the first entry.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// assert!(access.first_key().is_none());
+    ///
+    /// access.entry_mut(&5).set(&1).unwrap();
+    /// access.entry_mut(&3).set(&2).unwrap();
+    /// assert_eq!(access.first_key().unwrap().unwrap(), 3);
+    /// ```
+    pub fn first_key(&self) -> Option<Result<K, MapKeyDecodeError<V::KeyDecodeError>>> {
+        let key = self
+            .storage
+            .keys(Bound::Unbounded, Bound::Unbounded)
+            .next()?;
+
+        Some(decode_map_key::<K, V, F>(&key))
+    }
+
+    /// Returns the largest key in this map, or `None` if the map is empty.
+    ///
+    /// Only the key is decoded - the value is never read. For maps of non-terminal
+    /// containers (e.g. maps of maps), this is the outer key of the last entry.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// assert!(access.last_key().is_none());
+    ///
+    /// access.entry_mut(&5).set(&1).unwrap();
+    /// access.entry_mut(&3).set(&2).unwrap();
+    /// assert_eq!(access.last_key().unwrap().unwrap(), 5);
+    /// ```
+    pub fn last_key(&self) -> Option<Result<K, MapKeyDecodeError<V::KeyDecodeError>>>
+    where
+        S: RevIterableStorage,
+    {
+        let key = self
+            .storage
+            .rev_keys(Bound::Unbounded, Bound::Unbounded)
+            .next()?;
+
+        Some(decode_map_key::<K, V, F>(&key))
+    }
+}
+
+/// An error that can occur when decoding an entry of a map of terminal values, e.g. in
+/// [`ceil`](MapAccess::ceil) or [`range_collect`](MapAccess::range_collect).
+pub type EntryDecodeError<V> = KVDecodeError<
+    MapKeyDecodeError<<V as Storable>::KeyDecodeError>,
+    <V as Storable>::ValueDecodeError,
+>;
+
+/// The result of a [`ceil`](MapAccess::ceil) or [`floor`](MapAccess::floor) lookup.
+pub type NearestEntry<K, V> = Option<Result<(K, <V as Storable>::Value), EntryDecodeError<V>>>;
+
+impl<K, V, S, F> MapAccess<K, V, S, F>
+where
+    K: OwnedKey,
+    V: Storable<Kind = Terminal>,
+    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    S: IterableStorage,
+    F: KeyFraming,
+    (K::Kind, V::Kind): KeyEncodingT,
+{
+    /// Returns the entry with the smallest key greater than or equal to `key`, or `None` if
+    /// there's no such entry.
+    ///
+    /// Only a single entry is read. Keys are compared in their encoded order, which for
+    /// integers is numeric order.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let ticks = Map::<u32, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = ticks.access(&mut storage);
+    ///
+    /// access.entry_mut(&10).set(&100).unwrap();
+    /// access.entry_mut(&20).set(&200).unwrap();
+    ///
+    /// assert_eq!(access.ceil(&10).unwrap().unwrap(), (10, 100));
+    /// assert_eq!(access.ceil(&11).unwrap().unwrap(), (20, 200));
+    /// assert!(access.ceil(&21).is_none());
+    /// ```
+    pub fn ceil<Q>(&self, key: &Q) -> NearestEntry<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        // terminal values, so the key is never framed
+        let key = key.encode();
+        let pair = self
+            .storage
+            .pairs(Bound::Included(&key), Bound::Unbounded)
+            .next();
+
+        pair.map(|(key, value)| decode_entry::<K, V, F>(&key, &value))
+    }
+
+    /// Returns the entry with the largest key less than or equal to `key`, or `None` if
+    /// there's no such entry.
+    ///
+    /// This is the mirror image of [`ceil`](Self::ceil).
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let ticks = Map::<u32, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = ticks.access(&mut storage);
+    ///
+    /// access.entry_mut(&10).set(&100).unwrap();
+    /// access.entry_mut(&20).set(&200).unwrap();
+    ///
+    /// assert_eq!(access.floor(&20).unwrap().unwrap(), (20, 200));
+    /// assert_eq!(access.floor(&19).unwrap().unwrap(), (10, 100));
+    /// assert!(access.floor(&9).is_none());
+    /// ```
+    pub fn floor<Q>(&self, key: &Q) -> NearestEntry<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+        S: RevIterableStorage,
+    {
+        // terminal values, so the key is never framed
+        let key = key.encode();
+        let pair = self
+            .storage
+            .rev_pairs(Bound::Unbounded, Bound::Included(&key))
+            .next();
+
+        pair.map(|(key, value)| decode_entry::<K, V, F>(&key, &value))
+    }
+
+    /// Collects the entries with keys within the given bounds, in key order, stopping after
+    /// `limit` entries if one is given.
+    ///
+    /// This is a shorthand for the usual "read a slice of a sorted map" loop. Only the entries
+    /// that end up in the result are read. If any of them fails to decode, the first error is
+    /// returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use std::ops::Bound;
+    ///
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// for i in 1..=5 {
+    ///     access.entry_mut(&i).set(&(i as u64 * 10)).unwrap();
+    /// }
+    ///
+    /// let page = access
+    ///     .range_collect(Bound::Excluded(&1), Bound::Unbounded, Some(2))
+    ///     .unwrap();
+    /// assert_eq!(page, vec![(2, 20), (3, 30)]);
+    /// ```
+    pub fn range_collect<B>(
+        &self,
+        start: Bound<B>,
+        end: Bound<B>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(K, V::Value)>, EntryDecodeError<V>>
+    where
+        B: BoundFor<Map<K, V, F>>,
+    {
+        let start = start.map(|b| b.into_bytes());
+        let end = end.map(|b| b.into_bytes());
+
+        self.storage
+            .pairs(
+                start.as_ref().map(Vec::as_slice),
+                end.as_ref().map(Vec::as_slice),
+            )
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|(key, value)| decode_entry::<K, V, F>(&key, &value))
+            .collect()
+    }
+}
+
+impl<K, V, S, F> MapAccess<K, V, S, F>
+where
+    K: OwnedKey,
+    V: Storable,
+    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    S: Storage + IterableStorage + StorageMut,
+    F: KeyFraming,
+    (K::Kind, V::Kind): KeyEncodingT,
+{
+    /// Remove all entries from the map.
+    ///
+    /// The whole namespace of each entry is wiped, including the metadata of nested
+    /// containers (e.g. the length of a [`Column`](crate::containers::Column)), so nothing
+    /// is left behind.
+    ///
+    /// Nested containers are found by iterating over entries. A nested container that holds
+    /// metadata, but no entries at all, can't be discovered and is left untouched.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Column, IterableAccessor as _, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Column<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").push(&1337).unwrap();
+    /// access.clear();
+    ///
+    /// assert_eq!(access.keys().count(), 0);
+    /// assert_eq!(access.entry("foo").len().unwrap(), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        Map::<K, V, F>::clear_impl(&mut self.storage)
+    }
+
+    /// Read entries produced by [`export`](Self::export) from `reader` into the map, returning
+    /// the number of entries imported.
+    ///
+    /// Entries already in the map are kept unless the stream overwrites them. Call
+    /// [`clear`](Self::clear) first to get an exact copy. See
+    /// [`storage::import`](crate::storage::import) for details.
+    pub fn import<R: std::io::Read>(&mut self, reader: R) -> Result<usize, ImportError> {
+        crate::storage::import(&mut self.storage, reader)
+    }
+}
+
+impl<K, T, E, S, F> MapAccess<K, Item<T, E>, S, F>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage,
+    F: KeyFraming,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    /// Get the value under `key`, or `None` if there's no such entry.
+    ///
+    /// This is a shorthand for `access.entry(key).get()`.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").set(&1337).unwrap();
+    ///
+    /// assert_eq!(access.get_value("foo").unwrap(), Some(1337));
+    /// assert_eq!(access.get_value("bar").unwrap(), None);
+    /// ```
+    pub fn get_value<Q>(&self, key: &Q) -> Result<Option<T>, E::DecodeError>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        self.entry(key).get()
+    }
+
+    /// Get the value under `key`, or `default` if there's no such entry.
+    ///
+    /// This is a shorthand for `access.entry(key).get_or(default)`.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").set(&1337).unwrap();
+    ///
+    /// assert_eq!(access.get_or("foo", 42).unwrap(), 1337);
+    /// assert_eq!(access.get_or("bar", 42).unwrap(), 42);
+    /// ```
+    pub fn get_or<Q>(&self, key: &Q, default: T) -> Result<T, E::DecodeError>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        self.entry(key).get_or(default)
+    }
+
+    /// Get the value under `key`, or `T::default()` if there's no such entry.
+    ///
+    /// This is a shorthand for `access.entry(key).get_or_default()`.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").set(&1337).unwrap();
+    ///
+    /// assert_eq!(access.get_or_default("foo").unwrap(), 1337);
+    /// assert_eq!(access.get_or_default("bar").unwrap(), 0);
+    /// ```
+    pub fn get_or_default<Q>(&self, key: &Q) -> Result<T, E::DecodeError>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+        T: Default,
+    {
+        self.entry(key).get_or_default()
+    }
+}
+
+impl<K, T, E, S, F> MapAccess<K, Item<T, E>, S, F>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage + StorageMut,
+    F: KeyFraming,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    /// Rewrite every value in the map with `f`.
+    ///
+    /// Entries are collected before anything is written, so `f` sees the map as it was
+    /// before the call. If any entry fails to decode or its new value fails to encode,
+    /// nothing is written. Returns the number of entries written.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").set(&1).unwrap();
+    /// access.entry_mut("bar").set(&2).unwrap();
+    ///
+    /// assert_eq!(access.modify_all(|_, value| value * 10).unwrap(), 2);
+    /// assert_eq!(access.entry("bar").get().unwrap(), Some(20));
+    /// ```
+    pub fn modify_all<M>(&mut self, f: M) -> Result<usize, ModifyAllError<E>>
+    where
+        M: FnMut(&K, T) -> T,
+    {
+        self.modify_all_impl(f, false)
+    }
+
+    /// Like [`modify_all`](Self::modify_all), but skips writing entries whose re-encoded
+    /// value is byte-identical to what's already stored.
+    ///
+    /// This is useful for migrations that leave most entries unchanged, since a skipped
+    /// entry costs a read but no write. Returns the number of entries actually written.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").set(&1).unwrap();
+    /// access.entry_mut("bar").set(&200).unwrap();
+    ///
+    /// // cap values at 100 - only "bar" needs a write
+    /// assert_eq!(access.modify_all_dedup(|_, value| value.min(100)).unwrap(), 1);
+    /// assert_eq!(access.entry("bar").get().unwrap(), Some(100));
+    /// ```
+    pub fn modify_all_dedup<M>(&mut self, f: M) -> Result<usize, ModifyAllError<E>>
+    where
+        M: FnMut(&K, T) -> T,
+    {
+        self.modify_all_impl(f, true)
+    }
+
+    fn modify_all_impl<M>(&mut self, mut f: M, dedup: bool) -> Result<usize, ModifyAllError<E>>
+    where
+        M: FnMut(&K, T) -> T,
+    {
+        let entries = self
+            .storage
+            .pairs(Bound::Unbounded, Bound::Unbounded)
+            .collect::<Vec<_>>();
+
+        let mut writes = Vec::with_capacity(entries.len());
+        for (raw_key, raw_value) in entries {
+            let key = decode_map_key::<K, Item<T, E>, F>(&raw_key)
+                .map_err(|e| ModifyError::Decode(KVDecodeError::Key(e)))?;
+            let value = Item::<T, E>::decode_value(&raw_value)
+                .map_err(|e| ModifyError::Decode(KVDecodeError::Value(e)))?;
+
+            let new_value =
+                Item::<T, E>::encode_value(&f(&key, value)).map_err(ModifyError::Encode)?;
+
+            if dedup && new_value == raw_value {
+                continue;
+            }
+
+            writes.push((raw_key, new_value));
+        }
+
+        // everything is decoded and re-encoded, so a failure can't leave the map half-modified
+        for (raw_key, new_value) in &writes {
+            self.storage.set(raw_key, new_value);
+        }
+
+        Ok(writes.len())
+    }
+}
+
+impl<K, T, E, S, F> MapAccess<K, ExpiringItem<T, E>, S, F>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage + StorageMut,
+    F: KeyFraming,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    /// Remove every entry that's expired at `height`, returning the number of entries removed.
+    ///
+    /// Only the expiry heights are read - values aren't decoded. Entries too short to hold an
+    /// expiry height are left alone.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{ExpiringItem, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, ExpiringItem<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").set(&1, 10).unwrap();
+    /// access.entry_mut("bar").set(&2, 20).unwrap();
+    ///
+    /// assert_eq!(access.purge_expired(10), 1);
+    /// assert_eq!(access.entry("foo").get_with_expiry().unwrap(), None);
+    /// assert_eq!(access.entry("bar").get(10).unwrap(), Some(2));
+    /// ```
+    pub fn purge_expired(&mut self, height: u64) -> usize {
+        let expired = self
+            .storage
+            .pairs(Bound::Unbounded, Bound::Unbounded)
+            .filter(|(_, value)| {
+                decode_expiry(value).is_some_and(|expires_at| height >= expires_at)
+            })
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+
+        for key in &expired {
+            self.storage.remove(key);
+        }
+
+        expired.len()
+    }
+}
+
+impl<K, T, E, S, F> MapAccess<K, Item<T, E>, S, F>
+where
+    K: OwnedKey,
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+    F: KeyFraming,
+    (K::Kind, Terminal): KeyEncodingT,
+{
+    /// Get a guard over the value under `key` that writes it back when dropped, if it was
+    /// modified. Returns `Ok(None)` if there's no such entry.
+    ///
+    /// This saves the explicit get/modify/set sequence. See [`ItemGuard`] for details,
+    /// including how encoding errors are handled.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("foo").set(&1337).unwrap();
+    ///
+    /// if let Some(mut value) = access.get_mut("foo").unwrap() {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(access.entry("foo").get().unwrap(), Some(1338));
+    /// assert!(access.get_mut("bar").unwrap().is_none());
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn get_mut<Q>(
+        &mut self,
+        key: &Q,
+    ) -> Result<Option<ItemGuard<E, T, StorageBranch<&mut S>>>, E::DecodeError>
+    where
+        K: Borrow<Q>,
+        Q: Key<Kind = K::Kind> + ?Sized,
+    {
+        ItemGuard::new(self.entry_mut(key))
+    }
+}
+
+/// The error returned by [`MapAccess::modify_all`] and [`MapAccess::modify_all_dedup`].
+pub type ModifyAllError<E> = ModifyError<
+    KVDecodeError<MapKeyDecodeError<ItemKeyDecodeError>, <E as Encoding>::DecodeError>,
+    <E as Encoding>::EncodeError,
+>;
+
+/// An error that can occur when modifying entries in bulk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum ModifyError<D, E> {
+    #[error("decode error: {0}")]
+    Decode(D),
+    #[error("encode error: {0}")]
+    Encode(E),
+}
+
+impl<D: std::fmt::Display, E: std::fmt::Display> crate::error::StoreyError for ModifyError<D, E> {}
+
+/// Decodes only this map's own key from a raw key, ignoring whatever belongs to the
+/// inner container.
+fn decode_map_key<K, V, F>(key: &[u8]) -> Result<K, MapKeyDecodeError<V::KeyDecodeError>>
+where
+    K: OwnedKey,
+    V: Storable,
+    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    F: KeyFraming,
+    (K::Kind, V::Kind): KeyEncodingT,
+{
+    let (map_key, _) = split_key::<F, _>(<(K::Kind, V::Kind)>::BEHAVIOR, key)?;
+
+    K::from_bytes(map_key).map_err(|e| MapKeyDecodeError::InvalidKey(e.to_string()))
+}
+
+fn decode_entry<K, V, F>(key: &[u8], value: &[u8]) -> Result<(K, V::Value), EntryDecodeError<V>>
+where
+    K: OwnedKey,
+    V: Storable,
+    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    F: KeyFraming,
+    (K::Kind, V::Kind): KeyEncodingT,
+{
+    let key = decode_map_key::<K, V, F>(key).map_err(KVDecodeError::Key)?;
+    let value = V::decode_value(value).map_err(KVDecodeError::Value)?;
+    Ok((key, value))
+}
+
+// The following dance is necessary to make bounded iteration unavailable for maps
+// that have both dynamic keys and "non-terminal" values (i.e. maps of maps, maps of columns, etc).
+//
+// This is because in cases where the key is dynamically size **and** there's another key
+// after it, we have to frame (e.g. length-prefix) the key. This makes bounded iteration behave
+// differently than in other cases (and rather unintuitively).
+
+impl<K, V, S, F> BoundedIterableAccessor for MapAccess<K, V, S, F>
+where
+    K: OwnedKey,
+    V: Storable,
+    <V as Storable>::KeyDecodeError: std::fmt::Display,
+    S: IterableStorage,
+    F: KeyFraming,
+    (K::Kind, V::Kind): BoundedIterationAllowed + KeyEncodingT,
+{
+}
+
+trait BoundedIterationAllowed {}
+
+impl<const L: usize> BoundedIterationAllowed for (FixedSizeKey<L>, Terminal) {}
+impl<const L: usize> BoundedIterationAllowed for (FixedSizeKey<L>, NonTerminal) {}
+impl BoundedIterationAllowed for (DynamicKey, Terminal) {}
+
+impl<K, V, F, Q> BoundFor<Map<K, V, F>> for &Q
+where
+    K: Borrow<Q> + OwnedKey,
+    V: Storable,
+    Q: Key + ?Sized,
+    F: KeyFraming,
+    (K::Kind, V::Kind): BoundedIterationAllowed,
+{
+    fn into_bytes(self) -> Vec<u8> {
+        // bounded iteration is only allowed where keys are never framed
+        self.encode()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::*;
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+    use storey_storage::Storage as _;
+
+    #[test]
+    fn map() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+
+        map.access(&mut storage)
+            .entry_mut("foo")
+            .set(&1337)
+            .unwrap();
+
+        assert_eq!(map.access(&storage).entry("foo").get().unwrap(), Some(1337));
+        assert_eq!(
+            storage.get(&[0, 102, 111, 111]),
+            Some(1337u64.to_le_bytes().to_vec())
+        );
+        map.access(&mut storage).entry_mut("foo").remove();
+
+        assert_eq!(map.access(&storage).entry("foo").get().unwrap(), None);
+        assert_eq!(map.access(&storage).entry("bar").get().unwrap(), None);
+    }
+
+    #[test]
+    fn runtime_prefix() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::with_prefix(vec![1, 2, 3]);
+        map.access(&mut storage)
             .entry_mut("foo")
             .set(&1337)
             .unwrap();
 
-        assert_eq!(map.access(&storage).entry("foo").get().unwrap(), Some(1337));
+        assert_eq!(map.access(&storage).entry("foo").get().unwrap(), Some(1337));
+        assert_eq!(
+            storage.get(&[1, 2, 3, 102, 111, 111]),
+            Some(1337u64.to_le_bytes().to_vec())
+        );
+    }
+
+    #[test]
+    fn raw_entry_roundtrip() {
+        let mut storage = TestStorage::new();
+
+        type M = Map<String, Item<u64, TestEncoding>>;
+        let map = M::new(0);
+
+        let key = [&[0], &M::encode_key("foo").unwrap()[..]].concat();
+        let value = M::encode_value(&1337).unwrap();
+        storey_storage::StorageMut::set(&mut storage, &key, &value);
+        assert_eq!(map.access(&storage).entry("foo").get().unwrap(), Some(1337));
+
+        map.access(&mut storage).entry_mut("bar").set(&42).unwrap();
+        let raw = storage.get(&[0, 98, 97, 114]).unwrap();
+        assert_eq!(M::decode_value(&raw).unwrap(), 42);
+
+        type Nested = Map<String, Map<String, Item<u64, TestEncoding>>>;
+        assert_eq!(Nested::encode_key("foo"), Ok(vec![3, 102, 111, 111]));
+    }
+
+    #[test]
+    fn bounded_iter_dyn_map_of_item() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut("foo").set(&1337).unwrap();
+        access.entry_mut("bar").set(&42).unwrap();
+        access.entry_mut("baz").set(&69).unwrap();
+
+        let items = access
+            .bounded_pairs(Bound::Included("bar"), Bound::Excluded("bazz"))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            items,
+            vec![(("bar".to_string(), ()), 42), (("baz".to_string(), ()), 69)]
+        );
+
+        let items = access
+            .bounded_pairs(Bound::Excluded("bar"), Bound::Included("foo"))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            items,
+            vec![
+                (("baz".to_string(), ()), 69),
+                (("foo".to_string(), ()), 1337)
+            ]
+        );
+    }
+
+    #[test]
+    fn iter_static_map_of_item() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut("foo").set(&1337).unwrap();
+        access.entry_mut("bar").set(&42).unwrap();
+        access.entry_mut("baz").set(&69).unwrap();
+
+        let items = access.pairs().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            items,
+            vec![
+                (("bar".to_string(), ()), 42),
+                (("baz".to_string(), ()), 69),
+                (("foo".to_string(), ()), 1337)
+            ]
+        );
+    }
+
+    #[test]
+    fn bounded_iter_static_map_of_map() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<u32, Map<String, Item<u64, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut(&2).entry_mut("bar").set(&1337).unwrap();
+        access.entry_mut(&3).entry_mut("baz").set(&42).unwrap();
+        access.entry_mut(&4).entry_mut("quux").set(&69).unwrap();
+
+        let items = access
+            .bounded_pairs(Bound::Included(&2), Bound::Excluded(&4))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            items,
+            vec![
+                ((2, ("bar".to_string(), ())), 1337),
+                ((3, ("baz".to_string(), ())), 42)
+            ]
+        );
+    }
+
+    #[test]
+    fn pairs() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut("foo").set(&1337).unwrap();
+        access.entry_mut("bar").set(&42).unwrap();
+
+        let items = access.pairs().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            items,
+            vec![
+                (("bar".to_string(), ()), 42),
+                (("foo".to_string(), ()), 1337)
+            ]
+        );
+    }
+
+    #[test]
+    fn keys() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut("foo").set(&1337).unwrap();
+        access.entry_mut("bar").set(&42).unwrap();
+
+        let keys = access.keys().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(keys, vec![("bar".to_string(), ()), ("foo".to_string(), ())])
+    }
+
+    #[test]
+    fn values() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut("foo").set(&1337).unwrap();
+        access.entry_mut("bar").set(&42).unwrap();
+
+        let values = access.values().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(values, vec![42, 1337])
+    }
+
+    #[test]
+    fn values_skip_key_decoding() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        map.access(&mut storage).entry_mut("foo").set(&1).unwrap();
+        storey_storage::StorageMut::set(&mut storage, &[0, 0xff], &2u64.to_le_bytes());
+
+        let access = map.access(&storage);
+        assert!(access.keys().any(|k| k.is_err()));
+
+        let values = access.values().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(values, vec![1, 2]);
+
+        let values = access
+            .bounded_values(Bound::Included("g"), Bound::Unbounded)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, vec![2]);
+    }
+
+    #[test]
+    fn first_and_last_key() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<u32, Map<String, Item<u64, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        assert!(access.first_key().is_none());
+        assert!(access.last_key().is_none());
+
+        access.entry_mut(&7).entry_mut("foo").set(&1).unwrap();
+        access.entry_mut(&2).entry_mut("bar").set(&2).unwrap();
+        access.entry_mut(&7).entry_mut("baz").set(&3).unwrap();
+
+        // values are never decoded
+        storey_storage::StorageMut::set(&mut storage, &[0, 0, 0, 0, 9, b'x'], b"not a u64");
+        let access = map.access(&storage);
+
+        assert_eq!(access.first_key().unwrap().unwrap(), 2);
+        assert_eq!(access.last_key().unwrap().unwrap(), 9);
+    }
+
+    #[test]
+    fn peeking_decodes_once() {
+        use std::cell::Cell;
+
+        use crate::containers::StorableIter;
+
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+        access.entry_mut("a").entry_mut(&1).set(&1).unwrap();
+        access.entry_mut("a").entry_mut(&2).set(&2).unwrap();
+        access.entry_mut("b").entry_mut(&1).set(&3).unwrap();
+
+        // every raw entry pulled from storage gets decoded
+        let decoded = Cell::new(0);
+        let mut iter = StorableIter::<Map<String, Map<u32, Item<u64, TestEncoding>>>, _> {
+            inner: access
+                .storage()
+                .pairs(Bound::Unbounded, Bound::Unbounded)
+                .inspect(|_| decoded.set(decoded.get() + 1)),
+            phantom: PhantomData,
+        }
+        .peekable();
+
+        assert!(iter.peek().is_some());
+        assert!(iter.peek().is_some());
+        assert_eq!(decoded.get(), 1);
+
+        let groups = std::iter::from_fn(|| {
+            let ((outer, _), first) = iter.next()?.unwrap();
+            let mut sum = first;
+            while let Some(Ok((_, v))) =
+                iter.next_if(|e| matches!(e, Ok(((k, _), _)) if *k == outer))
+            {
+                sum += v;
+            }
+            Some((outer, sum))
+        })
+        .collect::<Vec<_>>();
+
+        assert_eq!(groups, vec![("a".to_string(), 3), ("b".to_string(), 3)]);
+        assert_eq!(decoded.get(), 3);
+    }
+
+    #[test]
+    fn range_collect() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        assert_eq!(
+            access.range_collect::<&str>(Bound::Unbounded, Bound::Unbounded, None),
+            Ok(vec![])
+        );
+
+        for (i, key) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            access.entry_mut(key).set(&(i as u64)).unwrap();
+        }
+
+        let all = access
+            .range_collect::<&str>(Bound::Unbounded, Bound::Unbounded, None)
+            .unwrap();
+        assert_eq!(all.len(), 4);
+
+        let range = access
+            .range_collect(Bound::Included("b"), Bound::Excluded("d"), None)
+            .unwrap();
+        assert_eq!(range, vec![("b".to_string(), 1), ("c".to_string(), 2)]);
+
+        let limited = access
+            .range_collect(Bound::Excluded("a"), Bound::Unbounded, Some(2))
+            .unwrap();
+        assert_eq!(limited, vec![("b".to_string(), 1), ("c".to_string(), 2)]);
+
+        assert_eq!(
+            access.range_collect::<&str>(Bound::Unbounded, Bound::Unbounded, Some(0)),
+            Ok(vec![])
+        );
+
+        // entries past the limit are never decoded
+        storey_storage::StorageMut::set(&mut storage, &[0, b'e'], b"not a u64");
+        let access = map.access(&storage);
         assert_eq!(
-            storage.get(&[0, 102, 111, 111]),
-            Some(1337u64.to_le_bytes().to_vec())
+            access
+                .range_collect::<&str>(Bound::Unbounded, Bound::Unbounded, Some(4))
+                .unwrap()
+                .len(),
+            4
         );
-        map.access(&mut storage).entry_mut("foo").remove();
+        assert!(matches!(
+            access.range_collect::<&str>(Bound::Unbounded, Bound::Unbounded, None),
+            Err(KVDecodeError::Value(_))
+        ));
+    }
 
-        assert_eq!(map.access(&storage).entry("foo").get().unwrap(), None);
-        assert_eq!(map.access(&storage).entry("bar").get().unwrap(), None);
+    #[test]
+    fn ceil_and_floor() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<i32, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        assert!(access.ceil(&0).is_none());
+        assert!(access.floor(&0).is_none());
+
+        access.entry_mut(&-5).set(&1).unwrap();
+        access.entry_mut(&0).set(&2).unwrap();
+        access.entry_mut(&5).set(&3).unwrap();
+
+        // entries outside the map are never picked up
+        Item::<u64, TestEncoding>::new(1)
+            .access(&mut storage)
+            .set(&4)
+            .unwrap();
+        let access = map.access(&storage);
+
+        assert_eq!(access.ceil(&i32::MIN).unwrap().unwrap(), (-5, 1));
+        assert_eq!(access.ceil(&-5).unwrap().unwrap(), (-5, 1));
+        assert_eq!(access.ceil(&-4).unwrap().unwrap(), (0, 2));
+        assert_eq!(access.ceil(&5).unwrap().unwrap(), (5, 3));
+        assert!(access.ceil(&6).is_none());
+        assert!(access.ceil(&i32::MAX).is_none());
+
+        assert_eq!(access.floor(&i32::MAX).unwrap().unwrap(), (5, 3));
+        assert_eq!(access.floor(&5).unwrap().unwrap(), (5, 3));
+        assert_eq!(access.floor(&4).unwrap().unwrap(), (0, 2));
+        assert_eq!(access.floor(&-5).unwrap().unwrap(), (-5, 1));
+        assert!(access.floor(&-6).is_none());
+        assert!(access.floor(&i32::MIN).is_none());
     }
 
     #[test]
-    fn bounded_iter_dyn_map_of_item() {
+    fn ceil_and_floor_string_keys() {
         let mut storage = TestStorage::new();
 
         let map = Map::<String, Item<u64, TestEncoding>>::new(0);
         let mut access = map.access(&mut storage);
 
-        access.entry_mut("foo").set(&1337).unwrap();
-        access.entry_mut("bar").set(&42).unwrap();
-        access.entry_mut("baz").set(&69).unwrap();
+        access.entry_mut("b").set(&1).unwrap();
+        access.entry_mut("bb").set(&2).unwrap();
+        access.entry_mut("c").set(&3).unwrap();
 
-        let items = access
-            .bounded_pairs(Bound::Included("bar"), Bound::Excluded("bazz"))
-            .collect::<Result<Vec<_>, _>>()
-            .unwrap();
+        assert_eq!(access.ceil("").unwrap().unwrap(), ("b".to_string(), 1));
+        assert_eq!(access.ceil("ba").unwrap().unwrap(), ("bb".to_string(), 2));
+        assert_eq!(access.floor("bz").unwrap().unwrap(), ("bb".to_string(), 2));
+        assert_eq!(access.floor("b").unwrap().unwrap(), ("b".to_string(), 1));
+        assert!(access.floor("a").is_none());
+        assert!(access.ceil("ca").is_none());
+    }
+
+    #[test]
+    fn clear_wipes_nested_namespaces() {
+        use crate::containers::Column;
+
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Column<u64, TestEncoding>>::new(0);
+        let other = Item::<u64, TestEncoding>::new(1);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut("alice").push(&1).unwrap();
+        access.entry_mut("alice").push(&2).unwrap();
+        access.entry_mut("bob").push(&3).unwrap();
+        other.access(&mut storage).set(&42).unwrap();
+
+        map.access(&mut storage).clear();
+
+        // only the unrelated item is left - no column metadata, no entries
         assert_eq!(
-            items,
-            vec![(("bar".to_string(), ()), 42), (("baz".to_string(), ()), 69)]
+            storage
+                .pairs(Bound::Unbounded, Bound::Unbounded)
+                .collect::<Vec<_>>(),
+            vec![(vec![1], 42u64.to_le_bytes().to_vec())]
         );
+    }
 
-        let items = access
-            .bounded_pairs(Bound::Excluded("bar"), Bound::Included("foo"))
-            .collect::<Result<Vec<_>, _>>()
-            .unwrap();
+    #[test]
+    fn three_levels() {
+        type Tree = Map<String, Map<u32, Map<String, Item<u64, TestEncoding>>>>;
+
+        let mut storage = TestStorage::new();
+
+        let map = Tree::new(0);
+        let mut access = map.access(&mut storage);
+
+        for (a, b, c, value) in [
+            ("bob", 2, "x", 1),
+            ("alice", 7, "z", 2),
+            ("alice", 7, "", 3),
+            ("alice", 1, "y", 4),
+            ("al", 9, "w", 5),
+        ] {
+            access
+                .entry_mut(a)
+                .entry_mut(&b)
+                .entry_mut(c)
+                .set(&value)
+                .unwrap();
+        }
+
+        let key = |a: &str, b: u32, c: &str| (a.to_string(), (b, (c.to_string(), ())));
+        // the outer keys are length-prefixed, so shorter ones come first
         assert_eq!(
-            items,
+            access.pairs().collect::<Result<Vec<_>, _>>().unwrap(),
             vec![
-                (("baz".to_string(), ()), 69),
-                (("foo".to_string(), ()), 1337)
+                (key("al", 9, "w"), 5),
+                (key("bob", 2, "x"), 1),
+                (key("alice", 1, "y"), 4),
+                (key("alice", 7, ""), 3),
+                (key("alice", 7, "z"), 2),
+            ]
+        );
+        assert_eq!(
+            access
+                .entry("alice")
+                .keys()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![
+                (1, ("y".to_string(), ())),
+                (7, ("".to_string(), ())),
+                (7, ("z".to_string(), ())),
             ]
         );
+
+        // raw keys decode back to the full path
+        let decoded = storage
+            .keys(Bound::Unbounded, Bound::Unbounded)
+            .map(|raw| Tree::decode_key(&raw[1..]))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            decoded,
+            map.access(&storage)
+                .keys()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        );
+        let (a, (b, (c, ()))) = &decoded[1];
+        assert_eq!(
+            map.access(&storage)
+                .entry(a)
+                .entry(b)
+                .entry(c)
+                .get()
+                .unwrap(),
+            Some(1)
+        );
+
+        // errors at each level surface wrapped once per level below the map being decoded
+        assert_eq!(Tree::decode_key(&[]), Err(MapKeyDecodeError::EmptyKey));
+        assert_eq!(
+            Tree::decode_key(&[1, b'a', 0, 0]),
+            Err(MapKeyDecodeError::Inner(MapKeyDecodeError::KeyTooShort(4)))
+        );
+        assert_eq!(
+            Tree::decode_key(&[1, b'a', 0, 0, 0, 1, 0xff]),
+            Err(MapKeyDecodeError::Inner(MapKeyDecodeError::Inner(
+                MapKeyDecodeError::InvalidKey("invalid UTF8".to_string())
+            )))
+        );
     }
 
     #[test]
-    fn iter_static_map_of_item() {
+    fn too_long_len_prefixed_key() {
         let mut storage = TestStorage::new();
 
-        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
         let mut access = map.access(&mut storage);
 
-        access.entry_mut("foo").set(&1337).unwrap();
-        access.entry_mut("bar").set(&42).unwrap();
-        access.entry_mut("baz").set(&69).unwrap();
+        let key = "a".repeat(255);
+        access.entry_mut(&key).entry_mut("foo").set(&1).unwrap();
+        assert_eq!(access.entry(&key).entry("foo").get().unwrap(), Some(1));
 
-        let items = access.pairs().collect::<Result<Vec<_>, _>>().unwrap();
+        // a 256-byte key can't be framed - the entry is detached, both for reads and writes
+        let before = storage.export();
+        let long = "a".repeat(256);
+        let mut access = map.access(&mut storage);
+        access.entry_mut(&long).entry_mut("foo").set(&2).unwrap();
         assert_eq!(
-            items,
-            vec![
-                (("bar".to_string(), ()), 42),
-                (("baz".to_string(), ()), 69),
-                (("foo".to_string(), ()), 1337)
-            ]
+            access.entry_mut(&long).entry_mut("foo").get().unwrap(),
+            None
         );
+        assert_eq!(access.entry(&long).entry("foo").get().unwrap(), None);
+        assert_eq!(storage.export(), before);
     }
 
     #[test]
-    fn bounded_iter_static_map_of_map() {
+    fn too_long_len_prefixed_key_reads_as_absent() {
         let mut storage = TestStorage::new();
 
-        let map = Map::<u32, Map<String, Item<u64, TestEncoding>>>::new(0);
+        let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
         let mut access = map.access(&mut storage);
 
-        access.entry_mut(&2).entry_mut("bar").set(&1337).unwrap();
-        access.entry_mut(&3).entry_mut("baz").set(&42).unwrap();
-        access.entry_mut(&4).entry_mut("quux").set(&69).unwrap();
+        // the longest key that still fits
+        let short = "a".repeat(255);
+        access.entry_mut(&short).entry_mut("a").set(&1).unwrap();
 
-        let items = access
-            .bounded_pairs(Bound::Included(&2), Bound::Excluded(&4))
+        let long = "a".repeat(256);
+        assert_eq!(
+            access.try_entry_mut(&long).err(),
+            Some(FramingError::Unrepresentable)
+        );
+        assert_eq!(access.entry(&long).entry("").get().unwrap(), None);
+        assert_eq!(access.entry(&long).keys().count(), 0);
+        assert!(!access.contains_prefix(&long));
+        assert!(access.contains_prefix(&short));
+    }
+
+    #[test]
+    fn alternative_key_framings() {
+        let mut storage = TestStorage::new();
+
+        let varint = Map::<String, Map<u32, Item<u64, TestEncoding>>, VarintLenPrefix>::new(0);
+        let long_key = "a".repeat(300);
+        varint
+            .access(&mut storage)
+            .entry_mut(&long_key)
+            .entry_mut(&1)
+            .set(&10)
+            .unwrap();
+        assert!(storage.has(
+            &[
+                [0, 0xac, 0x02].as_slice(),
+                long_key.as_bytes(),
+                &[0, 0, 0, 1]
+            ]
+            .concat()
+        ));
+
+        let pairs = varint
+            .access(&storage)
+            .pairs()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(pairs, vec![((long_key.clone(), (1, ())), 10)]);
+
+        let nulls = Map::<String, Map<u32, Item<u64, TestEncoding>>, NullTerminated>::new(1);
+        let mut access = nulls.access(&mut storage);
+        access.entry_mut("bob").entry_mut(&1).set(&1).unwrap();
+        access.entry_mut("alice").entry_mut(&2).set(&2).unwrap();
+        assert_eq!(access.prefix(), &[1]);
+        assert_eq!(access.entry("bob").prefix(), b"bob\x00");
+
+        // unlike with length prefixes, keys come out in lexicographical order
+        let keys = access
+            .keys()
+            .map(|r| r.map(|(key, _)| key))
             .collect::<Result<Vec<_>, _>>()
             .unwrap();
+        assert_eq!(keys, vec!["alice".to_string(), "bob".to_string()]);
 
+        // a key that's missing its terminator is reported rather than misparsed
+        crate::storage::StorageMut::set(&mut access.storage, b"carol", &[]);
         assert_eq!(
-            items,
+            access.keys().last(),
+            Some(Err(MapKeyDecodeError::MalformedFrame))
+        );
+    }
+
+    #[test]
+    fn cloned_iterators() {
+        // a backend with cloneable iterators
+        struct Snapshot(TestStorage);
+
+        impl Storage for Snapshot {
+            fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+                self.0.get(key)
+            }
+
+            fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
+                self.0.get_meta(key)
+            }
+        }
+
+        impl IterableStorage for Snapshot {
+            type KeysIterator<'a> = std::vec::IntoIter<Vec<u8>>;
+            type ValuesIterator<'a> = std::vec::IntoIter<Vec<u8>>;
+            type PairsIterator<'a> = std::vec::IntoIter<(Vec<u8>, Vec<u8>)>;
+
+            fn keys(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::KeysIterator<'_> {
+                self.0.keys(start, end).collect::<Vec<_>>().into_iter()
+            }
+
+            fn values(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::ValuesIterator<'_> {
+                self.0.values(start, end).collect::<Vec<_>>().into_iter()
+            }
+
+            fn pairs(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::PairsIterator<'_> {
+                self.0.pairs(start, end).collect::<Vec<_>>().into_iter()
+            }
+        }
+
+        let mut storage = TestStorage::new();
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        map.access(&mut storage).entry_mut("a").set(&1).unwrap();
+        map.access(&mut storage).entry_mut("b").set(&2).unwrap();
+
+        let storage = Snapshot(storage);
+        let access = map.access(&storage);
+
+        let mut pairs = access.pairs();
+        assert_eq!(pairs.clone().count(), 2);
+        assert_eq!(pairs.next(), Some(Ok((("a".to_string(), ()), 1))));
+
+        let lookahead = pairs.clone().map(Result::unwrap).collect::<Vec<_>>();
+        assert_eq!(lookahead, vec![(("b".to_string(), ()), 2)]);
+        assert_eq!(pairs.next(), Some(Ok((("b".to_string(), ()), 2))));
+
+        let keys = access.keys();
+        assert_eq!(keys.clone().last(), Some(Ok(("b".to_string(), ()))));
+        assert_eq!(keys.count(), 2);
+        assert_eq!(access.values().clone().next(), Some(Ok(1)));
+    }
+
+    #[test]
+    fn char_keys() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<char, Item<u32, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        for c in ['z', 'é', 'A', '😀', 'a'] {
+            access.entry_mut(&c).set(&(c as u32)).unwrap();
+        }
+
+        let keys = access.keys().map(|r| r.unwrap().0).collect::<Vec<_>>();
+        assert_eq!(keys, vec!['A', 'a', 'z', 'é', '😀']);
+
+        let keys = access
+            .range_from(&'a')
+            .map(|r| r.unwrap().0 .0)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!['a', 'z', 'é', '😀']);
+
+        // a surrogate isn't a valid `char`
+        storage.set(&[0, 0, 0, 0xd8, 0x00], &0u32.to_le_bytes());
+        let access = map.access(&storage);
+        assert_eq!(
+            access.keys().nth(4).unwrap(),
+            Err(MapKeyDecodeError::InvalidKey(
+                "invalid code point: 0xd800".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn bool_keys() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<bool, Map<String, Item<u32, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut(&true).entry_mut("foo").set(&1).unwrap();
+        access.entry_mut(&false).entry_mut("bar").set(&2).unwrap();
+        access.entry_mut(&true).entry_mut("baz").set(&3).unwrap();
+
+        let pairs = access.pairs().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            pairs,
             vec![
-                ((2, ("bar".to_string(), ())), 1337),
-                ((3, ("baz".to_string(), ())), 42)
+                ((false, ("bar".to_string(), ())), 2),
+                ((true, ("baz".to_string(), ())), 3),
+                ((true, ("foo".to_string(), ())), 1),
             ]
         );
+        assert_eq!(access.entry(&true).keys().count(), 2);
     }
 
     #[test]
-    fn pairs() {
+    fn descending_keys() {
+        let mut storage = TestStorage::new();
+
+        let map =
+            Map::<DescendingKey<u64>, Map<DescendingKey<i32>, Item<u32, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        for (a, b) in [(1, -5), (300, 2), (1, 7), (2, 0)] {
+            access
+                .entry_mut(&DescendingKey(a))
+                .entry_mut(&DescendingKey(b))
+                .set(&0)
+                .unwrap();
+        }
+
+        let keys = access
+            .keys()
+            .map(|r| r.unwrap())
+            .map(|(a, (b, ()))| (a.0, b.0))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![(300, 2), (2, 0), (1, 7), (1, -5)]);
+
+        let keys = access
+            .range_from(&DescendingKey(2))
+            .map(|r| r.unwrap().0 .0 .0)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![2, 1, 1]);
+    }
+
+    #[test]
+    fn flat_pairs_per_entry_errors() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut("a").entry_mut(&1).set(&10).unwrap();
+        access.entry_mut("a").entry_mut(&2).set(&20).unwrap();
+        access.entry_mut("b").entry_mut(&1).set(&30).unwrap();
+
+        // corrupt the value of ("a", 2)
+        storage.set(&[0, 1, b'a', 0, 0, 0, 2], &[1]);
+
+        let access = map.access(&storage);
+        let pairs = access.flat_pairs().collect::<Vec<_>>();
+
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(pairs[0], Ok((("a".to_string(), 1), 10)));
+        assert!(matches!(pairs[1], Err(KVDecodeError::Value(_))));
+        assert_eq!(pairs[2], Ok((("b".to_string(), 1), 30)));
+    }
+
+    #[test]
+    fn contains_prefix() {
         let mut storage = TestStorage::new();
 
         let map = Map::<String, Item<u64, TestEncoding>>::new(0);
         let mut access = map.access(&mut storage);
+        access.entry_mut("alice").set(&1).unwrap();
 
-        access.entry_mut("foo").set(&1337).unwrap();
-        access.entry_mut("bar").set(&42).unwrap();
+        assert!(access.contains_prefix("alice"));
+        assert!(!access.contains_prefix("al"));
+        assert!(!access.contains_prefix("alicea"));
+
+        let map = Map::<u32, Map<u32, Item<u64, TestEncoding>>>::new(1);
+        let mut access = map.access(&mut storage);
+        access.entry_mut(&1).entry_mut(&2).set(&3).unwrap();
+
+        assert!(access.contains_prefix(&1));
+        assert!(!access.contains_prefix(&0));
+        assert!(!access.contains_prefix(&2));
+    }
+
+    #[test]
+    fn iter_raw_bytes() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        access.entry_mut("foo").entry_mut("bar").set(&1).unwrap();
+        access.entry_mut("qux").entry_mut("baz").set(&2).unwrap();
+
+        // undecodable data is returned as is
+        storage.set(&[0, 9], b"garbage");
+        let access = map.access(&storage);
 
-        let items = access.pairs().collect::<Result<Vec<_>, _>>().unwrap();
         assert_eq!(
-            items,
+            access
+                .iter_raw_bytes(Bound::Unbounded, Bound::Excluded(&[9]))
+                .collect::<Vec<_>>(),
             vec![
-                (("bar".to_string(), ()), 42),
-                (("foo".to_string(), ()), 1337)
+                (b"\x03foobar".to_vec(), 1u64.to_le_bytes().to_vec()),
+                (b"\x03quxbaz".to_vec(), 2u64.to_le_bytes().to_vec()),
             ]
         );
+        assert_eq!(
+            access
+                .iter_raw_bytes(Bound::Included(&[9]), Bound::Unbounded)
+                .collect::<Vec<_>>(),
+            vec![(vec![9], b"garbage".to_vec())]
+        );
     }
 
     #[test]
-    fn keys() {
+    fn modify_all_dedup() {
         let mut storage = TestStorage::new();
 
-        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
         let mut access = map.access(&mut storage);
 
-        access.entry_mut("foo").set(&1337).unwrap();
-        access.entry_mut("bar").set(&42).unwrap();
+        for i in 0..10 {
+            access.entry_mut(&i).set(&(i as u64)).unwrap();
+        }
 
-        let keys = access.keys().collect::<Result<Vec<_>, _>>().unwrap();
-        assert_eq!(keys, vec![("bar".to_string(), ()), ("foo".to_string(), ())])
+        assert_eq!(access.modify_all(|_, value| value), Ok(10));
+        assert_eq!(access.modify_all_dedup(|_, value| value), Ok(0));
+        assert_eq!(
+            access.modify_all_dedup(|&key, value| if key % 2 == 0 { value + 100 } else { value }),
+            Ok(5)
+        );
+
+        assert_eq!(access.entry(&4).get().unwrap(), Some(104));
+        assert_eq!(access.entry(&5).get().unwrap(), Some(5));
+
+        // a corrupted entry fails the operation before anything is written, including the
+        // entries that come before it
+        storage.set(&[0, 0, 0, 0, 3], &[1]);
+        let mut access = map.access(&mut storage);
+        assert!(matches!(
+            access.modify_all(|_, value| value + 1),
+            Err(ModifyError::Decode(KVDecodeError::Value(_)))
+        ));
+        assert_eq!(access.entry(&0).get().unwrap(), Some(100));
     }
 
+    #[cfg(feature = "digest")]
     #[test]
-    fn values() {
+    fn hash() {
+        use sha2::Sha256;
+
         let mut storage = TestStorage::new();
 
-        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
-        let mut access = map.access(&mut storage);
+        let a = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+        let b = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(1);
 
-        access.entry_mut("foo").set(&1337).unwrap();
-        access.entry_mut("bar").set(&42).unwrap();
+        for map in [&a, &b] {
+            let mut access = map.access(&mut storage);
+            access.entry_mut("foo").entry_mut(&1).set(&1).unwrap();
+            access.entry_mut("bar").entry_mut(&2).set(&2).unwrap();
+        }
 
-        let values = access.values().collect::<Result<Vec<_>, _>>().unwrap();
-        assert_eq!(values, vec![42, 1337])
+        // the hash depends on contents only, not on where the map lives
+        assert_eq!(
+            a.access(&storage).hash::<Sha256>(),
+            b.access(&storage).hash::<Sha256>()
+        );
+
+        b.access(&mut storage)
+            .entry_mut("bar")
+            .entry_mut(&2)
+            .set(&3)
+            .unwrap();
+        assert_ne!(
+            a.access(&storage).hash::<Sha256>(),
+            b.access(&storage).hash::<Sha256>()
+        );
+    }
+
+    #[test]
+    fn malformed_composite_keys() {
+        type Nested = Map<String, Map<u32, Item<u64, TestEncoding>>>;
+
+        // declared length doesn't fit
+        assert_eq!(
+            Nested::decode_key(b"\x05foo"),
+            Err(MapKeyDecodeError::KeyTooShort(5))
+        );
+        // the inner fixed-size key is cut short
+        assert_eq!(
+            Nested::decode_key(b"\x03foo\x00\x00"),
+            Err(MapKeyDecodeError::Inner(
+                MapKeyDecodeError::KeyLengthMismatch {
+                    expected: 4,
+                    found: 2
+                }
+            ))
+        );
+        // trailing bytes after the inner fixed-size key
+        assert_eq!(
+            Nested::decode_key(b"\x03foo\x00\x00\x00\x01\x00"),
+            Err(MapKeyDecodeError::Inner(
+                MapKeyDecodeError::KeyLengthMismatch {
+                    expected: 4,
+                    found: 5
+                }
+            ))
+        );
+        assert_eq!(
+            Nested::decode_key(b"\x03foo\x00\x00\x00\x01"),
+            Ok(("foo".to_string(), (1, ())))
+        );
+
+        // fixed-size keys aren't length-prefixed, so the message mustn't mention a prefix
+        type Fixed = Map<u32, Map<u32, Item<u64, TestEncoding>>>;
+        let err = Fixed::decode_key(&[0, 0, 1]).unwrap_err();
+        assert_eq!(err, MapKeyDecodeError::KeyTooShort(4));
+        assert_eq!(err.to_string(), "key too short, expected 4 bytes of key");
+    }
+
+    #[test]
+    fn borrowed_byte_slice_lookups() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<Vec<u8>, Item<u64, TestEncoding>>::new(0);
+        let nested = Map::<Vec<u8>, Map<Vec<u8>, Item<u64, TestEncoding>>>::new(1);
+
+        let key: &[u8] = &[1, 2, 3];
+        map.access(&mut storage).entry_mut(key).set(&42).unwrap();
+        nested
+            .access(&mut storage)
+            .entry_mut(key)
+            .entry_mut(b"inner".as_slice())
+            .set(&1337)
+            .unwrap();
+
+        assert_eq!(map.access(&storage).entry(key).get().unwrap(), Some(42));
+        assert_eq!(
+            map.access(&storage).entry(&vec![1, 2, 3]).get().unwrap(),
+            Some(42)
+        );
+        assert_eq!(
+            nested
+                .access(&storage)
+                .entry(key)
+                .entry(b"inner".as_slice())
+                .get()
+                .unwrap(),
+            Some(1337)
+        );
+
+        // slices work as iteration bounds too
+        let keys = map
+            .access(&storage)
+            .bounded_keys(Bound::Included(key), Bound::Unbounded)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, vec![(vec![1, 2, 3], ())]);
+    }
+
+    #[test]
+    fn integer_key_and_value_layout() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<u32, Item<u32, TestEncoding>>::new(0);
+        map.access(&mut storage)
+            .entry_mut(&0x01020304)
+            .set(&0x01020304)
+            .unwrap();
+
+        // keys are big-endian regardless of the value encoding, which is little-endian here
+        assert_eq!(
+            storage.get(&[0, 0x01, 0x02, 0x03, 0x04]),
+            Some(vec![0x04, 0x03, 0x02, 0x01])
+        );
     }
 }
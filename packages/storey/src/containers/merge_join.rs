@@ -0,0 +1,235 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::ops::Bound;
+
+use crate::storage::IterableStorage;
+
+use super::{decode_pair, IterableAccessor, KVDecodeError, Storable};
+
+/// Walks two collections side by side in key order, pairing up entries with equal keys.
+///
+/// Each item is a key along with the value found under it in `left` and the value found under
+/// it in `right` - at least one of them is `Some`. This is handy for reconciling two
+/// collections, e.g. diffing expected balances against actual ones.
+///
+/// Keys are matched by their encoded bytes, so both collections need to encode keys the same
+/// way - typically they're both `Map`s with the same key type. Entries are compared at the
+/// innermost level: for nested maps, each leaf entry is matched separately.
+///
+/// Both collections are iterated lazily, so this doesn't buffer anything.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{merge_join, Item, Map};
+///
+/// let mut storage = TestStorage::new();
+/// let expected = Map::<String, Item<u64, TestEncoding>>::new(0);
+/// let actual = Map::<String, Item<u64, TestEncoding>>::new(1);
+///
+/// expected.access(&mut storage).entry_mut("alice").set(&100).unwrap();
+/// expected.access(&mut storage).entry_mut("bob").set(&50).unwrap();
+/// actual.access(&mut storage).entry_mut("bob").set(&40).unwrap();
+/// actual.access(&mut storage).entry_mut("carol").set(&10).unwrap();
+///
+/// let expected = expected.access(&storage);
+/// let actual = actual.access(&storage);
+///
+/// let diff = merge_join(&expected, &actual)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+///
+/// assert_eq!(
+///     diff,
+///     vec![
+///         (("alice".to_string(), ()), Some(100), None),
+///         (("bob".to_string(), ()), Some(50), Some(40)),
+///         (("carol".to_string(), ()), None, Some(10)),
+///     ]
+/// );
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn merge_join<'l, 'r, L, R>(
+    left: &'l L,
+    right: &'r R,
+) -> MergeJoin<
+    L::Storable,
+    R::Storable,
+    <L::Storage as IterableStorage>::PairsIterator<'l>,
+    <R::Storage as IterableStorage>::PairsIterator<'r>,
+>
+where
+    L: IterableAccessor,
+    R: IterableAccessor,
+    R::Storable: Storable<Key = <L::Storable as Storable>::Key>,
+{
+    MergeJoin {
+        left: left
+            .storage()
+            .pairs(Bound::Unbounded, Bound::Unbounded)
+            .peekable(),
+        right: right
+            .storage()
+            .pairs(Bound::Unbounded, Bound::Unbounded)
+            .peekable(),
+        phantom: PhantomData,
+    }
+}
+
+/// The iterator returned by [`merge_join`].
+pub struct MergeJoin<L, R, IL, IR>
+where
+    IL: Iterator,
+    IR: Iterator,
+{
+    left: Peekable<IL>,
+    right: Peekable<IR>,
+    phantom: PhantomData<(L, R)>,
+}
+
+impl<L, R, IL, IR> Iterator for MergeJoin<L, R, IL, IR>
+where
+    L: Storable,
+    R: Storable<Key = L::Key>,
+    IL: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+    IR: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+{
+    #[allow(clippy::type_complexity)]
+    type Item = Result<
+        (L::Key, Option<L::Value>, Option<R::Value>),
+        MergeJoinError<
+            KVDecodeError<L::KeyDecodeError, L::ValueDecodeError>,
+            KVDecodeError<R::KeyDecodeError, R::ValueDecodeError>,
+        >,
+    >;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ordering = match (self.left.peek(), self.right.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((l, _)), Some((r, _))) => l.cmp(r),
+        };
+
+        let item = match ordering {
+            Ordering::Less => {
+                let (k, v) = self.left.next()?;
+                decode_pair::<L>(&k, &v)
+                    .map(|(k, v)| (k, Some(v), None))
+                    .map_err(MergeJoinError::Left)
+            }
+            Ordering::Greater => {
+                let (k, v) = self.right.next()?;
+                decode_pair::<R>(&k, &v)
+                    .map(|(k, v)| (k, None, Some(v)))
+                    .map_err(MergeJoinError::Right)
+            }
+            Ordering::Equal => {
+                let (k, l) = self.left.next()?;
+                let (_, r) = self.right.next()?;
+                decode_pair::<L>(&k, &l)
+                    .map_err(MergeJoinError::Left)
+                    .and_then(|(k, l)| {
+                        let r = R::decode_value(&r)
+                            .map_err(|e| MergeJoinError::Right(KVDecodeError::Value(e)))?;
+                        Ok((k, Some(l), Some(r)))
+                    })
+            }
+        };
+
+        Some(item)
+    }
+}
+
+/// An error that can occur while iterating with [`merge_join`], identifying which side
+/// failed to decode.
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum MergeJoinError<L, R> {
+    #[error("left collection: {0}")]
+    Left(L),
+    #[error("right collection: {0}")]
+    Right(R),
+}
+
+impl<L: std::fmt::Display, R: std::fmt::Display> crate::error::StoreyError
+    for MergeJoinError<L, R>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{Item, Map};
+    use crate::storage::StorageMut as _;
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+
+    #[test]
+    fn nested() {
+        let mut storage = TestStorage::new();
+
+        let left = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+        let right = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(1);
+
+        let mut access = left.access(&mut storage);
+        access.entry_mut("a").entry_mut(&1).set(&10).unwrap();
+        access.entry_mut("a").entry_mut(&2).set(&20).unwrap();
+        access.entry_mut("b").entry_mut(&1).set(&30).unwrap();
+
+        let mut access = right.access(&mut storage);
+        access.entry_mut("a").entry_mut(&2).set(&21).unwrap();
+        access.entry_mut("c").entry_mut(&1).set(&40).unwrap();
+
+        let left = left.access(&storage);
+        let right = right.access(&storage);
+        let joined = merge_join(&left, &right)
+            .map(|r| r.unwrap())
+            .map(|((k, (i, ())), l, r)| (k, i, l, r))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            joined,
+            vec![
+                ("a".to_string(), 1, Some(10), None),
+                ("a".to_string(), 2, Some(20), Some(21)),
+                ("b".to_string(), 1, Some(30), None),
+                ("c".to_string(), 1, None, Some(40)),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let storage = TestStorage::new();
+
+        let left = Map::<u32, Item<u64, TestEncoding>>::new(0);
+        let right = Map::<u32, Item<u64, TestEncoding>>::new(1);
+
+        let left = left.access(&storage);
+        let right = right.access(&storage);
+        assert_eq!(merge_join(&left, &right).count(), 0);
+    }
+
+    #[test]
+    fn reports_failing_side() {
+        let mut storage = TestStorage::new();
+
+        let left = Map::<u32, Item<u64, TestEncoding>>::new(0);
+        let right = Map::<u32, Item<u64, TestEncoding>>::new(1);
+
+        left.access(&mut storage).entry_mut(&1).set(&10).unwrap();
+        storage.set(&[1, 0, 0, 0, 1], &[1, 2]);
+
+        let left = left.access(&storage);
+        let right = right.access(&storage);
+        let result = merge_join(&left, &right).next().unwrap();
+        assert!(matches!(
+            result,
+            Err(MergeJoinError::Right(KVDecodeError::Value(_)))
+        ));
+    }
+}
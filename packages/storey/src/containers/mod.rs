@@ -1,22 +1,70 @@
 //! This module contains both the traits for implementing collections/containers, as well as a
 //! few fundamental collections/containers themselves.
 
+mod bounded_map;
+mod cached_item;
 mod column;
 pub mod common;
+mod counted_map;
+mod default_item;
+mod expiring_item;
+mod flatten;
 mod item;
+mod item_guard;
+mod log;
 pub mod map;
+mod merge_join;
+#[doc(hidden)]
+pub mod namespace;
+mod tagged_item;
 
 use std::{marker::PhantomData, ops::Bound};
 
+pub use bounded_map::{BoundedMap, BoundedMapAccess, BoundedMapError};
+pub use cached_item::CachedItemAccess;
 pub use column::{Column, ColumnAccess};
-pub use item::{Item, ItemAccess};
+pub use counted_map::{CountedMap, CountedMapAccess, CountedMapError};
+pub use default_item::{DefaultItem, DefaultItemAccess, ItemDefault, StdDefault};
+pub use expiring_item::{Expiring, ExpiringDecodeError, ExpiringItem, ExpiringItemAccess};
+pub use flatten::{FlatPairs, FlattenKey, Prepend};
+pub use item::{Item, ItemAccess, ItemKeyDecodeError, ItemState, UpdateError};
+pub use item_guard::ItemGuard;
+pub use log::{Log, LogAccess};
 pub use map::{Map, MapAccess};
+pub use merge_join::{merge_join, MergeJoin, MergeJoinError};
 use storey_storage::RevIterableStorage;
+pub use tagged_item::{TaggedDecodeError, TaggedItem, TaggedItemAccess, TaggedValue};
 
-use crate::storage::IterableStorage;
+use crate::storage::{IterableStorage, Storage, StorageMut};
 
 /// The fundamental trait every collection/container should implement.
+///
+/// # Implementing a container
+///
+/// Containers can be implemented outside of this crate. A container is typically a pair of
+/// types: the container itself (implementing `Storable`) and its accessor
+/// ([`Storable::Accessor`]), which wraps a [`Storage`] namespace and provides the actual
+/// methods for reading and writing data. Implementing [`IterableAccessor`] for the accessor
+/// gives it iteration for free, based on [`decode_key`](Self::decode_key) and
+/// [`decode_value`](Self::decode_value).
+///
+/// To be usable as the value of a [`Map`], the container's `KeyDecodeError` must implement
+/// [`Display`](std::fmt::Display).
+///
+/// Anything other than the container's elements (counters, lengths and the like) belongs in
+/// the metadata namespace, so that it doesn't show up in iteration. See the
+/// [storage module docs](crate::storage#metadata).
+///
+/// See the `custom_container` integration test for a complete example of a ring buffer
+/// container defined outside of `storey`.
+///
+/// [`Storage`]: crate::storage::Storage
 pub trait Storable {
+    /// Whether this container manages subkeys ([`NonTerminal`]) or stores a single value
+    /// directly under its namespace ([`Terminal`]).
+    ///
+    /// This decides how keys of an outer [`Map`] are framed, so that they can be told apart
+    /// from the subkeys of this container.
     type Kind: StorableKind;
 
     /// The accessor type for this collection/container. An accessor is a type that provides
@@ -61,10 +109,60 @@ pub trait Storable {
     /// This method is used in value iteration to provide a typed value rather than raw bytes
     /// to the user.
     fn decode_value(value: &[u8]) -> Result<Self::Value, Self::ValueDecodeError>;
+
+    /// Remove everything this collection/container keeps in the given storage, including
+    /// metadata.
+    ///
+    /// The default implementation removes every key in the namespace. Containers that keep
+    /// metadata or manage nested containers should override it.
+    fn clear_impl<S>(storage: &mut S)
+    where
+        S: Storage + IterableStorage + StorageMut,
+    {
+        clear_namespace(storage)
+    }
+}
+
+/// Removes every key (but not metadata) in the given storage namespace.
+fn clear_namespace<S>(storage: &mut S)
+where
+    S: IterableStorage + StorageMut,
+{
+    let keys: Vec<_> = storage.keys(Bound::Unbounded, Bound::Unbounded).collect();
+
+    for key in keys {
+        storage.remove(&key);
+    }
 }
 
 /// A key-value pair decoding error.
+///
+/// This type implements [`std::error::Error`] whenever both inner error types implement
+/// [`Debug`](std::fmt::Debug) and [`Display`](std::fmt::Display), so it composes with `?`
+/// and boxed errors.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{Item, IterableAccessor as _, Map};
+///
+/// fn sum(storage: &TestStorage) -> Result<u64, Box<dyn std::error::Error>> {
+///     let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+///     let access = map.access(storage);
+///
+///     let mut sum = 0;
+///     for pair in access.pairs() {
+///         let (_, value) = pair?;
+///         sum += value;
+///     }
+///     Ok(sum)
+/// }
+///
+/// assert_eq!(sum(&TestStorage::new()).unwrap(), 0);
+/// ```
 #[derive(Debug, PartialEq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum KVDecodeError<K, V> {
     #[error("failed to decode key: {0}")]
     Key(K),
@@ -98,6 +196,37 @@ pub trait IterableAccessor: Sized {
         }
     }
 
+    /// Iterate over key-value pairs in this collection, with nested keys flattened into
+    /// a single tuple.
+    ///
+    /// See [`FlattenKey`] for how keys are flattened.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{IterableAccessor as _, Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Map<String, Map<u32, Item<u64, TestEncoding>>>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// access.entry_mut("alice").entry_mut("eur").entry_mut(&1).set(&100).unwrap();
+    ///
+    /// let pairs = access.flat_pairs().collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(pairs, vec![(("alice".to_string(), "eur".to_string(), 1), 100)]);
+    /// ```
+    fn flat_pairs(
+        &self,
+    ) -> FlatPairs<Self::Storable, <Self::Storage as IterableStorage>::PairsIterator<'_>>
+    where
+        <Self::Storable as Storable>::Key: FlattenKey,
+    {
+        FlatPairs {
+            inner: self.pairs(),
+        }
+    }
+
     /// Iterate over keys in this collection.
     fn keys(
         &self,
@@ -109,6 +238,26 @@ pub trait IterableAccessor: Sized {
     }
 
     /// Iterate over values in this collection.
+    ///
+    /// Keys are never decoded, so this is the cheapest way to aggregate values (and isn't
+    /// affected by keys that fail to decode).
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{IterableAccessor as _, Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let balances = Map::<String, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = balances.access(&mut storage);
+    ///
+    /// access.entry_mut("alice").set(&100).unwrap();
+    /// access.entry_mut("bob").set(&50).unwrap();
+    ///
+    /// let total: u64 = access.values().map(Result::unwrap).sum();
+    /// assert_eq!(total, 150);
+    /// ```
     fn values(
         &self,
     ) -> StorableValues<Self::Storable, <Self::Storage as IterableStorage>::ValuesIterator<'_>>
@@ -233,6 +382,7 @@ pub trait BoundedIterableAccessor: IterableAccessor {
     /// Iterate over values in this collection, respecting the given bounds.
     ///
     /// Either end of the range can be unbounded, inclusive, or exclusive. See [`Bound`] for more.
+    /// Like [`values`](IterableAccessor::values), this never decodes keys.
     fn bounded_values<B>(
         &self,
         start: Bound<B>,
@@ -252,6 +402,68 @@ pub trait BoundedIterableAccessor: IterableAccessor {
             phantom: PhantomData,
         }
     }
+
+    /// Iterate over key-value pairs with keys greater than or equal to `start`.
+    ///
+    /// This is a shorthand for `bounded_pairs(Bound::Included(start), Bound::Unbounded)`.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{BoundedIterableAccessor as _, Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// for i in 1..=4 {
+    ///     access.entry_mut(&i).set(&(i as u64 * 10)).unwrap();
+    /// }
+    ///
+    /// let keys = access.range_from(&3).map(|r| r.unwrap().0 .0).collect::<Vec<_>>();
+    /// assert_eq!(keys, vec![3, 4]);
+    /// ```
+    fn range_from<B>(
+        &self,
+        start: B,
+    ) -> StorableIter<Self::Storable, <Self::Storage as IterableStorage>::PairsIterator<'_>>
+    where
+        B: BoundFor<Self::Storable>,
+    {
+        self.bounded_pairs(Bound::Included(start), Bound::Unbounded)
+    }
+
+    /// Iterate over key-value pairs with keys less than `end`.
+    ///
+    /// This is a shorthand for `bounded_pairs(Bound::Unbounded, Bound::Excluded(end))`.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{BoundedIterableAccessor as _, Item, Map};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+    /// let mut access = map.access(&mut storage);
+    ///
+    /// for i in 1..=4 {
+    ///     access.entry_mut(&i).set(&(i as u64 * 10)).unwrap();
+    /// }
+    ///
+    /// let keys = access.range_to(&3).map(|r| r.unwrap().0 .0).collect::<Vec<_>>();
+    /// assert_eq!(keys, vec![1, 2]);
+    /// ```
+    fn range_to<B>(
+        &self,
+        end: B,
+    ) -> StorableIter<Self::Storable, <Self::Storage as IterableStorage>::PairsIterator<'_>>
+    where
+        B: BoundFor<Self::Storable>,
+    {
+        self.bounded_pairs(Bound::Unbounded, Bound::Excluded(end))
+    }
 }
 
 /// This trait extends [`BoundedIterableAccessor`] with methods for bounded reverse iteration.
@@ -356,11 +568,55 @@ pub trait BoundFor<T> {
 }
 
 /// The iterator over key-value pairs in a collection.
+///
+/// Each entry is decoded exactly once, as it's yielded. This still holds when wrapped in
+/// [`Peekable`](std::iter::Peekable) - a peeked entry is buffered, not decoded again - which
+/// makes it easy to aggregate entries of a composite-keyed map by their outer key.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{IterableAccessor as _, Item, Map};
+///
+/// let mut storage = TestStorage::new();
+/// let map = Map::<String, Map<u32, Item<u64, TestEncoding>>>::new(0);
+/// let mut access = map.access(&mut storage);
+///
+/// access.entry_mut("alice").entry_mut(&1).set(&10).unwrap();
+/// access.entry_mut("alice").entry_mut(&2).set(&20).unwrap();
+/// access.entry_mut("carol").entry_mut(&1).set(&5).unwrap();
+///
+/// let mut pairs = access.pairs().map(Result::unwrap).peekable();
+/// let mut totals = Vec::new();
+///
+/// while let Some(((owner, _), amount)) = pairs.next() {
+///     let mut total = amount;
+///     while let Some((_, amount)) = pairs.next_if(|((next, _), _)| *next == owner) {
+///         total += amount;
+///     }
+///     totals.push((owner, total));
+/// }
+///
+/// assert_eq!(totals, vec![("alice".to_string(), 30), ("carol".to_string(), 5)]);
+/// ```
+///
+/// The iterator can be cloned if the backend's iterator can, e.g. to look ahead without
+/// consuming the original. The clone decodes the entries it yields on its own.
 pub struct StorableIter<S, I> {
     inner: I,
     phantom: PhantomData<S>,
 }
 
+impl<S, I: Clone> Clone for StorableIter<S, I> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
 impl<S, I> Iterator for StorableIter<S, I>
 where
     S: Storable,
@@ -369,13 +625,20 @@ where
     type Item = Result<(S::Key, S::Value), KVDecodeError<S::KeyDecodeError, S::ValueDecodeError>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(k, v)| -> Self::Item {
-            match (S::decode_key(&k), S::decode_value(&v)) {
-                (Err(e), _) => Err(KVDecodeError::Key(e)),
-                (_, Err(e)) => Err(KVDecodeError::Value(e)),
-                (Ok(k), Ok(v)) => Ok((k, v)),
-            }
-        })
+        self.inner.next().map(|(k, v)| decode_pair::<S>(&k, &v))
+    }
+}
+
+/// Decodes a raw key-value pair of the given storable.
+#[allow(clippy::type_complexity)]
+fn decode_pair<S: Storable>(
+    key: &[u8],
+    value: &[u8],
+) -> Result<(S::Key, S::Value), KVDecodeError<S::KeyDecodeError, S::ValueDecodeError>> {
+    match (S::decode_key(key), S::decode_value(value)) {
+        (Err(e), _) => Err(KVDecodeError::Key(e)),
+        (_, Err(e)) => Err(KVDecodeError::Value(e)),
+        (Ok(k), Ok(v)) => Ok((k, v)),
     }
 }
 
@@ -385,6 +648,15 @@ pub struct StorableKeys<S, I> {
     phantom: PhantomData<S>,
 }
 
+impl<S, I: Clone> Clone for StorableKeys<S, I> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
 impl<S, I> Iterator for StorableKeys<S, I>
 where
     S: Storable,
@@ -403,6 +675,15 @@ pub struct StorableValues<S, I> {
     phantom: PhantomData<S>,
 }
 
+impl<S, I: Clone> Clone for StorableValues<S, I> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
 impl<S, I> Iterator for StorableValues<S, I>
 where
     S: Storable,
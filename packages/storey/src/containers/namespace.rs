@@ -0,0 +1,114 @@
+/// Declares a namespace enum for top-level containers, along with one container per variant.
+///
+/// Each variant becomes the prefix of exactly one container, so no two containers declared this
+/// way can share a prefix. The enum is `#[repr(u8)]`, which means the compiler rejects duplicate
+/// discriminants; a `const` assertion additionally rejects the byte `255`, which is reserved
+/// for metadata (see the [storage docs](crate::storage#metadata)).
+///
+/// Variants are numbered from `0` unless a discriminant is given explicitly. Keep the
+/// discriminants stable once data has been written - reordering variants without explicit
+/// discriminants changes where containers live in storage!
+///
+/// Containers are constructed with their `new` function, so any container taking a single
+/// byte prefix ([`Item`], [`Map`], [`Column`], [`TaggedItem`]) can be used.
+///
+/// [`Item`]: crate::containers::Item
+/// [`Map`]: crate::containers::Map
+/// [`Column`]: crate::containers::Column
+/// [`TaggedItem`]: crate::containers::TaggedItem
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{Column, Item, Map};
+///
+/// storey::namespace! {
+///     /// The storage layout of our contract.
+///     pub enum Namespace {
+///         Config => pub const CONFIG: Item<u64, TestEncoding>,
+///         Balances => pub const BALANCES: Map<String, Item<u64, TestEncoding>>,
+///         Log = 10 => pub const LOG: Column<u64, TestEncoding>,
+///     }
+/// }
+///
+/// assert_eq!(Namespace::Config.prefix(), 0);
+/// assert_eq!(Namespace::Balances.prefix(), 1);
+/// assert_eq!(Namespace::Log.prefix(), 10);
+///
+/// let mut storage = TestStorage::new();
+/// CONFIG.access(&mut storage).set(&42).unwrap();
+/// BALANCES.access(&mut storage).entry_mut("alice").set(&100).unwrap();
+/// LOG.access(&mut storage).push(&1).unwrap();
+///
+/// assert_eq!(CONFIG.access(&storage).get().unwrap(), Some(42));
+/// ```
+///
+/// Using the reserved prefix fails to compile:
+/// ```compile_fail
+/// # use mocks::encoding::TestEncoding;
+/// use storey::containers::Item;
+///
+/// storey::namespace! {
+///     enum Namespace {
+///         Config = 255 => const CONFIG: Item<u64, TestEncoding>,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! namespace {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident $(= $discriminant:expr)?
+                    => $container_vis:vis const $container:ident: $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(u8)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant $(= $discriminant)?,
+            )*
+        }
+
+        impl $name {
+            /// Returns the storage prefix of this namespace.
+            pub const fn prefix(self) -> u8 {
+                self as u8
+            }
+        }
+
+        const _: () = $crate::containers::namespace::assert_valid_prefixes(&[
+            $($name::$variant as u8),*
+        ]);
+
+        $(
+            $container_vis const $container: $ty = <$ty>::new($name::$variant as u8);
+        )*
+    };
+}
+
+/// Panics if any two of the given prefixes are equal, or if any of them is reserved.
+///
+/// This is meant to be evaluated in a `const` context, turning the panic into a compile error.
+#[doc(hidden)]
+pub const fn assert_valid_prefixes(prefixes: &[u8]) {
+    let mut i = 0;
+    while i < prefixes.len() {
+        assert!(prefixes[i] != 255, "prefix 255 is reserved for metadata");
+
+        let mut j = i + 1;
+        while j < prefixes.len() {
+            assert!(prefixes[i] != prefixes[j], "duplicate prefix");
+            j += 1;
+        }
+
+        i += 1;
+    }
+}
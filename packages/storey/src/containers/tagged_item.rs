@@ -0,0 +1,304 @@
+use std::marker::PhantomData;
+
+use thiserror::Error;
+
+use crate::encoding::Encoding;
+use crate::storage::StorageBranch;
+use crate::storage::{Storage, StorageMut};
+
+use super::{ItemKeyDecodeError, Storable, Terminal};
+
+/// A type whose values are stored as a tag byte identifying the variant, followed by
+/// the variant's encoded payload.
+///
+/// This is meant to be implemented for enums whose variants hold differently shaped data.
+/// See [`TaggedItem`] for an example.
+pub trait TaggedValue<E: Encoding>: Sized {
+    /// Returns the tag identifying this variant, along with the encoded payload.
+    fn encode_variant(&self) -> Result<(u8, Vec<u8>), E::EncodeError>;
+
+    /// Decodes the payload of the variant identified by `tag`.
+    ///
+    /// Returns `None` if the tag is not recognized.
+    fn decode_variant(tag: u8, payload: &[u8]) -> Option<Result<Self, E::DecodeError>>;
+}
+
+/// A single value of a [`TaggedValue`] type in the storage.
+///
+/// This works just like an [`Item`](crate::containers::Item), but the value is stored with
+/// a tag byte in front of it, so that each variant of an enum can have its own encoded shape.
+/// Decoding a value with an unrecognized tag fails with [`TaggedDecodeError::UnknownTag`].
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::{MockError, TestEncoding};
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{Map, TaggedItem, TaggedValue};
+/// use storey::encoding::{DecodableWith, EncodableWith};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Order {
+///     Market { amount: u64 },
+///     Limit { amount: u64, price: u32 },
+/// }
+///
+/// impl TaggedValue<TestEncoding> for Order {
+///     fn encode_variant(&self) -> Result<(u8, Vec<u8>), MockError> {
+///         match self {
+///             Order::Market { amount } => Ok((0, amount.encode()?)),
+///             Order::Limit { amount, price } => Ok((1, (*amount, *price).encode()?)),
+///         }
+///     }
+///
+///     fn decode_variant(tag: u8, payload: &[u8]) -> Option<Result<Self, MockError>> {
+///         match tag {
+///             0 => Some(u64::decode(payload).map(|amount| Order::Market { amount })),
+///             1 => Some(
+///                 <(u64, u32)>::decode(payload)
+///                     .map(|(amount, price)| Order::Limit { amount, price }),
+///             ),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// let orders = Map::<String, TaggedItem<Order, TestEncoding>>::new(0);
+/// let mut access = orders.access(&mut storage);
+///
+/// access.entry_mut("alice").set(&Order::Market { amount: 10 }).unwrap();
+/// access.entry_mut("bob").set(&Order::Limit { amount: 5, price: 42 }).unwrap();
+///
+/// assert_eq!(access.entry("alice").tag(), Some(0));
+/// assert_eq!(
+///     access.entry("bob").get().unwrap(),
+///     Some(Order::Limit { amount: 5, price: 42 })
+/// );
+/// ```
+pub struct TaggedItem<T, E> {
+    key: u8,
+    phantom: PhantomData<(T, E)>,
+}
+
+impl<T, E> TaggedItem<T, E>
+where
+    E: Encoding,
+    T: TaggedValue<E>,
+{
+    /// Create a new tagged item with the given key.
+    ///
+    /// It is the responsibility of the caller to ensure that the key is unique.
+    pub const fn new(key: u8) -> Self {
+        Self {
+            key,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Acquire an accessor to the tagged item.
+    pub fn access<S>(&self, storage: S) -> TaggedItemAccess<E, T, StorageBranch<S>> {
+        Self::access_impl(StorageBranch::new(storage, vec![self.key]))
+    }
+
+    /// Acquire an accessor to the tagged item, nested under `branch` rather than at the root
+    /// of the storage. The item's key is appended to the branch's prefix.
+    pub fn access_at<S>(
+        &self,
+        branch: StorageBranch<S>,
+    ) -> TaggedItemAccess<E, T, StorageBranch<S>> {
+        Self::access_impl(branch.nest(&[self.key]))
+    }
+}
+
+impl<T, E> Storable for TaggedItem<T, E>
+where
+    E: Encoding,
+    T: TaggedValue<E>,
+{
+    type Kind = Terminal;
+    type Accessor<S> = TaggedItemAccess<E, T, S>;
+    type Key = ();
+    type KeyDecodeError = ItemKeyDecodeError;
+    type Value = T;
+    type ValueDecodeError = TaggedDecodeError<E::DecodeError>;
+
+    fn access_impl<S>(storage: S) -> TaggedItemAccess<E, T, S> {
+        TaggedItemAccess {
+            storage,
+            phantom: PhantomData,
+        }
+    }
+
+    fn decode_key(key: &[u8]) -> Result<(), ItemKeyDecodeError> {
+        if key.is_empty() {
+            Ok(())
+        } else {
+            Err(ItemKeyDecodeError { key: key.to_vec() })
+        }
+    }
+
+    fn decode_value(value: &[u8]) -> Result<T, TaggedDecodeError<E::DecodeError>> {
+        let (&tag, payload) = value.split_first().ok_or(TaggedDecodeError::MissingTag)?;
+
+        T::decode_variant(tag, payload)
+            .ok_or(TaggedDecodeError::UnknownTag(tag))?
+            .map_err(TaggedDecodeError::Payload)
+    }
+}
+
+/// An error that can occur when decoding a [`TaggedItem`] value.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum TaggedDecodeError<E> {
+    #[error("empty value, expected a tag byte")]
+    MissingTag,
+    #[error("unknown tag: {0}")]
+    UnknownTag(u8),
+    #[error("failed to decode payload: {0}")]
+    Payload(E),
+}
+
+impl<E: std::fmt::Display> crate::error::StoreyError for TaggedDecodeError<E> {}
+
+/// An accessor for a `TaggedItem`.
+///
+/// This type provides methods to get and set the value of the tagged item.
+pub struct TaggedItemAccess<E, T, S> {
+    storage: S,
+    phantom: PhantomData<(E, T)>,
+}
+
+impl<E, T, S> TaggedItemAccess<E, T, S>
+where
+    E: Encoding,
+    T: TaggedValue<E>,
+    S: Storage,
+{
+    /// Get the value of the tagged item.
+    ///
+    /// Returns `Ok(None)` if the item doesn't exist (has not been set yet).
+    pub fn get(&self) -> Result<Option<T>, TaggedDecodeError<E::DecodeError>> {
+        self.storage
+            .get(&[])
+            .map(|bytes| TaggedItem::<T, E>::decode_value(&bytes))
+            .transpose()
+    }
+
+    /// Get the tag of the stored variant, without decoding the payload.
+    ///
+    /// Returns `None` if the item doesn't exist (or is stored without a tag).
+    pub fn tag(&self) -> Option<u8> {
+        self.storage
+            .get(&[])
+            .and_then(|bytes| bytes.first().copied())
+    }
+}
+
+impl<E, T, S> TaggedItemAccess<E, T, S>
+where
+    E: Encoding,
+    T: TaggedValue<E>,
+    S: Storage + StorageMut,
+{
+    /// Set the value of the tagged item.
+    pub fn set(&mut self, value: &T) -> Result<(), E::EncodeError> {
+        let (tag, payload) = value.encode_variant()?;
+
+        let mut bytes = Vec::with_capacity(payload.len() + 1);
+        bytes.push(tag);
+        bytes.extend_from_slice(&payload);
+
+        self.storage.set(&[], &bytes);
+        Ok(())
+    }
+
+    /// Remove the value of the tagged item.
+    pub fn remove(&mut self) {
+        self.storage.remove(&[]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::encoding::{DecodableWith as _, EncodableWith as _};
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::{MockError, TestEncoding};
+
+    #[derive(Debug, PartialEq)]
+    enum Shape {
+        Circle(u32),
+        Rect(u32, u32),
+    }
+
+    impl TaggedValue<TestEncoding> for Shape {
+        fn encode_variant(&self) -> Result<(u8, Vec<u8>), MockError> {
+            match self {
+                Shape::Circle(r) => Ok((0, r.encode()?)),
+                Shape::Rect(w, h) => Ok((1, (*w, *h).encode()?)),
+            }
+        }
+
+        fn decode_variant(tag: u8, payload: &[u8]) -> Option<Result<Self, MockError>> {
+            match tag {
+                0 => Some(u32::decode(payload).map(Shape::Circle)),
+                1 => Some(<(u32, u32)>::decode(payload).map(|(w, h)| Shape::Rect(w, h))),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut storage = TestStorage::new();
+
+        let item = TaggedItem::<Shape, TestEncoding>::new(0);
+        let mut access = item.access(&mut storage);
+
+        assert_eq!(access.get(), Ok(None));
+        assert_eq!(access.tag(), None);
+
+        access.set(&Shape::Rect(2, 3)).unwrap();
+        assert_eq!(access.get(), Ok(Some(Shape::Rect(2, 3))));
+        assert_eq!(access.tag(), Some(1));
+
+        access.set(&Shape::Circle(7)).unwrap();
+        assert_eq!(access.get(), Ok(Some(Shape::Circle(7))));
+        assert_eq!(storage.get(&[0]), Some(vec![0, 7, 0, 0, 0]));
+    }
+
+    #[test]
+    fn unknown_tag() {
+        let mut storage = TestStorage::new();
+        storage.set(&[0], &[9, 1, 2, 3]);
+        storage.set(&[1], &[]);
+
+        let item = TaggedItem::<Shape, TestEncoding>::new(0);
+        assert_eq!(
+            item.access(&storage).get(),
+            Err(TaggedDecodeError::UnknownTag(9))
+        );
+
+        let item = TaggedItem::<Shape, TestEncoding>::new(1);
+        assert_eq!(
+            item.access(&storage).get(),
+            Err(TaggedDecodeError::MissingTag)
+        );
+    }
+
+    #[test]
+    fn access_at() {
+        let mut storage = TestStorage::new();
+
+        let item = TaggedItem::<Shape, TestEncoding>::new(0);
+        let v1 = StorageBranch::new(&mut storage, b"v1/".to_vec());
+        item.access_at(v1).set(&Shape::Circle(7)).unwrap();
+
+        assert_eq!(storage.get(b"v1/\x00"), Some(vec![0, 7, 0, 0, 0]));
+        assert_eq!(item.access(&storage).get(), Ok(None));
+
+        let v1 = StorageBranch::new(&storage, b"v1/".to_vec());
+        assert_eq!(item.access_at(v1).tag(), Some(0));
+    }
+}
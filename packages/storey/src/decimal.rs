@@ -0,0 +1,281 @@
+//! A fixed-point decimal type with a deterministic storage representation.
+//!
+//! This module is only available with the `fixed-decimal` feature.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::containers::map::key::{FixedSizeKey, NumericKeyDecodeError};
+use crate::containers::map::{Key, OwnedKey};
+
+/// A non-negative fixed-point decimal number with 18 decimal places.
+///
+/// The value is stored as a `u128` count of atomic units (10<sup>-18</sup>), so the
+/// largest representable number is about 3.4 × 10<sup>20</sup>. Arithmetic is exact -
+/// there's no rounding anywhere, and every number has exactly one representation.
+///
+/// # Byte layout
+///
+/// As a map key, a decimal is encoded as its atomic units in 16 big-endian bytes, just like a
+/// `u128` key. Byte order matches numeric order, so maps keyed by decimals iterate in
+/// ascending numeric order.
+///
+/// With the `serde` feature, decimals are (de)serialized as strings in their canonical
+/// [`Display`](fmt::Display) form (e.g. `"1.5"`, never `"1.50"`), so serde-based value
+/// encodings produce the same bytes for equal numbers.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{IterableAccessor as _, Item, Map};
+/// use storey::decimal::Decimal;
+///
+/// let mut storage = TestStorage::new();
+/// let bids = Map::<Decimal, Item<u64, TestEncoding>>::new(0);
+/// let mut access = bids.access(&mut storage);
+///
+/// for (price, amount) in [("10", 1), ("2.5", 2), ("0.75", 3)] {
+///     access.entry_mut(&price.parse().unwrap()).set(&amount).unwrap();
+/// }
+///
+/// let prices = access
+///     .keys()
+///     .map(|key| key.unwrap().0.to_string())
+///     .collect::<Vec<_>>();
+/// assert_eq!(prices, vec!["0.75", "2.5", "10"]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Decimal(u128);
+
+impl Decimal {
+    /// The number of decimal places.
+    pub const DECIMAL_PLACES: u32 = 18;
+
+    const SCALE: u128 = 10u128.pow(Self::DECIMAL_PLACES);
+
+    /// Zero.
+    pub const ZERO: Self = Self(0);
+
+    /// One.
+    pub const ONE: Self = Self(Self::SCALE);
+
+    /// The largest representable decimal.
+    pub const MAX: Self = Self(u128::MAX);
+
+    /// Creates a decimal from a number of atomic units (10<sup>-18</sup>).
+    pub const fn from_atomics(atomics: u128) -> Self {
+        Self(atomics)
+    }
+
+    /// Returns the number of atomic units (10<sup>-18</sup>).
+    pub const fn atomics(&self) -> u128 {
+        self.0
+    }
+
+    /// Creates a decimal from an integer.
+    pub const fn from_int(value: u64) -> Self {
+        // can't overflow: u64::MAX * 10^18 < u128::MAX
+        Self(value as u128 * Self::SCALE)
+    }
+
+    /// Adds two decimals, returning `None` on overflow.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    /// Subtracts `other` from this decimal, returning `None` if the result would be negative.
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole = self.0 / Self::SCALE;
+        let fractional = self.0 % Self::SCALE;
+
+        if fractional == 0 {
+            write!(f, "{whole}")
+        } else {
+            let fractional = format!("{fractional:018}");
+            write!(f, "{whole}.{}", fractional.trim_end_matches('0'))
+        }
+    }
+}
+
+/// An error that can occur when parsing a [`Decimal`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum DecimalParseError {
+    #[error("invalid decimal, expected digits with an optional fractional part")]
+    Invalid,
+    #[error("too many decimal places, at most 18 are supported")]
+    TooPrecise,
+    #[error("decimal out of range")]
+    Overflow,
+}
+
+impl crate::error::StoreyError for DecimalParseError {}
+
+impl FromStr for Decimal {
+    type Err = DecimalParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (whole, fractional) = s.split_once('.').unwrap_or((s, "0"));
+
+        let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        if !is_digits(whole) || !is_digits(fractional) {
+            return Err(DecimalParseError::Invalid);
+        }
+        if fractional.len() > Self::DECIMAL_PLACES as usize {
+            return Err(DecimalParseError::TooPrecise);
+        }
+
+        let whole = whole
+            .parse::<u128>()
+            .ok()
+            .and_then(|whole| whole.checked_mul(Self::SCALE))
+            .ok_or(DecimalParseError::Overflow)?;
+        // can't fail: at most 18 digits
+        let fractional = format!("{fractional:0<18}").parse::<u128>().unwrap();
+
+        whole
+            .checked_add(fractional)
+            .map(Self)
+            .ok_or(DecimalParseError::Overflow)
+    }
+}
+
+impl Key for Decimal {
+    type Kind = FixedSizeKey<16>;
+
+    fn encode(&self) -> Vec<u8> {
+        self.0.encode()
+    }
+}
+
+impl OwnedKey for Decimal {
+    type Error = NumericKeyDecodeError;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        u128::from_bytes(bytes).map(Self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Decimal {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Decimal {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{Item, IterableAccessor as _, Map};
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::TestEncoding;
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        for s in ["0", "1", "1.5", "0.000000000000000001", "123456.789"] {
+            assert_eq!(dec(s).to_string(), s);
+        }
+
+        assert_eq!(dec("1.50"), dec("1.5"));
+        assert_eq!(dec("007").to_string(), "7");
+        assert_eq!(dec("2.5").atomics(), 2_500_000_000_000_000_000);
+        assert_eq!(Decimal::from_int(3), dec("3"));
+        assert_eq!(Decimal::MAX.to_string().parse(), Ok(Decimal::MAX));
+
+        for s in ["", ".5", "1.", "-1", "+1", "1.2.3", "1e5", " 1"] {
+            assert_eq!(
+                s.parse::<Decimal>(),
+                Err(DecimalParseError::Invalid),
+                "{s:?}"
+            );
+        }
+        assert_eq!(
+            "0.0000000000000000001".parse::<Decimal>(),
+            Err(DecimalParseError::TooPrecise)
+        );
+        assert_eq!(
+            "1000000000000000000000".parse::<Decimal>(),
+            Err(DecimalParseError::Overflow)
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(dec("1.25").checked_add(dec("0.75")), Some(dec("2")));
+        assert_eq!(dec("1").checked_sub(dec("1.5")), None);
+        assert_eq!(Decimal::MAX.checked_add(Decimal::ONE), None);
+    }
+
+    #[test]
+    fn keys_iterate_in_numeric_order() {
+        let mut storage = TestStorage::new();
+
+        let map = Map::<Decimal, Item<u64, TestEncoding>>::new(0);
+        let mut access = map.access(&mut storage);
+
+        let prices = [
+            "100",
+            "0.5",
+            "2",
+            "0.000000000000000001",
+            "10.25",
+            "10.3",
+            "0",
+        ];
+        for (i, price) in prices.iter().enumerate() {
+            access.entry_mut(&dec(price)).set(&(i as u64)).unwrap();
+        }
+
+        let keys = access
+            .keys()
+            .map(|key| key.unwrap().0.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            vec![
+                "0",
+                "0.000000000000000001",
+                "0.5",
+                "2",
+                "10.25",
+                "10.3",
+                "100"
+            ]
+        );
+        assert_eq!(access.entry(&dec("10.30")).get().unwrap(), Some(5));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_is_canonical() {
+        assert_eq!(serde_json::to_string(&dec("1.50")).unwrap(), r#""1.5""#);
+        assert_eq!(
+            serde_json::from_str::<Decimal>(r#""0.25""#).unwrap(),
+            dec("0.25")
+        );
+        assert!(serde_json::from_str::<Decimal>(r#""-1""#).is_err());
+    }
+}
@@ -34,6 +34,7 @@
 //! struct DisplayEncoding;
 //!
 //! impl Encoding for DisplayEncoding {
+//!     const FORMAT: &'static str = "display";
 //!     type DecodeError = String;
 //!     type EncodeError = String;
 //! }
@@ -67,6 +68,7 @@
 //! struct DisplayEncoding;
 //!
 //! impl Encoding for DisplayEncoding {
+//!    const FORMAT: &'static str = "display";
 //!    type DecodeError = String;
 //!    type EncodeError = String;
 //! }
@@ -93,6 +95,14 @@
 //! assert_eq!(u64::decode("12".as_bytes()), Ok(12));
 //! ```
 
+mod dispatch;
+mod raw;
+mod tagged;
+
+pub use dispatch::{DispatchError, TagDispatch};
+pub use raw::RawEncoding;
+pub use tagged::{TagError, Tagged, TaggedEncoding};
+
 /// A trait for types that serve as "markers" for a particular encoding.
 /// These types are expected to be empty structs.
 pub use storey_encoding::Encoding;
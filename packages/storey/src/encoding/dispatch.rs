@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use super::{DecodableWith, Encoding};
+
+type Decoder<O> = Box<dyn Fn(&[u8]) -> Result<O, String>>;
+
+/// A table of decoders for values of different types, selected at runtime by a tag byte.
+///
+/// This is the read side of storing heterogeneous values under one container, e.g. a plugin
+/// registry where each plugin keeps a differently shaped config. Each value type is assigned
+/// a tag and written as a [`Tagged`](super::Tagged) value, whose encoding prepends that tag to
+/// the payload. The values themselves are kept as opaque bytes, typically in a
+/// `Map<K, Item<Vec<u8>, RawEncoding>>`. On read, the tag picks the registered decoder and the
+/// decoded value is converted into a common output type `O` - usually an enum or a boxed
+/// trait object.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{Item, Map};
+/// use storey::encoding::{EncodableWith as _, RawEncoding, TagDispatch, Tagged};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Config {
+///     Limit(u64),
+///     Threshold(u32),
+/// }
+///
+/// let mut dispatch = TagDispatch::new();
+/// dispatch
+///     .register::<u64, TestEncoding>(1, Config::Limit)
+///     .register::<u32, TestEncoding>(2, Config::Threshold);
+///
+/// let mut storage = TestStorage::new();
+/// let plugins = Map::<String, Item<Vec<u8>, RawEncoding>>::new(0);
+/// let mut access = plugins.access(&mut storage);
+///
+/// let limit = Tagged::<1, _>(500u64).encode().unwrap();
+/// let threshold = Tagged::<2, _>(7u32).encode().unwrap();
+/// access.entry_mut("rate_limiter").set(&limit).unwrap();
+/// access.entry_mut("quorum").set(&threshold).unwrap();
+///
+/// let bytes = access.entry("quorum").get().unwrap().unwrap();
+/// assert_eq!(dispatch.decode(&bytes), Ok(Config::Threshold(7)));
+/// ```
+pub struct TagDispatch<O> {
+    decoders: BTreeMap<u8, Decoder<O>>,
+}
+
+impl<O> TagDispatch<O> {
+    /// Creates an empty dispatch table.
+    pub fn new() -> Self {
+        Self {
+            decoders: BTreeMap::new(),
+        }
+    }
+
+    /// Registers a decoder for values tagged with `tag`.
+    ///
+    /// The payload following the tag is decoded as `T` with the encoding `E`, and the result
+    /// is turned into the output type with `convert`. Values of this type should be written
+    /// as `Tagged<tag, T>` with `TaggedEncoding<E>`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a decoder for `tag` is already registered.
+    pub fn register<T, E>(&mut self, tag: u8, convert: impl Fn(T) -> O + 'static) -> &mut Self
+    where
+        E: Encoding,
+        T: DecodableWith<E>,
+    {
+        let decoder: Decoder<O> =
+            Box::new(move |payload| T::decode(payload).map(&convert).map_err(|e| e.to_string()));
+
+        if self.decoders.insert(tag, decoder).is_some() {
+            panic!("a decoder for tag {tag} is already registered");
+        }
+        self
+    }
+
+    /// Returns whether a decoder is registered for `tag`.
+    pub fn is_registered(&self, tag: u8) -> bool {
+        self.decoders.contains_key(&tag)
+    }
+
+    /// Decodes a tagged value with the decoder registered for its tag.
+    pub fn decode(&self, data: &[u8]) -> Result<O, DispatchError> {
+        let (&tag, payload) = data.split_first().ok_or(DispatchError::MissingTag)?;
+        let decoder = self
+            .decoders
+            .get(&tag)
+            .ok_or(DispatchError::UnknownTag(tag))?;
+
+        decoder(payload).map_err(|message| DispatchError::Decode { tag, message })
+    }
+}
+
+impl<O> Default for TagDispatch<O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An error that can occur when decoding a value with a [`TagDispatch`].
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+pub enum DispatchError {
+    #[error("missing tag byte")]
+    MissingTag,
+    #[error("no decoder registered for tag {0}")]
+    UnknownTag(u8),
+    #[error("failed to decode value with tag {tag}: {message}")]
+    Decode { tag: u8, message: String },
+}
+
+impl crate::error::StoreyError for DispatchError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::encoding::{EncodableWith as _, Tagged};
+
+    use mocks::encoding::TestEncoding;
+
+    trait Plugin {
+        fn describe(&self) -> String;
+    }
+
+    struct Limiter(u64);
+
+    impl Plugin for Limiter {
+        fn describe(&self) -> String {
+            format!("limit {}", self.0)
+        }
+    }
+
+    struct Quorum(u32);
+
+    impl Plugin for Quorum {
+        fn describe(&self) -> String {
+            format!("quorum {}", self.0)
+        }
+    }
+
+    fn dispatch() -> TagDispatch<Box<dyn Plugin>> {
+        let mut dispatch = TagDispatch::<Box<dyn Plugin>>::new();
+        dispatch
+            .register::<u64, TestEncoding>(1, |v| Box::new(Limiter(v)))
+            .register::<u32, TestEncoding>(2, |v| Box::new(Quorum(v)));
+        dispatch
+    }
+
+    #[test]
+    fn decodes_by_tag() {
+        let dispatch = dispatch();
+
+        let limiter = Tagged::<1, _>(500u64).encode().unwrap();
+        let quorum = Tagged::<2, _>(7u32).encode().unwrap();
+
+        let plugins = [limiter, quorum]
+            .iter()
+            .map(|bytes| dispatch.decode(bytes).unwrap().describe())
+            .collect::<Vec<_>>();
+        assert_eq!(plugins, vec!["limit 500", "quorum 7"]);
+    }
+
+    #[test]
+    fn errors() {
+        let dispatch = dispatch();
+
+        assert!(dispatch.is_registered(1));
+        assert!(!dispatch.is_registered(3));
+
+        assert_eq!(dispatch.decode(&[]).err(), Some(DispatchError::MissingTag));
+        assert_eq!(
+            dispatch.decode(&[3, 0]).err(),
+            Some(DispatchError::UnknownTag(3))
+        );
+        assert!(matches!(
+            dispatch.decode(&[2, 1, 2]).err(),
+            Some(DispatchError::Decode { tag: 2, .. })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "already registered")]
+    fn duplicate_tag() {
+        dispatch().register::<u64, TestEncoding>(2, |v| Box::new(Limiter(v)));
+    }
+}
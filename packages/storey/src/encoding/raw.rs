@@ -0,0 +1,85 @@
+use std::convert::Infallible;
+
+use super::{Cover, DecodableWithImpl, EncodableWithImpl, Encoding};
+
+/// An encoding that stores byte vectors as they are.
+///
+/// Encoding and decoding never fail. This is useful for values that are already serialized
+/// (e.g. precomputed proofs or opaque blobs), or for moving raw entries around in migrations.
+///
+/// # Example
+/// ```
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::encoding::RawEncoding;
+/// use storey::storage::Storage as _;
+///
+/// let mut storage = TestStorage::new();
+/// let blob = Item::<Vec<u8>, RawEncoding>::new(0);
+///
+/// blob.access(&mut storage).set(&b"opaque".to_vec()).unwrap();
+///
+/// assert_eq!(storage.get(&[0]), Some(b"opaque".to_vec()));
+/// assert_eq!(blob.access(&storage).get().unwrap(), Some(b"opaque".to_vec()));
+/// ```
+pub struct RawEncoding;
+
+impl Encoding for RawEncoding {
+    const FORMAT: &'static str = "raw";
+    type EncodeError = Infallible;
+    type DecodeError = Infallible;
+}
+
+impl EncodableWithImpl<RawEncoding> for Cover<&Vec<u8>> {
+    fn encode_impl(self) -> Result<Vec<u8>, Infallible> {
+        Ok(self.0.clone())
+    }
+
+    fn encoded_len_impl(self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+impl DecodableWithImpl<RawEncoding> for Cover<Vec<u8>> {
+    fn decode_impl(data: &[u8]) -> Result<Self, Infallible> {
+        Ok(Cover(data.to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::encoding::{DecodableWith, EncodableWith, Tagged, TaggedEncoding};
+
+    #[test]
+    fn roundtrip() {
+        let bytes = vec![0, 1, 2, 255];
+
+        assert_eq!(
+            EncodableWith::<RawEncoding>::encode(&bytes),
+            Ok(bytes.clone())
+        );
+        assert_eq!(EncodableWith::<RawEncoding>::encoded_len(&bytes), Some(4));
+        assert_eq!(
+            <Vec<u8> as DecodableWith<RawEncoding>>::decode(&bytes),
+            Ok(bytes.clone())
+        );
+        assert_eq!(
+            <Vec<u8> as DecodableWith<RawEncoding>>::decode(&[]),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn tagged() {
+        type Tagged1 = TaggedEncoding<RawEncoding>;
+
+        let encoded = EncodableWith::<Tagged1>::encode(&Tagged::<1, _>(vec![7, 8])).unwrap();
+        assert_eq!(encoded, vec![1, 7, 8]);
+        assert_eq!(
+            <Tagged<1, Vec<u8>> as DecodableWith<Tagged1>>::decode(&encoded),
+            Ok(Tagged(vec![7, 8]))
+        );
+    }
+}
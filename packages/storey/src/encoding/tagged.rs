@@ -0,0 +1,173 @@
+use std::marker::PhantomData;
+
+use super::{Cover, DecodableWith, DecodableWithImpl, EncodableWith, EncodableWithImpl, Encoding};
+
+/// A value paired with a constant tag byte.
+///
+/// Encoded with [`TaggedEncoding<E>`], the tag is written in front of the value encoded with
+/// `E`. On decode, the tag is verified and stripped before the rest is passed on to `E`.
+/// A value stored with a different tag (or no tag at all) fails to decode with
+/// [`TagError::Mismatch`]. This makes it possible to keep several logical types behind
+/// the same storage key and tell them apart safely.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::encoding::{TagError, Tagged, TaggedEncoding};
+///
+/// let mut storage = TestStorage::new();
+/// let balance = Item::<Tagged<1, u64>, TaggedEncoding<TestEncoding>>::new(0);
+/// let nonce = Item::<Tagged<2, u32>, TaggedEncoding<TestEncoding>>::new(0); // same key!
+///
+/// balance.access(&mut storage).set(&Tagged(1337)).unwrap();
+///
+/// assert_eq!(balance.access(&storage).get().unwrap(), Some(Tagged(1337)));
+/// assert!(matches!(
+///     nonce.access(&storage).get(),
+///     Err(TagError::Mismatch { expected: 2, found: Some(1) })
+/// ));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Tagged<const TAG: u8, T>(pub T);
+
+impl<const TAG: u8, T> Tagged<TAG, T> {
+    /// The tag byte written in front of the value.
+    pub const TAG: u8 = TAG;
+
+    /// Unwraps the value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// The encoding of [`Tagged`] values, with the payload following the tag byte encoded with `E`.
+///
+/// The [`FORMAT`](Encoding::FORMAT) of this encoding is `"tagged"`. Constant strings can't be
+/// concatenated in generic code, so the format of the payload is exposed separately as
+/// [`INNER_FORMAT`](Self::INNER_FORMAT).
+pub struct TaggedEncoding<E>(PhantomData<E>);
+
+impl<E: Encoding> TaggedEncoding<E> {
+    /// The format of the payload following the tag byte.
+    pub const INNER_FORMAT: &'static str = E::FORMAT;
+}
+
+impl<E: Encoding> Encoding for TaggedEncoding<E> {
+    const FORMAT: &'static str = "tagged";
+    type EncodeError = E::EncodeError;
+    type DecodeError = TagError<E::DecodeError>;
+}
+
+impl<const TAG: u8, E, T> EncodableWithImpl<TaggedEncoding<E>> for Cover<&Tagged<TAG, T>>
+where
+    E: Encoding,
+    T: EncodableWith<E>,
+{
+    fn encode_impl(self) -> Result<Vec<u8>, E::EncodeError> {
+        let payload = self.0 .0.encode()?;
+
+        let mut bytes = Vec::with_capacity(payload.len() + 1);
+        bytes.push(TAG);
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    fn encoded_len_impl(self) -> Option<usize> {
+        self.0 .0.encoded_len().map(|len| len + 1)
+    }
+}
+
+impl<const TAG: u8, E, T> DecodableWithImpl<TaggedEncoding<E>> for Cover<Tagged<TAG, T>>
+where
+    E: Encoding,
+    T: DecodableWith<E>,
+{
+    fn decode_impl(data: &[u8]) -> Result<Self, TagError<E::DecodeError>> {
+        match data.split_first() {
+            Some((&tag, payload)) if tag == TAG => {
+                let value = T::decode(payload).map_err(TagError::Inner)?;
+                Ok(Cover(Tagged(value)))
+            }
+            other => Err(TagError::Mismatch {
+                expected: TAG,
+                found: other.map(|(&tag, _)| tag),
+            }),
+        }
+    }
+}
+
+/// An error that can occur when decoding a value with the [`Tagged`] encoding.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+pub enum TagError<E> {
+    #[error("tag mismatch, expected {expected}, found {found:?}")]
+    Mismatch { expected: u8, found: Option<u8> },
+    #[error(transparent)]
+    Inner(E),
+}
+
+impl<E: std::fmt::Display> crate::error::StoreyError for TagError<E> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mocks::encoding::{MockError, TestEncoding};
+
+    type T7 = TaggedEncoding<TestEncoding>;
+
+    #[test]
+    fn roundtrip() {
+        let encoded = Tagged::<7, _>(42u64).encode().unwrap();
+        assert_eq!(encoded[0], 7);
+        assert_eq!(&encoded[1..], &42u64.to_le_bytes());
+        assert_eq!(Tagged::<7, _>(42u64).encoded_len(), Some(9));
+
+        assert_eq!(Tagged::<7, u64>::decode(&encoded), Ok(Tagged(42)));
+    }
+
+    #[test]
+    fn mismatch() {
+        let encoded = Tagged::<8, _>(42u64).encode().unwrap();
+
+        assert_eq!(
+            Tagged::<7, u64>::decode(&encoded),
+            Err(TagError::Mismatch {
+                expected: 7,
+                found: Some(8)
+            })
+        );
+        assert_eq!(
+            Tagged::<7, u64>::decode(&[]),
+            Err(TagError::Mismatch {
+                expected: 7,
+                found: None
+            })
+        );
+        assert_eq!(
+            Tagged::<7, u64>::decode(&[7, 1, 2]),
+            Err(TagError::Inner(MockError))
+        );
+    }
+
+    #[test]
+    fn plain_values_keep_a_single_encoding() {
+        // `Tagged` only applies to the wrapper, so untagged values aren't ambiguous
+        assert_eq!(12u64.encode(), Ok(12u64.to_le_bytes().to_vec()));
+        assert_eq!(u64::decode(&12u64.to_le_bytes()), Ok(12));
+        assert_eq!(T7::INNER_FORMAT, "test");
+    }
+
+    #[test]
+    fn invariants() {
+        use storey_encoding::test_util::{assert_decode_rejects, assert_encoding_roundtrip};
+
+        assert_encoding_roundtrip::<T7, _>([Tagged::<7, _>(0u64), Tagged(42), Tagged(u64::MAX)]);
+        assert_encoding_roundtrip::<T7, _>([
+            Tagged::<7, _>("".to_string()),
+            Tagged("foo".to_string()),
+        ]);
+        assert_decode_rejects::<T7, Tagged<7, u64>>(&[&[], &[7], &[8, 0, 0, 0, 0, 0, 0, 0, 0]]);
+    }
+}
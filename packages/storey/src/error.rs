@@ -1,7 +1,197 @@
 use std::fmt::Display;
 
+use crate::containers::common::TryGetError;
+use crate::containers::map::{MapKeyDecodeError, ModifyError};
+use crate::containers::{ItemKeyDecodeError, KVDecodeError, UpdateError};
+
 /// A trait representing a Storey error.
 ///
 /// This trait is implemented for all Storey error types, allowing third-party crates
 /// to implement extension traits for all of those error types.
 pub trait StoreyError: Display {}
+
+/// A catch-all error for code that just wants to bubble storage errors up.
+///
+/// The errors returned by containers are specific to the operation and generic over the
+/// encoding, which makes them precise to match on, but tedious to combine. The common ones
+/// convert into this type, so a function can use `?` on all of them and return a single error
+/// type. The original errors are kept as the [`source`](std::error::Error::source) of this one,
+/// so they can still be inspected by downcasting.
+///
+/// Errors of an [`Encoding`](crate::encoding::Encoding) don't say whether they came from
+/// encoding or decoding, so they're converted explicitly with [`Error::decode`] and
+/// [`Error::encode`]. Errors that only implement [`Display`] can be kept as messages, e.g.
+/// `Error::decode(e.to_string())`.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::{MockError, TestEncoding};
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{IterableAccessor as _, Item, Map};
+/// use storey::error::Error;
+///
+/// const CONFIG: Item<u64, TestEncoding> = Item::new(0);
+/// const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new(1);
+///
+/// fn total(storage: &TestStorage) -> Result<u64, Error> {
+///     let mut total = CONFIG.access(storage).try_get()?;
+///     for pair in BALANCES.access(storage).pairs() {
+///         let (_, balance) = pair?;
+///         total += balance;
+///     }
+///     total += BALANCES.access(storage).entry("fees").get().map_err(Error::decode)?.unwrap_or(0);
+///     Ok(total)
+/// }
+///
+/// let mut storage = TestStorage::new();
+/// assert!(matches!(total(&storage), Err(Error::NotFound)));
+///
+/// CONFIG.access(&mut storage).set(&1).unwrap();
+/// BALANCES.access(&mut storage).entry_mut("alice").set(&10).unwrap();
+/// assert_eq!(total(&storage).unwrap(), 11);
+///
+/// // the fees entry doesn't hold a valid u64
+/// storey::storage::StorageMut::set(&mut storage, b"\x01fees", &[1]);
+/// let Err(Error::Decode(source)) = total(&storage) else { panic!() };
+/// assert!(source.downcast_ref::<MockError>().is_some());
+/// ```
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A value that was required isn't stored.
+    #[error("value not found")]
+    NotFound,
+    /// A stored value couldn't be decoded.
+    #[error("failed to decode value: {0}")]
+    Decode(#[source] Source),
+    /// A value couldn't be encoded.
+    #[error("failed to encode value: {0}")]
+    Encode(#[source] Source),
+    /// A stored key couldn't be decoded.
+    #[error("failed to decode key: {0}")]
+    KeyDecode(#[source] Source),
+}
+
+/// The original error wrapped by an [`Error`].
+pub type Source = Box<dyn std::error::Error + Send + Sync>;
+
+impl Error {
+    /// Wraps an error that occurred while decoding a value.
+    pub fn decode(error: impl Into<Source>) -> Self {
+        Self::Decode(error.into())
+    }
+
+    /// Wraps an error that occurred while encoding a value.
+    pub fn encode(error: impl Into<Source>) -> Self {
+        Self::Encode(error.into())
+    }
+}
+
+impl StoreyError for Error {}
+
+impl<E: Into<Source>> From<TryGetError<E>> for Error {
+    fn from(error: TryGetError<E>) -> Self {
+        match error {
+            TryGetError::Empty => Self::NotFound,
+            TryGetError::Decode(e) => Self::decode(e),
+        }
+    }
+}
+
+impl<K: Into<Source>, V: Into<Source>> From<KVDecodeError<K, V>> for Error {
+    fn from(error: KVDecodeError<K, V>) -> Self {
+        match error {
+            KVDecodeError::Key(e) => Self::KeyDecode(e.into()),
+            KVDecodeError::Value(e) => Self::decode(e),
+        }
+    }
+}
+
+impl From<ItemKeyDecodeError> for Error {
+    fn from(error: ItemKeyDecodeError) -> Self {
+        Self::KeyDecode(error.into())
+    }
+}
+
+impl<I> From<MapKeyDecodeError<I>> for Error
+where
+    I: Display,
+    MapKeyDecodeError<I>: Into<Source>,
+{
+    fn from(error: MapKeyDecodeError<I>) -> Self {
+        Self::KeyDecode(error.into())
+    }
+}
+
+impl<D: Into<Source>, E: Into<Source>> From<UpdateError<D, E>> for Error {
+    fn from(error: UpdateError<D, E>) -> Self {
+        match error {
+            UpdateError::Decode(e) => Self::decode(e),
+            UpdateError::Encode(e) => Self::encode(e),
+        }
+    }
+}
+
+impl<D: Into<Source>, E: Into<Source>> From<ModifyError<D, E>> for Error {
+    fn from(error: ModifyError<D, E>) -> Self {
+        match error {
+            ModifyError::Decode(e) => Self::decode(e),
+            ModifyError::Encode(e) => Self::encode(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::containers::{Item, IterableAccessor as _, Map};
+
+    use mocks::backend::TestStorage;
+    use mocks::encoding::{MockError, TestEncoding};
+    use storey_storage::StorageMut as _;
+
+    #[test]
+    fn conversions() {
+        assert!(matches!(
+            Error::from(TryGetError::<String>::Empty),
+            Error::NotFound
+        ));
+
+        let error = Error::from(UpdateError::<String, String>::Encode("bad".to_string()));
+        assert!(matches!(error, Error::Encode(_)));
+        assert_eq!(error.to_string(), "failed to encode value: bad");
+
+        let error = Error::from(ItemKeyDecodeError { key: vec![1] });
+        assert_eq!(
+            error.to_string(),
+            "failed to decode key: invalid key length, expected empty key, got 1 bytes"
+        );
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(
+            source.downcast_ref::<ItemKeyDecodeError>(),
+            Some(&ItemKeyDecodeError { key: vec![1] })
+        );
+    }
+
+    #[test]
+    fn question_mark() {
+        fn sum(storage: &TestStorage) -> Result<u64, Error> {
+            let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+            let mut sum = 0;
+            for pair in map.access(storage).pairs() {
+                sum += pair?.1;
+            }
+            Ok(sum)
+        }
+
+        let mut storage = TestStorage::new();
+        storage.set(&[0, b'a'], &7u64.to_le_bytes());
+        assert_eq!(sum(&storage).unwrap(), 7);
+
+        storage.set(&[0, b'b'], &[1, 2]);
+        let Err(Error::Decode(source)) = sum(&storage) else {
+            panic!("expected a decode error");
+        };
+        assert_eq!(source.downcast_ref::<MockError>(), Some(&MockError));
+    }
+}
@@ -15,6 +15,12 @@
 //! for that.
 
 pub mod containers;
+#[cfg(feature = "fixed-decimal")]
+pub mod decimal;
 pub mod encoding;
 pub mod error;
 pub mod storage;
+#[cfg(feature = "digest")]
+pub use digest;
+#[cfg(feature = "proptest")]
+pub mod testing;
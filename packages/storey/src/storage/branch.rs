@@ -1,6 +1,10 @@
+use std::borrow::Cow;
 use std::ops::Bound;
 
-use crate::storage::{IterableStorage, RevIterableStorage, Storage, StorageMut};
+use crate::storage::{
+    prefix_successor, FlushError, FramingError, IndexKey, IterableStorage, KeyFraming,
+    RevIterableStorage, Storage, StorageMut, StorageRef, StorageRefMut,
+};
 
 /// A type representing a storage namespace created by applying a prefix to all keys.
 ///
@@ -22,67 +26,263 @@ use crate::storage::{IterableStorage, RevIterableStorage, Storage, StorageMut};
 /// assert_eq!(branch.get(b"bar"), Some(b"baz".to_vec()));
 /// assert_eq!(storage.get(b"foobar"), Some(b"baz".to_vec()));
 /// ```
+///
+/// A branch over a shared reference can be cloned cheaply (only the prefix is copied), which
+/// is handy for handing out several accessors to the same namespace.
+#[derive(Clone)]
 pub struct StorageBranch<S> {
     backend: S,
     prefix: Vec<u8>,
+    detached: bool,
 }
 
 impl<S> StorageBranch<S> {
     /// Creates a new `StorageBranch` instance given a prefix.
     pub fn new(backend: S, prefix: Vec<u8>) -> Self {
-        Self { backend, prefix }
+        Self {
+            backend,
+            prefix,
+            detached: false,
+        }
     }
-}
 
-impl<S: Storage> Storage for StorageBranch<&S> {
-    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
-        self.backend.get(&[&self.prefix[..], key].concat())
+    /// Creates a branch standing in for a namespace that can't hold any entries, e.g. the
+    /// namespace of a map key that can't be framed. Reads through it find nothing, and writes
+    /// through it are discarded.
+    pub(crate) fn detached(backend: S) -> Self {
+        Self {
+            backend,
+            prefix: Vec::new(),
+            detached: true,
+        }
     }
 
-    fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
-        self.backend.get_meta(&[&self.prefix[..], key].concat())
+    /// Runs `f` against a branch of `backend` under `prefix`, returning whatever `f` returns.
+    ///
+    /// This is handy for one-off operations on a manually laid out part of the storage: the
+    /// branch only lives for the duration of the closure, so there's no branch (or borrow of
+    /// the backend) left lingering afterwards.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::backend::TestStorage;
+    /// use storey::storage::{Storage as _, StorageMut as _, StorageBranch};
+    ///
+    /// let mut storage = TestStorage::new();
+    ///
+    /// let existed = StorageBranch::with_prefix(&mut storage, b"cfg/", |cfg| {
+    ///     cfg.set(b"owner", b"alice");
+    ///     cfg.has(b"admin")
+    /// });
+    ///
+    /// assert!(!existed);
+    /// assert_eq!(storage.get(b"cfg/owner"), Some(b"alice".to_vec()));
+    /// ```
+    pub fn with_prefix<T>(backend: S, prefix: &[u8], f: impl FnOnce(&mut Self) -> T) -> T {
+        f(&mut Self::new(backend, prefix.to_vec()))
+    }
+
+    /// Returns the prefix applied to keys by this branch.
+    ///
+    /// The prefix is relative to the backend this branch wraps. For branches nested in
+    /// other branches (e.g. accessors of containers inside a [`Map`](crate::containers::Map)),
+    /// the prefixes of the outer branches aren't included.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::backend::TestStorage;
+    /// use storey::storage::StorageBranch;
+    ///
+    /// let storage = TestStorage::new();
+    /// let branch = StorageBranch::new(&storage, b"foo".to_vec()).with_index(1u8);
+    ///
+    /// assert_eq!(branch.prefix(), b"foo\x01");
+    /// ```
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// Consumes the branch, returning the underlying backend.
+    pub fn into_inner(self) -> S {
+        self.backend
+    }
+
+    /// Appends `suffix` to the prefix, turning this into a branch nested under the original one.
+    pub(crate) fn nest(mut self, suffix: &[u8]) -> Self {
+        self.prefix.extend_from_slice(suffix);
+        self
+    }
+
+    /// Scopes this branch to the given index, appending it to the prefix as a big-endian
+    /// integer. See [`IndexKey`].
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::backend::TestStorage;
+    /// use storey::storage::{Storage as _, StorageMut as _, StorageBranch};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// StorageBranch::new(&mut storage, vec![0])
+    ///     .with_index(1u32)
+    ///     .set(b"foo", b"bar");
+    ///
+    /// assert_eq!(storage.get(b"\x00\x00\x00\x00\x01foo"), Some(b"bar".to_vec()));
+    /// ```
+    pub fn with_index<I: IndexKey>(self, index: I) -> Self {
+        self.nest(&index.to_index_bytes())
+    }
+
+    /// Scopes this branch to a path of segments, appending each one to the prefix framed
+    /// with `F`.
+    ///
+    /// This is exactly how a [`Map`] with a dynamically sized key (e.g. `String` or `Vec<u8>`)
+    /// and the framing `F` lays out a key followed by a nested container. Custom containers can
+    /// use this instead of re-deriving the framing.
+    ///
+    /// Like [`with_index`](Self::with_index), this consumes the branch. Clone a branch over a
+    /// shared reference first to keep the original.
+    ///
+    /// Fails if any segment can't be framed with `F`, e.g. a segment longer than 255 bytes
+    /// with [`ByteLenPrefix`](super::ByteLenPrefix).
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::{Column, Map};
+    /// use storey::storage::{ByteLenPrefix, Storage as _, StorageBranch};
+    ///
+    /// let mut storage = TestStorage::new();
+    /// let map = Map::<String, Map<String, Column<u64, TestEncoding>>>::new(0);
+    /// map.access(&mut storage).entry_mut("alice").entry_mut("eur").push(&1).unwrap();
+    ///
+    /// let branch =
+    ///     StorageBranch::new(&storage, vec![0]).branch_many::<ByteLenPrefix>(&[b"alice", b"eur"]);
+    /// assert!(branch.unwrap().has(&1u32.to_be_bytes()));
+    /// ```
+    ///
+    /// [`Map`]: crate::containers::Map
+    pub fn branch_many<F: KeyFraming>(mut self, segments: &[&[u8]]) -> Result<Self, FramingError> {
+        for segment in segments {
+            self.prefix.extend_from_slice(&F::frame(segment)?);
+        }
+        Ok(self)
     }
 }
 
-impl<S: Storage> Storage for StorageBranch<&mut S> {
+/// Applies `prefix` to `key`.
+///
+/// Accessors of single-value containers (e.g. an [`Item`](crate::containers::Item) inside a
+/// [`Map`](crate::containers::Map)) use the empty key, so this avoids allocating a new key
+/// for every operation in that case.
+fn full_key<'a>(prefix: &'a [u8], key: &[u8]) -> Cow<'a, [u8]> {
+    if key.is_empty() {
+        Cow::Borrowed(prefix)
+    } else {
+        Cow::Owned([prefix, key].concat())
+    }
+}
+
+impl<R: StorageRef> Storage for StorageBranch<R> {
     fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
-        self.backend.get(&[&self.prefix[..], key].concat())
+        if self.detached {
+            return None;
+        }
+
+        self.backend
+            .storage()
+            .get(&full_key(&self.prefix, key))
+    }
+
+    fn value_len(&self, key: &[u8]) -> Option<usize> {
+        if self.detached {
+            return None;
+        }
+
+        self.backend
+            .storage()
+            .value_len(&full_key(&self.prefix, key))
     }
 
     fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
-        self.backend.get_meta(&[&self.prefix[..], key].concat())
+        if self.detached {
+            return None;
+        }
+
+        self.backend
+            .storage()
+            .get_meta(&full_key(&self.prefix, key))
     }
 }
 
-impl<S: StorageMut> StorageMut for StorageBranch<&mut S> {
+impl<R: StorageRefMut> StorageMut for StorageBranch<R> {
     fn set(&mut self, key: &[u8], value: &[u8]) {
-        self.backend.set(&[&self.prefix[..], key].concat(), value)
+        if self.detached {
+            return;
+        }
+
+        self.backend
+            .storage_mut()
+            .set(&full_key(&self.prefix, key), value)
     }
 
     fn remove(&mut self, key: &[u8]) {
-        self.backend.remove(&[&self.prefix[..], key].concat())
+        if self.detached {
+            return;
+        }
+
+        self.backend
+            .storage_mut()
+            .remove(&full_key(&self.prefix, key))
     }
 
     fn set_meta(&mut self, key: &[u8], value: &[u8]) {
+        if self.detached {
+            return;
+        }
+
         self.backend
-            .set_meta(&[&self.prefix[..], key].concat(), value)
+            .storage_mut()
+            .set_meta(&full_key(&self.prefix, key), value)
     }
 
     fn remove_meta(&mut self, key: &[u8]) {
-        self.backend.remove_meta(&[&self.prefix[..], key].concat())
+        if self.detached {
+            return;
+        }
+
+        self.backend
+            .storage_mut()
+            .remove_meta(&full_key(&self.prefix, key))
+    }
+
+    fn flush(&mut self) -> Result<(), FlushError> {
+        self.backend.storage_mut().flush()
     }
 }
 
-impl<S: IterableStorage> IterableStorage for StorageBranch<&S> {
-    type KeysIterator<'a> = BranchKeysIter<S::KeysIterator<'a>> where Self: 'a;
-    type ValuesIterator<'a> = S::ValuesIterator<'a> where Self: 'a;
-    type PairsIterator<'a> = BranchKVIter<S::PairsIterator<'a>> where Self: 'a;
+impl<R: StorageRef> IterableStorage for StorageBranch<R>
+where
+    R::Target: IterableStorage,
+{
+    type KeysIterator<'a>
+        = BranchKeysIter<<R::Target as IterableStorage>::KeysIterator<'a>>
+    where
+        Self: 'a;
+    type ValuesIterator<'a>
+        = <R::Target as IterableStorage>::ValuesIterator<'a>
+    where
+        Self: 'a;
+    type PairsIterator<'a>
+        = BranchKVIter<<R::Target as IterableStorage>::PairsIterator<'a>>
+    where
+        Self: 'a;
 
     fn keys<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::KeysIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
+        let (start, end) = self.sub_bounds(start, end);
 
         BranchKeysIter {
-            inner: self.backend.keys(
+            inner: self.backend.storage().keys(
                 start.as_ref().map(AsRef::as_ref),
                 end.as_ref().map(AsRef::as_ref),
             ),
@@ -91,76 +291,58 @@ impl<S: IterableStorage> IterableStorage for StorageBranch<&S> {
     }
 
     fn values<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::ValuesIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
+        let (start, end) = self.sub_bounds(start, end);
 
-        self.backend.values(
+        self.backend.storage().values(
             start.as_ref().map(AsRef::as_ref),
             end.as_ref().map(AsRef::as_ref),
         )
     }
 
     fn pairs<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::PairsIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
+        let (start, end) = self.sub_bounds(start, end);
 
         BranchKVIter {
-            inner: self.backend.pairs(
+            inner: self.backend.storage().pairs(
                 start.as_ref().map(AsRef::as_ref),
                 end.as_ref().map(AsRef::as_ref),
             ),
             prefix_len: self.prefix.len(),
         }
     }
-}
-
-impl<S: IterableStorage> IterableStorage for StorageBranch<&mut S> {
-    type KeysIterator<'a> = BranchKeysIter<S::KeysIterator<'a>> where Self: 'a;
-    type ValuesIterator<'a> = S::ValuesIterator<'a> where Self: 'a;
-    type PairsIterator<'a> = BranchKVIter<S::PairsIterator<'a>> where Self: 'a;
 
-    fn keys<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::KeysIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
+    fn count_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> usize {
+        let (start, end) = self.sub_bounds(start, end);
 
-        BranchKeysIter {
-            inner: self.backend.keys(
-                start.as_ref().map(AsRef::as_ref),
-                end.as_ref().map(AsRef::as_ref),
-            ),
-            prefix_len: self.prefix.len(),
-        }
-    }
-
-    fn values<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::ValuesIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
-
-        self.backend.values(
+        self.backend.storage().count_range(
             start.as_ref().map(AsRef::as_ref),
             end.as_ref().map(AsRef::as_ref),
         )
     }
-
-    fn pairs<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::PairsIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
-
-        BranchKVIter {
-            inner: self.backend.pairs(
-                start.as_ref().map(AsRef::as_ref),
-                end.as_ref().map(AsRef::as_ref),
-            ),
-            prefix_len: self.prefix.len(),
-        }
-    }
 }
 
-impl<S: RevIterableStorage> RevIterableStorage for StorageBranch<&S> {
-    type RevKeysIterator<'a> = BranchKeysIter<S::RevKeysIterator<'a>> where Self: 'a;
-    type RevValuesIterator<'a> = S::RevValuesIterator<'a> where Self: 'a;
-    type RevPairsIterator<'a> = BranchKVIter<S::RevPairsIterator<'a>> where Self: 'a;
+impl<R: StorageRef> RevIterableStorage for StorageBranch<R>
+where
+    R::Target: RevIterableStorage,
+{
+    type RevKeysIterator<'a>
+        = BranchKeysIter<<R::Target as RevIterableStorage>::RevKeysIterator<'a>>
+    where
+        Self: 'a;
+    type RevValuesIterator<'a>
+        = <R::Target as RevIterableStorage>::RevValuesIterator<'a>
+    where
+        Self: 'a;
+    type RevPairsIterator<'a>
+        = BranchKVIter<<R::Target as RevIterableStorage>::RevPairsIterator<'a>>
+    where
+        Self: 'a;
 
     fn rev_keys<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::RevKeysIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
+        let (start, end) = self.sub_bounds(start, end);
 
         BranchKeysIter {
-            inner: self.backend.rev_keys(
+            inner: self.backend.storage().rev_keys(
                 start.as_ref().map(AsRef::as_ref),
                 end.as_ref().map(AsRef::as_ref),
             ),
@@ -173,9 +355,9 @@ impl<S: RevIterableStorage> RevIterableStorage for StorageBranch<&S> {
         start: Bound<&[u8]>,
         end: Bound<&[u8]>,
     ) -> Self::RevValuesIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
+        let (start, end) = self.sub_bounds(start, end);
 
-        self.backend.rev_values(
+        self.backend.storage().rev_values(
             start.as_ref().map(AsRef::as_ref),
             end.as_ref().map(AsRef::as_ref),
         )
@@ -186,10 +368,10 @@ impl<S: RevIterableStorage> RevIterableStorage for StorageBranch<&S> {
         start: Bound<&[u8]>,
         end: Bound<&[u8]>,
     ) -> Self::RevPairsIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
+        let (start, end) = self.sub_bounds(start, end);
 
         BranchKVIter {
-            inner: self.backend.rev_pairs(
+            inner: self.backend.storage().rev_pairs(
                 start.as_ref().map(AsRef::as_ref),
                 end.as_ref().map(AsRef::as_ref),
             ),
@@ -198,50 +380,18 @@ impl<S: RevIterableStorage> RevIterableStorage for StorageBranch<&S> {
     }
 }
 
-impl<S: RevIterableStorage> RevIterableStorage for StorageBranch<&mut S> {
-    type RevKeysIterator<'a> = BranchKeysIter<S::RevKeysIterator<'a>> where Self: 'a;
-    type RevValuesIterator<'a> = S::RevValuesIterator<'a> where Self: 'a;
-    type RevPairsIterator<'a> = BranchKVIter<S::RevPairsIterator<'a>> where Self: 'a;
-
-    fn rev_keys<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::RevKeysIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
-
-        BranchKeysIter {
-            inner: self.backend.rev_keys(
-                start.as_ref().map(AsRef::as_ref),
-                end.as_ref().map(AsRef::as_ref),
-            ),
-            prefix_len: self.prefix.len(),
-        }
-    }
-
-    fn rev_values<'a>(
-        &'a self,
-        start: Bound<&[u8]>,
-        end: Bound<&[u8]>,
-    ) -> Self::RevValuesIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
-
-        self.backend.rev_values(
-            start.as_ref().map(AsRef::as_ref),
-            end.as_ref().map(AsRef::as_ref),
-        )
-    }
-
-    fn rev_pairs<'a>(
-        &'a self,
+impl<S> StorageBranch<S> {
+    fn sub_bounds(
+        &self,
         start: Bound<&[u8]>,
         end: Bound<&[u8]>,
-    ) -> Self::RevPairsIterator<'a> {
-        let (start, end) = sub_bounds(&self.prefix, start, end);
-
-        BranchKVIter {
-            inner: self.backend.rev_pairs(
-                start.as_ref().map(AsRef::as_ref),
-                end.as_ref().map(AsRef::as_ref),
-            ),
-            prefix_len: self.prefix.len(),
+    ) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+        if self.detached {
+            // an empty half-open range
+            return (Bound::Included(Vec::new()), Bound::Excluded(Vec::new()));
         }
+
+        sub_bounds(&self.prefix, start, end)
     }
 }
 
@@ -262,13 +412,7 @@ fn sub_bounds(
                 start.map(|s| [prefix, s].concat())
             },
             if let Bound::Unbounded = end {
-                Bound::Excluded({
-                    let mut pref = prefix.to_vec();
-                    if let Some(x) = pref.last_mut() {
-                        *x += 1;
-                    }
-                    pref
-                })
+                prefix_successor(prefix).map_or(Bound::Unbounded, Bound::Excluded)
             } else {
                 end.map(|e| [prefix, e].concat())
             },
@@ -277,6 +421,7 @@ fn sub_bounds(
 }
 
 /// An iterator over the keys of a `StorageBranch`.
+#[derive(Clone)]
 pub struct BranchKeysIter<I> {
     inner: I,
     prefix_len: usize,
@@ -294,6 +439,7 @@ where
 }
 
 /// An iterator over the key-value pairs of a `StorageBranch`.
+#[derive(Clone)]
 pub struct BranchKVIter<I> {
     inner: I,
     prefix_len: usize,
@@ -334,6 +480,258 @@ mod tests {
         assert_eq!(storage.get(b"fooqux"), Some(b"quux".to_vec()));
     }
 
+    #[test]
+    fn empty_key() {
+        let mut storage = TestStorage::new();
+        let mut branch = StorageBranch::new(&mut storage, b"foo".to_vec());
+
+        branch.set(b"", b"bar");
+        assert_eq!(branch.get(b""), Some(b"bar".to_vec()));
+        assert_eq!(storage.get(b"foo"), Some(b"bar".to_vec()));
+
+        StorageBranch::new(&mut storage, b"foo".to_vec()).remove(b"");
+        assert_eq!(storage.get(b"foo"), None);
+    }
+
+    #[test]
+    fn nested_branches() {
+        // This is how composed containers work - e.g. an `Item` inside a `Map` inside a `Map`
+        // is a branch with an empty key over a branch over a branch.
+        let mut storage = TestStorage::new();
+        storage.set(b"fo", b"outside");
+        storage.set(b"foo", b"outer root");
+
+        let mut outer = StorageBranch::new(&mut storage, b"foo".to_vec());
+        outer.set(b"ba", b"sibling");
+
+        let mut inner = StorageBranch::new(&mut outer, b"bar".to_vec());
+        inner.set(b"", b"inner root");
+        inner.set(b"baz", b"qux");
+        assert_eq!(inner.get(b""), Some(b"inner root".to_vec()));
+        assert_eq!(inner.get(b"baz"), Some(b"qux".to_vec()));
+        assert_eq!(inner.get(b"ba"), None);
+
+        let mut nested = StorageBranch::new(&mut inner, vec![]).nest(b"baz");
+        nested.set(b"", b"quux");
+        assert_eq!(nested.get(b""), Some(b"quux".to_vec()));
+
+        assert_eq!(
+            inner
+                .pairs(Bound::Unbounded, Bound::Unbounded)
+                .collect::<Vec<_>>(),
+            vec![
+                (b"".to_vec(), b"inner root".to_vec()),
+                (b"baz".to_vec(), b"quux".to_vec()),
+            ]
+        );
+
+        inner.remove(b"");
+        assert_eq!(
+            inner.keys(Bound::Unbounded, Bound::Unbounded).collect::<Vec<_>>(),
+            vec![b"baz".to_vec()]
+        );
+
+        assert_eq!(
+            storage
+                .pairs(Bound::Unbounded, Bound::Unbounded)
+                .collect::<Vec<_>>(),
+            vec![
+                (b"fo".to_vec(), b"outside".to_vec()),
+                (b"foo".to_vec(), b"outer root".to_vec()),
+                (b"fooba".to_vec(), b"sibling".to_vec()),
+                (b"foobarbaz".to_vec(), b"quux".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn branch_many_matches_map_framing() {
+        use crate::containers::{Item, Map};
+        use crate::storage::{ByteLenPrefix, NullTerminated};
+
+        use mocks::encoding::TestEncoding;
+
+        let mut storage = TestStorage::new();
+        let map = Map::<String, Map<Vec<u8>, Map<u32, Item<u64, TestEncoding>>>>::new(0);
+
+        map.access(&mut storage)
+            .entry_mut("alice")
+            .entry_mut(b"eur".as_slice())
+            .entry_mut(&7)
+            .set(&100)
+            .unwrap();
+
+        let branch = StorageBranch::new(&storage, vec![0])
+            .branch_many::<ByteLenPrefix>(&[b"alice", b"eur"])
+            .unwrap();
+        assert_eq!(branch.prefix(), b"\x00\x05alice\x03eur");
+        assert_eq!(branch.get(&7u32.to_be_bytes()), Some(100u64.to_le_bytes().to_vec()));
+
+        StorageBranch::new(&mut storage, vec![0])
+            .branch_many::<ByteLenPrefix>(&[b"bob", b""])
+            .unwrap()
+            .set(&8u32.to_be_bytes(), &200u64.to_le_bytes());
+        assert_eq!(
+            map.access(&storage)
+                .entry("bob")
+                .entry(b"".as_slice())
+                .entry(&8)
+                .get(),
+            Ok(Some(200))
+        );
+
+        assert_eq!(
+            StorageBranch::new(&storage, vec![0])
+                .branch_many::<ByteLenPrefix>(&[])
+                .unwrap()
+                .prefix(),
+            &[0]
+        );
+
+        // other framings are honored too
+        let nt = Map::<String, Map<u32, Item<u64, TestEncoding>>, NullTerminated>::new(1);
+        nt.access(&mut storage)
+            .entry_mut("carol")
+            .entry_mut(&9)
+            .set(&300)
+            .unwrap();
+        let branch =
+            StorageBranch::new(&storage, vec![1]).branch_many::<NullTerminated>(&[b"carol"]);
+        let branch = branch.unwrap();
+        assert_eq!(branch.prefix(), b"\x01carol\x00");
+        assert_eq!(branch.get(&9u32.to_be_bytes()), Some(300u64.to_le_bytes().to_vec()));
+
+        // segments the framing can't represent are rejected
+        assert!(StorageBranch::new(&storage, vec![1])
+            .branch_many::<NullTerminated>(&[b"a\x00b"])
+            .is_err());
+        assert!(StorageBranch::new(&storage, vec![0])
+            .branch_many::<ByteLenPrefix>(&[&[7; 256]])
+            .is_err());
+    }
+
+    #[test]
+    fn with_prefix() {
+        use crate::containers::Item;
+
+        use mocks::encoding::TestEncoding;
+
+        let mut storage = TestStorage::new();
+
+        StorageBranch::with_prefix(&mut storage, b"foo", |branch| {
+            branch.set(b"bar", b"baz");
+            Item::<u64, TestEncoding>::new(0)
+                .access(&mut *branch)
+                .set(&1)
+                .unwrap();
+        });
+
+        assert_eq!(storage.get(b"foobar"), Some(b"baz".to_vec()));
+        assert_eq!(storage.get(b"foo\x00"), Some(1u64.to_le_bytes().to_vec()));
+
+        let keys = StorageBranch::with_prefix(&storage, b"foo", |branch| {
+            branch
+                .keys(Bound::Unbounded, Bound::Unbounded)
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(keys, vec![b"\x00".to_vec(), b"bar".to_vec()]);
+    }
+
+    #[test]
+    fn clone() {
+        use std::{cell::RefCell, rc::Rc};
+
+        // a cloneable handle that can write, so clones of one branch share the backend
+        #[derive(Clone)]
+        struct Shared(Rc<RefCell<TestStorage>>);
+
+        impl Storage for Shared {
+            fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+                self.0.borrow().get(key)
+            }
+
+            fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
+                self.0.borrow().get_meta(key)
+            }
+        }
+
+        impl StorageMut for Shared {
+            fn set(&mut self, key: &[u8], value: &[u8]) {
+                StorageMut::set(&mut *self.0.borrow_mut(), key, value)
+            }
+
+            fn remove(&mut self, key: &[u8]) {
+                StorageMut::remove(&mut *self.0.borrow_mut(), key)
+            }
+
+            fn set_meta(&mut self, key: &[u8], value: &[u8]) {
+                self.0.borrow_mut().set_meta(key, value)
+            }
+
+            fn remove_meta(&mut self, key: &[u8]) {
+                self.0.borrow_mut().remove_meta(key)
+            }
+        }
+
+        impl StorageRef for Shared {
+            type Target = Self;
+
+            fn storage(&self) -> &Self {
+                self
+            }
+        }
+
+        impl StorageRefMut for Shared {
+            type TargetMut = Self;
+
+            fn storage_mut(&mut self) -> &mut Self {
+                self
+            }
+        }
+
+        let storage = Rc::new(RefCell::new(TestStorage::new()));
+        let mut branch = StorageBranch::new(Shared(storage.clone()), b"foo".to_vec());
+        let mut cloned = branch.clone();
+
+        branch.set(b"bar", b"baz");
+        assert_eq!(cloned.get(b"bar"), Some(b"baz".to_vec()));
+
+        cloned.set(b"qux", b"quux");
+        assert_eq!(branch.get(b"qux"), Some(b"quux".to_vec()));
+
+        cloned.remove(b"bar");
+        assert_eq!(branch.get(b"bar"), None);
+        assert_eq!(storage.borrow().get(b"fooqux"), Some(b"quux".to_vec()));
+    }
+
+    #[test]
+    fn sub_bounds_max_byte_prefix() {
+        assert_eq!(
+            sub_bounds(&[1, 255], Bound::Unbounded, Bound::Unbounded),
+            (Bound::Included(vec![1, 255]), Bound::Excluded(vec![2]))
+        );
+        assert_eq!(
+            sub_bounds(&[255], Bound::Unbounded, Bound::Unbounded),
+            (Bound::Included(vec![255]), Bound::Unbounded)
+        );
+    }
+
+    #[test]
+    fn iteration_max_byte_prefix() {
+        let mut storage = TestStorage::new();
+        storage.set(&[1, 255, 0], b"foo");
+        storage.set(&[1, 255, 255], b"bar");
+        storage.set(&[2], b"baz");
+
+        let branch = StorageBranch::new(&storage, vec![1, 255]);
+        assert_eq!(
+            branch
+                .keys(Bound::Unbounded, Bound::Unbounded)
+                .collect::<Vec<_>>(),
+            vec![vec![0], vec![255]]
+        );
+    }
+
     #[test]
     fn sub_bounds_no_prefix() {
         assert_eq!(
@@ -454,4 +852,109 @@ mod tests {
         assert_eq!(storage.get(b"foobar"), None);
         assert_eq!(storage.get(b"fooqux"), None);
     }
+
+    #[test]
+    fn count_range_is_forwarded() {
+        use std::cell::Cell;
+
+        // A backend that counts natively (and records that it did).
+        #[derive(Default)]
+        struct Counting {
+            storage: TestStorage,
+            native_counts: Cell<usize>,
+        }
+
+        impl IterableStorage for Counting {
+            type KeysIterator<'a> = <TestStorage as IterableStorage>::KeysIterator<'a>;
+            type ValuesIterator<'a> = <TestStorage as IterableStorage>::ValuesIterator<'a>;
+            type PairsIterator<'a> = <TestStorage as IterableStorage>::PairsIterator<'a>;
+
+            fn keys<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::KeysIterator<'a> {
+                self.storage.keys(start, end)
+            }
+
+            fn values<'a>(
+                &'a self,
+                start: Bound<&[u8]>,
+                end: Bound<&[u8]>,
+            ) -> Self::ValuesIterator<'a> {
+                self.storage.values(start, end)
+            }
+
+            fn pairs<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::PairsIterator<'a> {
+                self.storage.pairs(start, end)
+            }
+
+            fn count_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> usize {
+                self.native_counts.set(self.native_counts.get() + 1);
+                self.storage.keys(start, end).count()
+            }
+        }
+
+        impl Storage for Counting {
+            fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+                self.storage.get(key)
+            }
+
+            fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
+                self.storage.get_meta(key)
+            }
+        }
+
+        let mut backend = Counting::default();
+        backend.storage.set(b"foo1", b"");
+        backend.storage.set(b"foo2", b"");
+        backend.storage.set(b"foo3", b"");
+        backend.storage.set(b"bar1", b"");
+
+        let branch = StorageBranch::new(&backend, b"foo".to_vec());
+        assert_eq!(branch.count_range(Bound::Unbounded, Bound::Unbounded), 3);
+        assert_eq!(branch.count_range(Bound::Excluded(b"1"), Bound::Unbounded), 2);
+        assert_eq!(backend.native_counts.get(), 2);
+    }
+
+    #[test]
+    fn value_len_is_forwarded() {
+        // A backend that knows value lengths without fetching values.
+        struct Lengths;
+
+        impl Storage for Lengths {
+            fn get(&self, _key: &[u8]) -> Option<Vec<u8>> {
+                panic!("value fetched")
+            }
+
+            fn value_len(&self, key: &[u8]) -> Option<usize> {
+                Some(key.len())
+            }
+
+            fn get_meta(&self, _key: &[u8]) -> Option<Vec<u8>> {
+                None
+            }
+        }
+
+        let branch = StorageBranch::new(&Lengths, b"foo".to_vec());
+        assert_eq!(branch.value_len(b"ba"), Some(5));
+        assert_eq!(branch.value_len(b""), Some(3));
+    }
+
+    #[test]
+    fn clear_all() {
+        let mut storage = TestStorage::new();
+        storage.set(b"bar", b"untouched");
+
+        let mut branch = StorageBranch::new(&mut storage, b"foo".to_vec());
+        branch.set(b"", b"root");
+        branch.set(b"baz", b"qux");
+        branch.set_meta(b"len", b"2");
+
+        branch.clear_all();
+        assert_eq!(branch.keys(Bound::Unbounded, Bound::Unbounded).count(), 0);
+        assert_eq!(branch.get_meta(b"len"), Some(b"2".to_vec()));
+
+        assert_eq!(storage.get(b"bar"), Some(b"untouched".to_vec()));
+
+        storage.clear_all();
+        assert_eq!(storage.keys(Bound::Unbounded, Bound::Unbounded).count(), 0);
+        assert_eq!(storage.get_meta(b"foolen"), None);
+    }
 }
@@ -0,0 +1,261 @@
+use std::ops::Bound;
+
+use crate::storage::{IterableStorage, RevIterableStorage, Storage, StorageMut};
+
+/// A storage wrapper that serves reads from the underlying backend, but discards all writes.
+///
+/// Writes are not applied to the backend. Instead, they're recorded and can be inspected with
+/// [`writes`](Self::writes) and [`meta_writes`](Self::meta_writes). This is useful for
+/// simulating the effects of some code without persisting anything.
+///
+/// Reads always reflect the state of the underlying backend - recorded writes are **not**
+/// visible to subsequent reads.
+///
+/// If the writes don't need inspecting (e.g. for a cheap estimation pass), create the wrapper
+/// with [`discarding`](Self::discarding) instead. Writes are then silently dropped without
+/// being recorded, so nothing is buffered or allocated for them.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::Item;
+/// use storey::storage::DryRunStorage;
+///
+/// let storage = TestStorage::new();
+/// let mut dry_run = DryRunStorage::new(&storage);
+///
+/// let item = Item::<u64, TestEncoding>::new(0);
+/// item.access(&mut dry_run).set(&42).unwrap();
+///
+/// assert_eq!(dry_run.writes(), &[(vec![0], Some(42u64.to_le_bytes().to_vec()))]);
+/// assert_eq!(item.access(&storage).get().unwrap(), None);
+/// ```
+pub struct DryRunStorage<S> {
+    backend: S,
+    writes: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+    meta_writes: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+    record: bool,
+}
+
+impl<S> DryRunStorage<S> {
+    /// Creates a new `DryRunStorage` wrapping the given backend.
+    pub fn new(backend: S) -> Self {
+        Self {
+            backend,
+            writes: Vec::new(),
+            meta_writes: Vec::new(),
+            record: true,
+        }
+    }
+
+    /// Creates a new `DryRunStorage` wrapping the given backend that drops writes without
+    /// recording them.
+    ///
+    /// [`writes`](Self::writes) and [`meta_writes`](Self::meta_writes) always return empty
+    /// slices for such a wrapper.
+    ///
+    /// # Example
+    /// ```
+    /// # use mocks::encoding::TestEncoding;
+    /// # use mocks::backend::TestStorage;
+    /// use storey::containers::Item;
+    /// use storey::storage::DryRunStorage;
+    ///
+    /// let storage = TestStorage::new();
+    /// let mut dry_run = DryRunStorage::discarding(&storage);
+    ///
+    /// let item = Item::<u64, TestEncoding>::new(0);
+    /// item.access(&mut dry_run).set(&42).unwrap();
+    ///
+    /// assert!(dry_run.writes().is_empty());
+    /// assert_eq!(item.access(&dry_run).get().unwrap(), None);
+    /// ```
+    pub fn discarding(backend: S) -> Self {
+        Self {
+            record: false,
+            ..Self::new(backend)
+        }
+    }
+
+    /// Returns the writes recorded so far, in the order they were made.
+    ///
+    /// A `None` value represents a removal.
+    pub fn writes(&self) -> &[(Vec<u8>, Option<Vec<u8>>)] {
+        &self.writes
+    }
+
+    /// Returns the writes to the metadata namespace recorded so far, in the order they
+    /// were made.
+    ///
+    /// A `None` value represents a removal.
+    pub fn meta_writes(&self) -> &[(Vec<u8>, Option<Vec<u8>>)] {
+        &self.meta_writes
+    }
+
+    /// Consumes the wrapper, returning the underlying backend.
+    pub fn into_inner(self) -> S {
+        self.backend
+    }
+}
+
+impl<S: Storage> Storage for DryRunStorage<&S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.backend.get(key)
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        self.backend.has(key)
+    }
+
+    fn value_len(&self, key: &[u8]) -> Option<usize> {
+        self.backend.value_len(key)
+    }
+
+    fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.backend.get_meta(key)
+    }
+
+    fn has_meta(&self, key: &[u8]) -> bool {
+        self.backend.has_meta(key)
+    }
+}
+
+impl<S: Storage> StorageMut for DryRunStorage<&S> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        if self.record {
+            self.writes.push((key.to_vec(), Some(value.to_vec())));
+        }
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        if self.record {
+            self.writes.push((key.to_vec(), None));
+        }
+    }
+
+    fn set_meta(&mut self, key: &[u8], value: &[u8]) {
+        if self.record {
+            self.meta_writes.push((key.to_vec(), Some(value.to_vec())));
+        }
+    }
+
+    fn remove_meta(&mut self, key: &[u8]) {
+        if self.record {
+            self.meta_writes.push((key.to_vec(), None));
+        }
+    }
+}
+
+impl<S: IterableStorage> IterableStorage for DryRunStorage<&S> {
+    type KeysIterator<'a> = S::KeysIterator<'a> where Self: 'a;
+    type ValuesIterator<'a> = S::ValuesIterator<'a> where Self: 'a;
+    type PairsIterator<'a> = S::PairsIterator<'a> where Self: 'a;
+
+    fn keys<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::KeysIterator<'a> {
+        self.backend.keys(start, end)
+    }
+
+    fn values<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::ValuesIterator<'a> {
+        self.backend.values(start, end)
+    }
+
+    fn pairs<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::PairsIterator<'a> {
+        self.backend.pairs(start, end)
+    }
+
+    fn count_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> usize {
+        self.backend.count_range(start, end)
+    }
+}
+
+impl<S: RevIterableStorage> RevIterableStorage for DryRunStorage<&S> {
+    type RevKeysIterator<'a> = S::RevKeysIterator<'a> where Self: 'a;
+    type RevValuesIterator<'a> = S::RevValuesIterator<'a> where Self: 'a;
+    type RevPairsIterator<'a> = S::RevPairsIterator<'a> where Self: 'a;
+
+    fn rev_keys<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::RevKeysIterator<'a> {
+        self.backend.rev_keys(start, end)
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        self.backend.rev_values(start, end)
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        self.backend.rev_pairs(start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mocks::backend::TestStorage;
+
+    #[test]
+    fn writes_are_discarded() {
+        let mut storage = TestStorage::new();
+        storage.set(b"foo", b"bar");
+
+        let mut dry_run = DryRunStorage::new(&storage);
+        dry_run.set(b"baz", b"qux");
+        dry_run.remove(b"foo");
+        dry_run.set_meta(b"meta", b"data");
+
+        assert_eq!(dry_run.get(b"foo"), Some(b"bar".to_vec()));
+        assert_eq!(dry_run.get(b"baz"), None);
+        assert_eq!(
+            dry_run.writes(),
+            &[
+                (b"baz".to_vec(), Some(b"qux".to_vec())),
+                (b"foo".to_vec(), None)
+            ]
+        );
+        assert_eq!(
+            dry_run.meta_writes(),
+            &[(b"meta".to_vec(), Some(b"data".to_vec()))]
+        );
+
+        assert_eq!(storage.get(b"foo"), Some(b"bar".to_vec()));
+        assert_eq!(storage.get(b"baz"), None);
+        assert_eq!(storage.get_meta(b"meta"), None);
+    }
+
+    #[test]
+    fn discarding() {
+        let mut storage = TestStorage::new();
+        storage.set(b"foo", b"bar");
+
+        let mut dry_run = DryRunStorage::discarding(&storage);
+        dry_run.set(b"baz", b"qux");
+        dry_run.remove(b"foo");
+        dry_run.remove_meta(b"meta");
+
+        assert_eq!(dry_run.get(b"foo"), Some(b"bar".to_vec()));
+        assert!(dry_run.writes().is_empty());
+        assert!(dry_run.meta_writes().is_empty());
+
+        assert_eq!(storage.get(b"baz"), None);
+    }
+
+    #[test]
+    fn iteration_reads_backend() {
+        let mut storage = TestStorage::new();
+        storage.set(b"foo", b"bar");
+
+        let mut dry_run = DryRunStorage::new(&storage);
+        dry_run.set(b"baz", b"qux");
+
+        let pairs: Vec<_> = dry_run.pairs(Bound::Unbounded, Bound::Unbounded).collect();
+        assert_eq!(pairs, vec![(b"foo".to_vec(), b"bar".to_vec())]);
+    }
+}
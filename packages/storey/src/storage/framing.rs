@@ -0,0 +1,276 @@
+/// The way a [`Map`](crate::containers::Map) frames dynamically sized keys that are followed
+/// by the keys of a nested container (e.g. the `String` keys of a `Map<String, Map<u32, _>>`).
+///
+/// Without framing, there would be no telling where the map's key ends and the nested key
+/// begins. Fixed-size keys and keys of maps holding terminal values (e.g. [`Item`]s) are never
+/// framed.
+///
+/// The default is [`ByteLenPrefix`]. Changing the framing of an existing map changes its
+/// storage layout, so existing data has to be migrated (see [`Map::rekey`]).
+///
+/// # Implementing a framing
+///
+/// The framing must be prefix-free: no framed key may be a prefix of another framed key.
+/// Otherwise the entries of nested containers under different keys could get mixed up.
+/// [`split`](Self::split) must undo [`frame`](Self::frame), returning the original key and
+/// whatever followed the framed key.
+///
+/// [`Item`]: crate::containers::Item
+/// [`Map::rekey`]: crate::containers::Map::rekey
+pub trait KeyFraming {
+    /// Frames `key`.
+    ///
+    /// Keys the framing can't represent (e.g. keys that are too long) are rejected with
+    /// [`FramingError::Unrepresentable`].
+    fn frame(key: &[u8]) -> Result<Vec<u8>, FramingError>;
+
+    /// Splits a framed key off the front of `bytes`, returning the key and the rest of the
+    /// bytes.
+    fn split(bytes: &[u8]) -> Result<(&[u8], &[u8]), FramingError>;
+}
+
+/// An error returned by [`KeyFraming`] for keys that can't be framed, or bytes that don't
+/// start with a framed key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FramingError {
+    /// There are no bytes at all.
+    #[error("empty key, expected a framed key")]
+    Empty,
+    /// The frame announces a key of this length, but fewer bytes follow.
+    #[error("key too short, expected {0} bytes after the frame header")]
+    TooShort(usize),
+    /// The bytes don't form a valid frame.
+    #[error("malformed key frame")]
+    Malformed,
+    /// The key can't be framed.
+    #[error("key can't be represented in this framing")]
+    Unrepresentable,
+}
+
+impl crate::error::StoreyError for FramingError {}
+
+/// Prefixes keys with their length as a single byte. This is the default framing.
+///
+/// It's the most compact framing for short keys, but keys can't be longer than 255 bytes -
+/// framing a longer key fails.
+pub struct ByteLenPrefix;
+
+impl KeyFraming for ByteLenPrefix {
+    fn frame(key: &[u8]) -> Result<Vec<u8>, FramingError> {
+        let len = u8::try_from(key.len()).map_err(|_| FramingError::Unrepresentable)?;
+
+        let mut result = Vec::with_capacity(key.len() + 1);
+        result.push(len);
+        result.extend_from_slice(key);
+        Ok(result)
+    }
+
+    fn split(bytes: &[u8]) -> Result<(&[u8], &[u8]), FramingError> {
+        let (&len, rest) = bytes.split_first().ok_or(FramingError::Empty)?;
+        let len = len as usize;
+
+        if rest.len() < len {
+            return Err(FramingError::TooShort(len));
+        }
+
+        Ok(rest.split_at(len))
+    }
+}
+
+/// Prefixes keys with their length encoded as an unsigned LEB128 varint.
+///
+/// Keys shorter than 128 bytes take a single byte of overhead, just like with
+/// [`ByteLenPrefix`], but there's no practical limit on key length.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::map::VarintLenPrefix;
+/// use storey::containers::{Item, Map};
+///
+/// let mut storage = TestStorage::new();
+/// let map = Map::<String, Map<u32, Item<u64, TestEncoding>>, VarintLenPrefix>::new(0);
+///
+/// let long_key = "x".repeat(1000);
+/// map.access(&mut storage).entry_mut(&long_key).entry_mut(&1).set(&7).unwrap();
+/// assert_eq!(map.access(&storage).entry(&long_key).entry(&1).get().unwrap(), Some(7));
+/// ```
+pub struct VarintLenPrefix;
+
+impl KeyFraming for VarintLenPrefix {
+    fn frame(key: &[u8]) -> Result<Vec<u8>, FramingError> {
+        let mut result = Vec::with_capacity(key.len() + 2);
+
+        let mut len = key.len();
+        while len >= 0x80 {
+            result.push((len as u8 & 0x7f) | 0x80);
+            len >>= 7;
+        }
+        result.push(len as u8);
+
+        result.extend_from_slice(key);
+        Ok(result)
+    }
+
+    fn split(bytes: &[u8]) -> Result<(&[u8], &[u8]), FramingError> {
+        if bytes.is_empty() {
+            return Err(FramingError::Empty);
+        }
+
+        let mut len: usize = 0;
+        for (i, &byte) in bytes.iter().enumerate() {
+            let chunk = (byte & 0x7f) as usize;
+            let shift = 7 * i as u32;
+
+            // reject lengths that don't fit a `usize`, and non-canonical encodings
+            // (trailing zero groups), so that every key has exactly one framing
+            if shift >= usize::BITS || chunk.checked_shl(shift).map(|c| c >> shift) != Some(chunk) {
+                return Err(FramingError::Malformed);
+            }
+            len |= chunk << shift;
+
+            if byte & 0x80 == 0 {
+                if i > 0 && byte == 0 {
+                    return Err(FramingError::Malformed);
+                }
+
+                let rest = &bytes[i + 1..];
+                if rest.len() < len {
+                    return Err(FramingError::TooShort(len));
+                }
+                return Ok(rest.split_at(len));
+            }
+        }
+
+        Err(FramingError::Malformed)
+    }
+}
+
+/// Terminates keys with a zero byte.
+///
+/// Unlike length prefixes, this keeps framed keys in the same order as the keys themselves,
+/// so e.g. string keys of a map of maps are iterated in lexicographical order. Keys must not
+/// contain zero bytes - framing such a key fails.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::map::NullTerminated;
+/// use storey::containers::{Item, Map};
+///
+/// let mut storage = TestStorage::new();
+/// let map = Map::<String, Map<u32, Item<u64, TestEncoding>>, NullTerminated>::new(0);
+/// let mut access = map.access(&mut storage);
+///
+/// access.entry_mut("bb").entry_mut(&1).set(&1).unwrap();
+/// access.entry_mut("a").entry_mut(&1).set(&2).unwrap();
+///
+/// let keys = access.group_keys().collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(keys, vec!["a".to_string(), "bb".to_string()]);
+/// ```
+pub struct NullTerminated;
+
+impl KeyFraming for NullTerminated {
+    fn frame(key: &[u8]) -> Result<Vec<u8>, FramingError> {
+        if key.contains(&0) {
+            return Err(FramingError::Unrepresentable);
+        }
+
+        let mut result = Vec::with_capacity(key.len() + 1);
+        result.extend_from_slice(key);
+        result.push(0);
+        Ok(result)
+    }
+
+    fn split(bytes: &[u8]) -> Result<(&[u8], &[u8]), FramingError> {
+        if bytes.is_empty() {
+            return Err(FramingError::Empty);
+        }
+
+        let end = bytes
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(FramingError::Malformed)?;
+        Ok((&bytes[..end], &bytes[end + 1..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip<F: KeyFraming>(keys: &[&[u8]]) {
+        for key in keys {
+            let mut framed = F::frame(key).unwrap();
+            assert_eq!(F::split(&framed), Ok((*key, &[][..])));
+
+            framed.extend_from_slice(b"rest");
+            assert_eq!(F::split(&framed), Ok((*key, &b"rest"[..])));
+        }
+    }
+
+    #[test]
+    fn byte_len_prefix() {
+        roundtrip::<ByteLenPrefix>(&[b"", b"foo", &[7; 255]]);
+
+        assert_eq!(ByteLenPrefix::frame(b"foo").unwrap(), b"\x03foo");
+        assert_eq!(
+            ByteLenPrefix::frame(&[7; 256]),
+            Err(FramingError::Unrepresentable)
+        );
+        assert_eq!(ByteLenPrefix::split(&[]), Err(FramingError::Empty));
+        assert_eq!(
+            ByteLenPrefix::split(&[3, 1]),
+            Err(FramingError::TooShort(3))
+        );
+    }
+
+    #[test]
+    fn varint_len_prefix() {
+        roundtrip::<VarintLenPrefix>(&[b"", b"foo", &[7; 127], &[7; 128], &[7; 20000]]);
+
+        assert_eq!(VarintLenPrefix::frame(b"foo").unwrap(), b"\x03foo");
+        assert_eq!(
+            &VarintLenPrefix::frame(&[7; 300]).unwrap()[..2],
+            &[0xac, 0x02]
+        );
+
+        assert_eq!(VarintLenPrefix::split(&[]), Err(FramingError::Empty));
+        assert_eq!(
+            VarintLenPrefix::split(&[0x80]),
+            Err(FramingError::Malformed)
+        );
+        assert_eq!(
+            VarintLenPrefix::split(&[0x83, 0x00, 1, 2, 3]),
+            Err(FramingError::Malformed)
+        );
+        assert_eq!(
+            VarintLenPrefix::split(&[0xff; 11]),
+            Err(FramingError::Malformed)
+        );
+        assert_eq!(
+            VarintLenPrefix::split(&[0xac, 0x02, 1]),
+            Err(FramingError::TooShort(300))
+        );
+    }
+
+    #[test]
+    fn null_terminated() {
+        roundtrip::<NullTerminated>(&[b"", b"foo", &[0xff; 300]]);
+
+        assert_eq!(NullTerminated::frame(b"foo").unwrap(), b"foo\x00");
+        assert_eq!(
+            NullTerminated::frame(b"a\x00b"),
+            Err(FramingError::Unrepresentable)
+        );
+        assert_eq!(NullTerminated::split(&[]), Err(FramingError::Empty));
+        assert_eq!(NullTerminated::split(b"foo"), Err(FramingError::Malformed));
+
+        // order is preserved
+        assert!(NullTerminated::frame(b"a").unwrap() < NullTerminated::frame(b"ab").unwrap());
+        assert!(NullTerminated::frame(b"ab").unwrap() < NullTerminated::frame(b"b").unwrap());
+    }
+}
@@ -0,0 +1,84 @@
+/// An unsigned integer that can be used as an index sub-key.
+///
+/// Indices are encoded as fixed-size big-endian integers, so that the lexicographical order
+/// of the encoded keys matches the numerical order of the indices. Sequence containers like
+/// [`Column`](crate::containers::Column) should use this (or
+/// [`StorageBranch::with_index`](super::StorageBranch::with_index)) rather than encoding
+/// indices themselves.
+///
+/// # Example
+/// ```
+/// use storey::storage::IndexKey;
+///
+/// assert_eq!(258u32.to_index_bytes(), vec![0, 0, 1, 2]);
+/// assert_eq!(u32::from_index_bytes(&[0, 0, 1, 2]), Some(258));
+/// assert_eq!(u32::from_index_bytes(&[1, 2]), None);
+/// ```
+pub trait IndexKey: Copy {
+    /// The length of the encoded index in bytes.
+    const LEN: usize;
+
+    /// Encodes the index as a big-endian integer.
+    fn to_index_bytes(self) -> Vec<u8>;
+
+    /// Decodes an index, returning `None` if `bytes` isn't exactly [`LEN`](Self::LEN) long.
+    fn from_index_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_index_key {
+    ($($t:ty),*) => {
+        $(
+            impl IndexKey for $t {
+                const LEN: usize = std::mem::size_of::<$t>();
+
+                fn to_index_bytes(self) -> Vec<u8> {
+                    self.to_be_bytes().to_vec()
+                }
+
+                fn from_index_bytes(bytes: &[u8]) -> Option<Self> {
+                    bytes.try_into().ok().map(<$t>::from_be_bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_index_key!(u8, u16, u32, u64, u128);
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::*;
+
+    use crate::storage::{IterableStorage as _, StorageBranch, StorageMut as _};
+
+    use mocks::backend::TestStorage;
+
+    #[test]
+    fn indices_sort_numerically() {
+        let mut storage = TestStorage::new();
+
+        // insert in an order that would sort wrong with little-endian or unpadded keys
+        for i in (0..300u64).rev() {
+            StorageBranch::new(&mut storage, vec![0])
+                .with_index(i)
+                .set(&[], &[]);
+            StorageBranch::new(&mut storage, vec![1])
+                .with_index(i as u16)
+                .set(&[], &[]);
+        }
+
+        let u64s: Vec<_> = StorageBranch::new(&storage, vec![0])
+            .keys(Bound::Unbounded, Bound::Unbounded)
+            .map(|key| u64::from_index_bytes(&key).unwrap())
+            .collect();
+        assert_eq!(u64s, (0..300).collect::<Vec<_>>());
+
+        let u16s: Vec<_> = StorageBranch::new(&storage, vec![1])
+            .keys(Bound::Unbounded, Bound::Unbounded)
+            .map(|key| u16::from_index_bytes(&key).unwrap())
+            .collect();
+        assert_eq!(u16s, (0..300).collect::<Vec<_>>());
+    }
+}
@@ -9,13 +9,98 @@
 //! and [`IterableStorage`] represent binary storage types that provide iteration. These traits
 //! are something you might be interested in if you're implementing a new container.
 //!
+//! [`StorageRef`] and [`StorageRefMut`] abstract over holding storage by shared or mutable
+//! reference. See [Generic code](#generic-code).
+//!
 //! [`StorageBranch`] is a storage namespace. It can be used to divide a backend's key namespace
 //! into smaller namespaces. This is a fundamental building block for the hierarchy of storage
 //! containers. You only need to be aware of it if you're implementing a new container.
+//!
+//! [`DryRunStorage`] is a wrapper that serves reads from a backend, but records and discards
+//! writes. It can be used to simulate the effects of some code without persisting them. Use
+//! [`DryRunStorage::discarding`] to drop writes without recording them.
+//!
+//! [`Transaction`] is a wrapper that stages writes in memory, serving reads (including
+//! iteration) from the merged view, until they're committed to the backend.
+//!
+//! [`export`] and [`import`] serialize all entries of a storage (e.g. a container's namespace)
+//! to a byte stream and back, which can be used for backups and state migrations.
+//!
+//! # Metadata
+//!
+//! Containers that need bookkeeping (e.g. the length of a [`Column`]) must keep it in the
+//! metadata namespace ([`Storage::get_meta`], [`StorageMut::set_meta`]) rather than under a
+//! regular key. Storage backends keep metadata under keys starting with the byte `255`,
+//! outside of any container's namespace, so iterating over a container's elements never
+//! yields its metadata. For the same reason, `255` must not be used as the prefix of a
+//! top-level container.
+//!
+//! [`Column`]: crate::containers::Column
+//!
+//! # Generic code
+//!
+//! Containers accept storage either by shared (`&S`) or by mutable (`&mut S`) reference.
+//! Library code that wraps `storey` and wants to accept both should be generic over
+//! [`StorageRef`] (or [`StorageRefMut`] if it writes) rather than over the storage type
+//! itself:
+//!
+//! - `R: StorageRef` - reading. Accepts `&S` and `&mut S`.
+//! - `R: StorageRefMut` - reading and writing. Accepts `&mut S`.
+//! - add `R::Target: IterableStorage` (or `R::Target: RevIterableStorage`) to either of
+//!   the above if you need iteration.
+//!
+//! ```
+//! # use mocks::encoding::TestEncoding;
+//! # use mocks::backend::TestStorage;
+//! use storey::containers::{IterableAccessor as _, Item, Map};
+//! use storey::storage::{IterableStorage, StorageRef, StorageRefMut};
+//!
+//! const COUNTER: Item<u64, TestEncoding> = Item::new(0);
+//! const BALANCES: Map<String, Item<u64, TestEncoding>> = Map::new(1);
+//!
+//! fn counter<R: StorageRef>(storage: R) -> u64 {
+//!     COUNTER.access(storage).get().unwrap().unwrap_or(0)
+//! }
+//!
+//! fn bump<R: StorageRefMut>(storage: R) {
+//!     COUNTER.access(storage).update(|v| Some(v.unwrap_or(0) + 1)).unwrap();
+//! }
+//!
+//! fn total_balance<R>(storage: R) -> u64
+//! where
+//!     R: StorageRef,
+//!     R::Target: IterableStorage,
+//! {
+//!     BALANCES.access(storage).values().map(Result::unwrap).sum()
+//! }
+//!
+//! let mut storage = TestStorage::new();
+//!
+//! bump(&mut storage);
+//! assert_eq!(counter(&mut storage), 1);
+//! assert_eq!(counter(&storage), 1);
+//!
+//! BALANCES.access(&mut storage).entry_mut("alice").set(&5).unwrap();
+//! BALANCES.access(&mut storage).entry_mut("bob").set(&7).unwrap();
+//! assert_eq!(total_balance(&storage), 12);
+//! ```
 
 mod branch;
+mod dry_run;
+mod framing;
+mod index;
+mod storage_ref;
+mod stream;
+mod transaction;
 
 pub use branch::StorageBranch;
+pub use dry_run::DryRunStorage;
+pub use framing::{ByteLenPrefix, FramingError, KeyFraming, NullTerminated, VarintLenPrefix};
+pub use index::IndexKey;
+pub use storage_ref::{StorageRef, StorageRefMut};
 pub use storey_storage::{
-    IterableStorage, RevIterableStorage, Storage, StorageBackend, StorageBackendMut, StorageMut,
+    prefix_successor, FlushError, IterableStorage, RevIterableStorage, Storage, StorageBackend,
+    StorageBackendMut, StorageMut,
 };
+pub use stream::{export, import, ImportError};
+pub use transaction::{MergedKeys, MergedPairs, MergedValues, Transaction};
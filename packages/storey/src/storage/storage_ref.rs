@@ -0,0 +1,58 @@
+use crate::storage::{Storage, StorageMut};
+
+/// A handle to storage, held either by shared (`&S`) or by mutable (`&mut S`) reference.
+///
+/// A [`StorageBranch`] over a `StorageRef` implements [`Storage`], so any accessor created
+/// from one (e.g. with [`access`]) can be read from.
+///
+/// Library code building on `storey` can use this trait as a bound to accept either kind of
+/// reference without duplicating its methods. See the
+/// [module docs](crate::storage#generic-code) for the recommended bounds.
+///
+/// [`access`]: crate::containers::Item::access
+/// [`StorageBranch`]: crate::storage::StorageBranch
+pub trait StorageRef {
+    /// The storage being referenced.
+    type Target: Storage + ?Sized;
+
+    /// Returns a shared reference to the storage.
+    fn storage(&self) -> &Self::Target;
+}
+
+/// A handle to storage held by mutable reference (`&mut S`).
+///
+/// A [`StorageBranch`] over a `StorageRefMut` implements both [`Storage`] and [`StorageMut`].
+///
+/// [`StorageBranch`]: crate::storage::StorageBranch
+pub trait StorageRefMut: StorageRef<Target = <Self as StorageRefMut>::TargetMut> {
+    /// The storage being referenced. The supertrait bound makes this the same type as
+    /// [`StorageRef::Target`], with the added guarantee that it implements [`StorageMut`].
+    type TargetMut: Storage + StorageMut + ?Sized;
+
+    /// Returns a mutable reference to the storage.
+    fn storage_mut(&mut self) -> &mut Self::TargetMut;
+}
+
+impl<S: Storage + ?Sized> StorageRef for &S {
+    type Target = S;
+
+    fn storage(&self) -> &S {
+        self
+    }
+}
+
+impl<S: Storage + ?Sized> StorageRef for &mut S {
+    type Target = S;
+
+    fn storage(&self) -> &S {
+        self
+    }
+}
+
+impl<S: Storage + StorageMut + ?Sized> StorageRefMut for &mut S {
+    type TargetMut = S;
+
+    fn storage_mut(&mut self) -> &mut S {
+        self
+    }
+}
@@ -0,0 +1,159 @@
+use std::io::{self, Read, Write};
+use std::ops::Bound;
+
+use crate::storage::{IterableStorage, StorageMut};
+
+/// Writes every entry of `storage` to `writer`, returning the number of entries written.
+///
+/// Each entry is framed as the key and the value, each preceded by its length as a big-endian
+/// `u32`. The stream can be restored with [`import`].
+///
+/// Only regular entries are exported - metadata (see [Metadata](crate::storage#metadata))
+/// isn't iterable, so it's not part of the stream.
+///
+/// # Example
+/// ```
+/// # use mocks::backend::TestStorage;
+/// use storey::storage::{export, import, Storage as _, StorageMut as _};
+///
+/// let mut storage = TestStorage::new();
+/// storage.set(b"foo", b"bar");
+/// storage.set(b"baz", b"");
+///
+/// let mut stream = Vec::new();
+/// assert_eq!(export(&storage, &mut stream).unwrap(), 2);
+///
+/// let mut restored = TestStorage::new();
+/// assert_eq!(import(&mut restored, &stream[..]).unwrap(), 2);
+/// assert_eq!(restored.get(b"foo"), Some(b"bar".to_vec()));
+/// assert_eq!(restored.get(b"baz"), Some(b"".to_vec()));
+/// ```
+pub fn export<S, W>(storage: &S, mut writer: W) -> io::Result<usize>
+where
+    S: IterableStorage + ?Sized,
+    W: Write,
+{
+    let mut count = 0;
+
+    for (key, value) in storage.pairs(Bound::Unbounded, Bound::Unbounded) {
+        write_frame(&mut writer, &key)?;
+        write_frame(&mut writer, &value)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Reads entries produced by [`export`] from `reader` and writes them to `storage`, returning
+/// the number of entries imported.
+///
+/// Existing entries are overwritten if the stream contains the same key, and left alone
+/// otherwise. Clear the target first to get an exact copy.
+///
+/// Entries are written as they're read, so if the stream turns out to be malformed, the
+/// entries before the malformed one have already been imported.
+pub fn import<S, R>(storage: &mut S, mut reader: R) -> Result<usize, ImportError>
+where
+    S: StorageMut + ?Sized,
+    R: Read,
+{
+    let mut count = 0;
+
+    while let Some(key) = read_frame(&mut reader, true)? {
+        let value = read_frame(&mut reader, false)?.ok_or(ImportError::Truncated)?;
+        storage.set(&key, &value);
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+fn write_frame<W: Write>(writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "entry too large to export"))?;
+
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(bytes)
+}
+
+/// Reads a length-prefixed frame. Returns `Ok(None)` if the stream ends cleanly before the
+/// frame and `allow_eof` is set.
+fn read_frame<R: Read>(reader: &mut R, allow_eof: bool) -> Result<Option<Vec<u8>>, ImportError> {
+    let mut len = [0; 4];
+    let mut filled = 0;
+
+    while filled < len.len() {
+        match reader.read(&mut len[filled..]) {
+            Ok(0) if filled == 0 && allow_eof => return Ok(None),
+            Ok(0) => return Err(ImportError::Truncated),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(ImportError::Io(e)),
+        }
+    }
+
+    let mut bytes = Vec::new();
+    let len = u32::from_be_bytes(len) as u64;
+    reader.take(len).read_to_end(&mut bytes)?;
+
+    if (bytes.len() as u64) < len {
+        return Err(ImportError::Truncated);
+    }
+
+    Ok(Some(bytes))
+}
+
+/// An error that can occur when importing a stream with [`import`].
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("the stream ends in the middle of an entry")]
+    Truncated,
+}
+
+impl crate::error::StoreyError for ImportError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::storage::Storage as _;
+
+    use mocks::backend::TestStorage;
+
+    #[test]
+    fn framing() {
+        let mut storage = TestStorage::new();
+        storage.set(b"a", b"xyz");
+
+        let mut stream = Vec::new();
+        export(&storage, &mut stream).unwrap();
+        assert_eq!(stream, b"\x00\x00\x00\x01a\x00\x00\x00\x03xyz");
+    }
+
+    #[test]
+    fn truncated() {
+        let mut storage = TestStorage::new();
+        storage.set(b"a", b"xyz");
+        storage.set(b"b", b"w");
+
+        let mut stream = Vec::new();
+        export(&storage, &mut stream).unwrap();
+
+        for len in 1..stream.len() {
+            let mut restored = TestStorage::new();
+            let result = import(&mut restored, &stream[..len]);
+
+            if len == 12 {
+                assert_eq!(result.unwrap(), 1);
+            } else {
+                assert!(matches!(result, Err(ImportError::Truncated)), "len {len}");
+            }
+        }
+
+        let mut restored = TestStorage::new();
+        assert_eq!(import(&mut restored, &stream[..]).unwrap(), 2);
+        assert_eq!(restored.get(b"b"), Some(b"w".to_vec()));
+    }
+}
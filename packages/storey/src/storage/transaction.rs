@@ -0,0 +1,447 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use crate::storage::{FlushError, IterableStorage, RevIterableStorage, Storage, StorageMut};
+
+type StagedEntry = (Vec<u8>, Option<Vec<u8>>);
+type Staged = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// A storage wrapper that stages writes in memory until they're committed.
+///
+/// Reads see the merged view: staged writes shadow the backend, and staged removals hide
+/// backend entries. Nothing reaches the backend until [`commit`](Self::commit) is called.
+/// Dropping the transaction discards the staged writes.
+///
+/// # Iteration
+///
+/// Iterators reflect the merged view as it was when the iterator was created. Staged
+/// entries are inserted in order among the backend's entries, staged values replace
+/// backend values under the same key, and staged removals are skipped over. Since
+/// iterators borrow the transaction, nothing can be staged while one is alive.
+///
+/// Writes to the metadata namespace are staged as well and visible through
+/// [`get_meta`](Storage::get_meta), but they're not part of iteration.
+///
+/// # Example
+/// ```
+/// # use mocks::encoding::TestEncoding;
+/// # use mocks::backend::TestStorage;
+/// use storey::containers::{IterableAccessor as _, Item, Map};
+/// use storey::storage::Transaction;
+///
+/// let mut storage = TestStorage::new();
+/// let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+/// map.access(&mut storage).entry_mut("alice").set(&1).unwrap();
+/// map.access(&mut storage).entry_mut("bob").set(&2).unwrap();
+///
+/// let mut tx = Transaction::new(&mut storage);
+/// map.access(&mut tx).entry_mut("alice").remove();
+/// map.access(&mut tx).entry_mut("carol").set(&3).unwrap();
+///
+/// let keys = map.access(&tx).keys().collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(keys, [("bob".to_string(), ()), ("carol".to_string(), ())]);
+///
+/// tx.commit().unwrap();
+/// assert_eq!(map.access(&storage).entry("alice").get().unwrap(), None);
+/// assert_eq!(map.access(&storage).entry("carol").get().unwrap(), Some(3));
+/// ```
+pub struct Transaction<S> {
+    backend: S,
+    writes: Staged,
+    meta_writes: Staged,
+}
+
+impl<S> Transaction<S> {
+    /// Creates a new `Transaction` on top of the given backend.
+    pub fn new(backend: S) -> Self {
+        Self {
+            backend,
+            writes: BTreeMap::new(),
+            meta_writes: BTreeMap::new(),
+        }
+    }
+}
+
+impl<S: StorageMut> Transaction<&mut S> {
+    /// Applies the staged writes to the backend, then [flushes](StorageMut::flush) it.
+    pub fn commit(self) -> Result<(), FlushError> {
+        for (key, value) in self.writes {
+            match value {
+                Some(value) => self.backend.set(&key, &value),
+                None => self.backend.remove(&key),
+            }
+        }
+
+        for (key, value) in self.meta_writes {
+            match value {
+                Some(value) => self.backend.set_meta(&key, &value),
+                None => self.backend.remove_meta(&key),
+            }
+        }
+
+        self.backend.flush()
+    }
+}
+
+impl<S: Storage> Storage for Transaction<&mut S> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.writes.get(key) {
+            Some(staged) => staged.clone(),
+            None => self.backend.get(key),
+        }
+    }
+
+    fn has(&self, key: &[u8]) -> bool {
+        match self.writes.get(key) {
+            Some(staged) => staged.is_some(),
+            None => self.backend.has(key),
+        }
+    }
+
+    fn value_len(&self, key: &[u8]) -> Option<usize> {
+        match self.writes.get(key) {
+            Some(staged) => staged.as_ref().map(|value| value.len()),
+            None => self.backend.value_len(key),
+        }
+    }
+
+    fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.meta_writes.get(key) {
+            Some(staged) => staged.clone(),
+            None => self.backend.get_meta(key),
+        }
+    }
+
+    fn has_meta(&self, key: &[u8]) -> bool {
+        match self.meta_writes.get(key) {
+            Some(staged) => staged.is_some(),
+            None => self.backend.has_meta(key),
+        }
+    }
+}
+
+impl<S: Storage> StorageMut for Transaction<&mut S> {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes.insert(key.to_vec(), None);
+    }
+
+    fn set_meta(&mut self, key: &[u8], value: &[u8]) {
+        self.meta_writes.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn remove_meta(&mut self, key: &[u8]) {
+        self.meta_writes.insert(key.to_vec(), None);
+    }
+}
+
+impl<S: IterableStorage> IterableStorage for Transaction<&mut S> {
+    type KeysIterator<'a> = MergedKeys<S::PairsIterator<'a>> where Self: 'a;
+    type ValuesIterator<'a> = MergedValues<S::PairsIterator<'a>> where Self: 'a;
+    type PairsIterator<'a> = MergedPairs<S::PairsIterator<'a>> where Self: 'a;
+
+    fn keys<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::KeysIterator<'a> {
+        MergedKeys(self.pairs(start, end))
+    }
+
+    fn values<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::ValuesIterator<'a> {
+        MergedValues(self.pairs(start, end))
+    }
+
+    fn pairs<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::PairsIterator<'a> {
+        MergedPairs::new(
+            self.backend.pairs(start, end),
+            staged_range(&self.writes, start, end),
+            false,
+        )
+    }
+}
+
+impl<S: RevIterableStorage> RevIterableStorage for Transaction<&mut S> {
+    type RevKeysIterator<'a> = MergedKeys<S::RevPairsIterator<'a>> where Self: 'a;
+    type RevValuesIterator<'a> = MergedValues<S::RevPairsIterator<'a>> where Self: 'a;
+    type RevPairsIterator<'a> = MergedPairs<S::RevPairsIterator<'a>> where Self: 'a;
+
+    fn rev_keys<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::RevKeysIterator<'a> {
+        MergedKeys(self.rev_pairs(start, end))
+    }
+
+    fn rev_values<'a>(
+        &'a self,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+    ) -> Self::RevValuesIterator<'a> {
+        MergedValues(self.rev_pairs(start, end))
+    }
+
+    fn rev_pairs<'a>(
+        &'a self,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+    ) -> Self::RevPairsIterator<'a> {
+        let mut staged = staged_range(&self.writes, start, end);
+        staged.reverse();
+
+        MergedPairs::new(self.backend.rev_pairs(start, end), staged, true)
+    }
+}
+
+/// Returns the staged writes within the given bounds, in ascending order.
+fn staged_range(writes: &Staged, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Vec<StagedEntry> {
+    // `BTreeMap::range` panics on inverted or empty-excluded ranges, while storage
+    // iteration just yields nothing
+    if let (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) =
+        (start, end)
+    {
+        let both_included = matches!((start, end), (Bound::Included(_), Bound::Included(_)));
+        if s > e || (s == e && !both_included) {
+            return Vec::new();
+        }
+    }
+
+    writes
+        .range::<[u8], _>((start, end))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// The iterator over key-value pairs in a [`Transaction`], merging staged writes
+/// with the backend's entries.
+pub struct MergedPairs<I: Iterator> {
+    backend: std::iter::Peekable<I>,
+    staged: std::iter::Peekable<std::vec::IntoIter<StagedEntry>>,
+    rev: bool,
+}
+
+impl<I: Iterator> MergedPairs<I> {
+    fn new(backend: I, staged: Vec<StagedEntry>, rev: bool) -> Self {
+        Self {
+            backend: backend.peekable(),
+            staged: staged.into_iter().peekable(),
+            rev,
+        }
+    }
+}
+
+impl<I> Iterator for MergedPairs<I>
+where
+    I: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+{
+    type Item = (Vec<u8>, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let ordering = match (self.backend.peek(), self.staged.peek()) {
+                (None, None) => return None,
+                (Some(_), None) => return self.backend.next(),
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (Some((backend_key, _)), Some((staged_key, _))) => {
+                    let ordering = backend_key.cmp(staged_key);
+                    if self.rev {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                }
+            };
+
+            match ordering {
+                std::cmp::Ordering::Less => return self.backend.next(),
+                std::cmp::Ordering::Equal => {
+                    // the staged write shadows the backend entry
+                    self.backend.next();
+                }
+                std::cmp::Ordering::Greater => {}
+            }
+
+            let (key, value) = self.staged.next().expect("staged entry was peeked");
+            if let Some(value) = value {
+                return Some((key, value));
+            }
+        }
+    }
+}
+
+/// The iterator over keys in a [`Transaction`].
+pub struct MergedKeys<I: Iterator>(MergedPairs<I>);
+
+impl<I> Iterator for MergedKeys<I>
+where
+    I: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+{
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, _)| key)
+    }
+}
+
+/// The iterator over values in a [`Transaction`].
+pub struct MergedValues<I: Iterator>(MergedPairs<I>);
+
+impl<I> Iterator for MergedValues<I>
+where
+    I: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+{
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mocks::backend::TestStorage;
+
+    fn populated() -> TestStorage {
+        let mut storage = TestStorage::new();
+        storage.set(b"a", b"1");
+        storage.set(b"c", b"3");
+        storage.set(b"e", b"5");
+        storage
+    }
+
+    #[test]
+    fn reads_see_staged_writes() {
+        let mut storage = populated();
+
+        let mut tx = Transaction::new(&mut storage);
+        tx.set(b"b", b"2");
+        tx.remove(b"c");
+        tx.set_meta(b"meta", b"data");
+
+        assert_eq!(tx.get(b"a"), Some(b"1".to_vec()));
+        assert_eq!(tx.get(b"b"), Some(b"2".to_vec()));
+        assert_eq!(tx.get(b"c"), None);
+        assert!(!tx.has(b"c"));
+        assert_eq!(tx.get_meta(b"meta"), Some(b"data".to_vec()));
+
+        drop(tx);
+        assert_eq!(storage.get(b"b"), None);
+        assert_eq!(storage.get(b"c"), Some(b"3".to_vec()));
+        assert_eq!(storage.get_meta(b"meta"), None);
+    }
+
+    #[test]
+    fn merged_iteration() {
+        let mut storage = populated();
+
+        let mut tx = Transaction::new(&mut storage);
+        tx.set(b"b", b"2");
+        tx.remove(b"c");
+        tx.set(b"e", b"five");
+        tx.remove(b"f");
+        tx.set(b"g", b"7");
+
+        let pairs: Vec<_> = tx.pairs(Bound::Unbounded, Bound::Unbounded).collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (b"a".to_vec(), b"1".to_vec()),
+                (b"b".to_vec(), b"2".to_vec()),
+                (b"e".to_vec(), b"five".to_vec()),
+                (b"g".to_vec(), b"7".to_vec()),
+            ]
+        );
+
+        let rev_keys: Vec<_> = tx.rev_keys(Bound::Unbounded, Bound::Unbounded).collect();
+        assert_eq!(
+            rev_keys,
+            vec![b"g".to_vec(), b"e".to_vec(), b"b".to_vec(), b"a".to_vec()]
+        );
+
+        let values: Vec<_> = tx
+            .values(Bound::Excluded(b"a"), Bound::Included(b"e"))
+            .collect();
+        assert_eq!(values, vec![b"2".to_vec(), b"five".to_vec()]);
+
+        assert_eq!(
+            tx.keys(Bound::Included(b"e"), Bound::Excluded(b"b"))
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn commit() {
+        let mut storage = populated();
+
+        let mut tx = Transaction::new(&mut storage);
+        tx.set(b"b", b"2");
+        tx.remove(b"c");
+        tx.set_meta(b"meta", b"data");
+        tx.commit().unwrap();
+
+        let pairs: Vec<_> = storage
+            .pairs(Bound::Unbounded, Bound::Excluded(&[255]))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (b"a".to_vec(), b"1".to_vec()),
+                (b"b".to_vec(), b"2".to_vec()),
+                (b"e".to_vec(), b"5".to_vec()),
+            ]
+        );
+        assert_eq!(storage.get_meta(b"meta"), Some(b"data".to_vec()));
+    }
+
+    #[test]
+    fn commit_flushes_backend() {
+        #[derive(Default)]
+        struct Buffered {
+            storage: TestStorage,
+            buffer: Vec<(Vec<u8>, Vec<u8>)>,
+        }
+
+        impl Storage for Buffered {
+            fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+                self.storage.get(key)
+            }
+
+            fn get_meta(&self, key: &[u8]) -> Option<Vec<u8>> {
+                self.storage.get_meta(key)
+            }
+        }
+
+        impl StorageMut for Buffered {
+            fn set(&mut self, key: &[u8], value: &[u8]) {
+                self.buffer.push((key.to_vec(), value.to_vec()));
+            }
+
+            fn remove(&mut self, key: &[u8]) {
+                self.storage.remove(key);
+            }
+
+            fn set_meta(&mut self, key: &[u8], value: &[u8]) {
+                self.storage.set_meta(key, value);
+            }
+
+            fn remove_meta(&mut self, key: &[u8]) {
+                self.storage.remove_meta(key);
+            }
+
+            fn flush(&mut self) -> Result<(), FlushError> {
+                for (key, value) in self.buffer.drain(..) {
+                    self.storage.set(&key, &value);
+                }
+                Ok(())
+            }
+        }
+
+        let mut backend = Buffered::default();
+
+        let mut tx = Transaction::new(&mut backend);
+        tx.set(b"foo", b"bar");
+        tx.commit().unwrap();
+
+        assert!(backend.buffer.is_empty());
+        assert_eq!(backend.storage.get(b"foo"), Some(b"bar".to_vec()));
+    }
+}
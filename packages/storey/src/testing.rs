@@ -0,0 +1,204 @@
+//! Property-testing helpers for code built on `storey` containers.
+//!
+//! This module is only available with the `proptest` feature enabled.
+//!
+//! The idea is to generate random sequences of operations, apply them both to a container
+//! and to a simple in-memory model (a [`BTreeMap`] or an [`Option`]), and check that the two
+//! never disagree. The strategies and harnesses here can be dropped into your own
+//! [`proptest!`](proptest::proptest) blocks.
+//!
+//! # Example
+//! ```
+//! # use mocks::encoding::TestEncoding;
+//! # use mocks::backend::TestStorage;
+//! use proptest::prelude::*;
+//! use storey::containers::{Item, Map};
+//! use storey::testing::{check_map_model, map_ops};
+//!
+//! proptest! {
+//!     // In your own test suite, annotate this with `#[test]`.
+//!     fn map_behaves_like_btree_map(ops in map_ops(0..8u32, any::<u64>(), 0..64)) {
+//!         let map = Map::<u32, Item<u64, TestEncoding>>::new(0);
+//!         let mut storage = TestStorage::new();
+//!
+//!         check_map_model(&map, &mut storage, &ops)?;
+//!     }
+//! }
+//! # map_behaves_like_btree_map();
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use proptest::collection::{vec, SizeRange};
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+
+use crate::containers::map::{KeyEncodingT, OwnedKey};
+use crate::containers::{Item, IterableAccessor as _, Map, Storable};
+use crate::encoding::{DecodableWith, EncodableWith, Encoding};
+use crate::storage::{IterableStorage, Storage, StorageMut};
+
+/// A single operation on an [`Item`], as generated by [`item_ops`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemOp<V> {
+    /// Set the value.
+    Set(V),
+    /// Remove the value.
+    Remove,
+    /// Replace the value, but only if one is already present.
+    Update(V),
+}
+
+/// A single operation on a [`Map`], as generated by [`map_ops`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapOp<K, V> {
+    /// Set the value under the key.
+    Set(K, V),
+    /// Remove the value under the key.
+    Remove(K),
+    /// Replace the value under the key, but only if one is already present.
+    Update(K, V),
+}
+
+/// A strategy generating sequences of [`ItemOp`]s with values drawn from `values`.
+pub fn item_ops<V>(
+    values: impl Strategy<Value = V> + Clone,
+    len: impl Into<SizeRange>,
+) -> impl Strategy<Value = Vec<ItemOp<V>>>
+where
+    V: Debug + Clone,
+{
+    let op = prop_oneof![
+        values.clone().prop_map(ItemOp::Set),
+        Just(ItemOp::Remove),
+        values.prop_map(ItemOp::Update),
+    ];
+
+    vec(op, len)
+}
+
+/// A strategy generating sequences of [`MapOp`]s with keys drawn from `keys` and values
+/// drawn from `values`.
+///
+/// Keep the key space small (for example `0..8u32`) so that operations frequently hit
+/// keys that are already present.
+pub fn map_ops<K, V>(
+    keys: impl Strategy<Value = K> + Clone,
+    values: impl Strategy<Value = V> + Clone,
+    len: impl Into<SizeRange>,
+) -> impl Strategy<Value = Vec<MapOp<K, V>>>
+where
+    K: Debug + Clone,
+    V: Debug + Clone,
+{
+    let op = prop_oneof![
+        (keys.clone(), values.clone()).prop_map(|(k, v)| MapOp::Set(k, v)),
+        keys.clone().prop_map(MapOp::Remove),
+        (keys, values).prop_map(|(k, v)| MapOp::Update(k, v)),
+    ];
+
+    vec(op, len)
+}
+
+/// Applies `ops` to `item` and to an [`Option`] model, checking after every operation
+/// that the item holds the same value as the model.
+pub fn check_item_model<V, E, S>(
+    item: &Item<V, E>,
+    storage: &mut S,
+    ops: &[ItemOp<V>],
+) -> Result<(), TestCaseError>
+where
+    E: Encoding,
+    E::DecodeError: Debug,
+    E::EncodeError: Debug,
+    V: EncodableWith<E> + DecodableWith<E> + Debug + Clone + PartialEq,
+    S: Storage + StorageMut,
+{
+    let mut model = None;
+
+    for op in ops {
+        let mut access = item.access(&mut *storage);
+
+        match op {
+            ItemOp::Set(value) => {
+                access.set(value).unwrap();
+                model = Some(value.clone());
+            }
+            ItemOp::Remove => {
+                access.remove();
+                model = None;
+            }
+            ItemOp::Update(value) => {
+                access.update(|old| old.map(|_| value.clone())).unwrap();
+                model = model.map(|_| value.clone());
+            }
+        }
+
+        prop_assert_eq!(item.access(&*storage).get().unwrap(), model.clone());
+    }
+
+    Ok(())
+}
+
+/// Applies `ops` to `map` and to a [`BTreeMap`] model, checking after every operation
+/// that the touched entry matches the model, and at the end that iterating the map
+/// yields exactly the model's contents.
+pub fn check_map_model<K, V, E, S>(
+    map: &Map<K, Item<V, E>>,
+    storage: &mut S,
+    ops: &[MapOp<K, V>],
+) -> Result<(), TestCaseError>
+where
+    K: OwnedKey + Ord + Debug + Clone,
+    (K::Kind, <Item<V, E> as Storable>::Kind): KeyEncodingT,
+    E: Encoding,
+    E::DecodeError: Debug + std::fmt::Display,
+    E::EncodeError: Debug,
+    V: EncodableWith<E> + DecodableWith<E> + Debug + Clone + PartialEq,
+    S: Storage + IterableStorage + StorageMut,
+{
+    let mut model = BTreeMap::new();
+
+    for op in ops {
+        let mut access = map.access(&mut *storage);
+
+        let key = match op {
+            MapOp::Set(key, value) => {
+                access.entry_mut(key).set(value).unwrap();
+                model.insert(key.clone(), value.clone());
+                key
+            }
+            MapOp::Remove(key) => {
+                access.entry_mut(key).remove();
+                model.remove(key);
+                key
+            }
+            MapOp::Update(key, value) => {
+                access
+                    .entry_mut(key)
+                    .update(|old| old.map(|_| value.clone()))
+                    .unwrap();
+                if let Some(old) = model.get_mut(key) {
+                    *old = value.clone();
+                }
+                key
+            }
+        };
+
+        prop_assert_eq!(
+            map.access(&*storage).entry(key).get().unwrap(),
+            model.get(key).cloned()
+        );
+    }
+
+    let contents = map
+        .access(&*storage)
+        .pairs()
+        .map(|pair| pair.map(|((key, ()), value)| (key, value)))
+        .collect::<Result<BTreeMap<_, _>, _>>()
+        .unwrap();
+    prop_assert_eq!(contents, model);
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use storey::containers::{Column, Item, IterableAccessor as _, Map};
+use storey::storage::StorageBranch;
 
-use mocks::backend::TestStorage;
+use mocks::backend::{HashMapStorage, TestStorage};
 use mocks::encoding::TestEncoding;
 use storey_storage::Storage as _;
 
@@ -67,3 +68,71 @@ fn map_of_column() {
         ]
     );
 }
+
+#[test]
+fn map_of_column_unordered_backend() {
+    // Point lookups and pushes work without an iterable backend.
+    let mut storage = HashMapStorage::new();
+
+    let map = Map::<String, Column<u64, TestEncoding>>::new(0);
+    let mut access = map.access(&mut storage);
+
+    access.entry_mut("foo").push(&1337).unwrap();
+    access.entry_mut("foo").push(&42).unwrap();
+
+    assert_eq!(access.entry("foo").get(2).unwrap(), Some(42));
+    assert_eq!(access.entry("foo").len().unwrap(), 2);
+    assert_eq!(access.entry("bar").get(1).unwrap(), None);
+}
+
+#[test]
+fn column_at_branch() {
+    let mut storage = TestStorage::new();
+
+    let column = Column::<u64, TestEncoding>::new(0);
+
+    let epoch_1 = StorageBranch::new(&mut storage, vec![1]);
+    column.access_at(epoch_1).push(&1337).unwrap();
+    let epoch_2 = StorageBranch::new(&mut storage, vec![2]);
+    column.access_at(epoch_2).push(&42).unwrap();
+
+    let epoch_1 = StorageBranch::new(&storage, vec![1]);
+    assert_eq!(
+        column
+            .access_at(epoch_1)
+            .pairs()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        vec![(1, 1337)]
+    );
+    assert_eq!(
+        storage.get(&[2, 0, 0, 0, 0, 1]),
+        Some(42u64.to_le_bytes().to_vec())
+    );
+    assert_eq!(column.access(&storage).len().unwrap(), 0);
+}
+
+#[test]
+fn item_of_tuple() {
+    let mut storage = TestStorage::new();
+
+    let item = Item::<(u32, String), TestEncoding>::new(0);
+
+    item.access(&mut storage)
+        .set(&(42, "foo".to_string()))
+        .unwrap();
+    assert_eq!(
+        item.access(&storage).get().unwrap(),
+        Some((42, "foo".to_string()))
+    );
+}
+
+#[test]
+fn item_of_array() {
+    let mut storage = TestStorage::new();
+
+    let item = Item::<[u8; 4], TestEncoding>::new(0);
+
+    item.access(&mut storage).set(&[1, 2, 3, 4]).unwrap();
+    assert_eq!(item.access(&storage).get().unwrap(), Some([1, 2, 3, 4]));
+}
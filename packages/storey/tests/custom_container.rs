@@ -0,0 +1,199 @@
+//! A container implemented outside of `storey`, using only its public API.
+
+use std::marker::PhantomData;
+
+use storey::containers::{IterableAccessor, Map, NonTerminal, Storable};
+use storey::encoding::{DecodableWith, EncodableWith, Encoding};
+use storey::storage::{IterableStorage, Storage, StorageBranch, StorageMut};
+
+use mocks::backend::TestStorage;
+use mocks::encoding::TestEncoding;
+
+const META_PUSHED: &[u8] = &[0];
+
+/// A fixed-capacity buffer that overwrites its oldest entry once full.
+///
+/// Entries live under their slot index (big-endian `u32`). The total number of pushes is
+/// kept in the metadata namespace.
+struct RingBuffer<T, E, const N: u32> {
+    prefix: u8,
+    phantom: PhantomData<(T, E)>,
+}
+
+impl<T, E, const N: u32> RingBuffer<T, E, N>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+{
+    const fn new(prefix: u8) -> Self {
+        Self {
+            prefix,
+            phantom: PhantomData,
+        }
+    }
+
+    fn access<S>(&self, storage: S) -> RingBufferAccess<T, E, N, StorageBranch<S>> {
+        Self::access_impl(StorageBranch::new(storage, vec![self.prefix]))
+    }
+}
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+#[error("invalid slot key")]
+struct InvalidSlot;
+
+impl<T, E, const N: u32> Storable for RingBuffer<T, E, N>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+{
+    type Kind = NonTerminal;
+    type Accessor<S> = RingBufferAccess<T, E, N, S>;
+    type Key = u32;
+    type KeyDecodeError = InvalidSlot;
+    type Value = T;
+    type ValueDecodeError = E::DecodeError;
+
+    fn access_impl<S>(storage: S) -> Self::Accessor<S> {
+        RingBufferAccess {
+            storage,
+            phantom: PhantomData,
+        }
+    }
+
+    fn decode_key(key: &[u8]) -> Result<u32, InvalidSlot> {
+        let bytes = key.try_into().map_err(|_| InvalidSlot)?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    fn decode_value(value: &[u8]) -> Result<T, E::DecodeError> {
+        T::decode(value)
+    }
+
+    fn clear_impl<S>(storage: &mut S)
+    where
+        S: IterableStorage + StorageMut,
+    {
+        for slot in 0..N {
+            storage.remove(&slot.to_be_bytes());
+        }
+        storage.remove_meta(META_PUSHED);
+    }
+}
+
+struct RingBufferAccess<T, E, const N: u32, S> {
+    storage: S,
+    phantom: PhantomData<(T, E)>,
+}
+
+impl<T, E, const N: u32, S> RingBufferAccess<T, E, N, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage,
+{
+    fn pushed(&self) -> u32 {
+        self.storage
+            .get_meta(META_PUSHED)
+            .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+            .unwrap_or(0)
+    }
+
+    fn len(&self) -> u32 {
+        self.pushed().min(N)
+    }
+
+    /// Gets the `i`-th oldest entry.
+    fn get(&self, i: u32) -> Result<Option<T>, E::DecodeError> {
+        if i >= self.len() {
+            return Ok(None);
+        }
+
+        let slot = (self.pushed() - self.len() + i) % N;
+        self.storage
+            .get(&slot.to_be_bytes())
+            .map(|bytes| T::decode(&bytes))
+            .transpose()
+    }
+}
+
+impl<T, E, const N: u32, S> RingBufferAccess<T, E, N, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: Storage + StorageMut,
+{
+    fn push(&mut self, value: &T) -> Result<(), E::EncodeError> {
+        let pushed = self.pushed();
+
+        self.storage
+            .set(&(pushed % N).to_be_bytes(), &value.encode()?);
+        self.storage
+            .set_meta(META_PUSHED, &(pushed + 1).to_be_bytes());
+        Ok(())
+    }
+}
+
+impl<T, E, const N: u32, S> IterableAccessor for RingBufferAccess<T, E, N, S>
+where
+    E: Encoding,
+    T: EncodableWith<E> + DecodableWith<E>,
+    S: IterableStorage,
+{
+    type Storable = RingBuffer<T, E, N>;
+    type Storage = S;
+
+    fn storage(&self) -> &S {
+        &self.storage
+    }
+}
+
+#[test]
+fn ring_buffer_wraps_around() {
+    let mut storage = TestStorage::new();
+
+    let buffer = RingBuffer::<u64, TestEncoding, 3>::new(0);
+    let mut access = buffer.access(&mut storage);
+
+    for value in 1..=5 {
+        access.push(&value).unwrap();
+    }
+
+    assert_eq!(access.len(), 3);
+    assert_eq!(access.get(0).unwrap(), Some(3));
+    assert_eq!(access.get(2).unwrap(), Some(5));
+    assert_eq!(access.get(3).unwrap(), None);
+
+    // iteration goes in slot order
+    let pairs = access.pairs().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(pairs, vec![(0, 4), (1, 5), (2, 3)]);
+}
+
+#[test]
+fn ring_buffer_in_map() {
+    let mut storage = TestStorage::new();
+
+    let map = Map::<String, RingBuffer<u64, TestEncoding, 2>>::new(0);
+    let mut access = map.access(&mut storage);
+
+    access.entry_mut("amy").push(&1).unwrap();
+    access.entry_mut("amy").push(&2).unwrap();
+    access.entry_mut("amy").push(&3).unwrap();
+    access.entry_mut("bob").push(&10).unwrap();
+
+    assert_eq!(access.entry("amy").get(0).unwrap(), Some(2));
+    assert_eq!(access.entry("bob").len(), 1);
+
+    let keys = access.keys().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(
+        keys,
+        vec![
+            ("amy".to_string(), 0),
+            ("amy".to_string(), 1),
+            ("bob".to_string(), 0)
+        ]
+    );
+
+    access.clear();
+    assert_eq!(access.entry("amy").len(), 0);
+    assert_eq!(access.keys().count(), 0);
+}
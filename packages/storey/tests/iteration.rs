@@ -1,6 +1,7 @@
 use std::ops::Bound;
 
 use storey::containers::{BoundedIterableAccessor, Item, IterableAccessor as _, Map};
+use storey::storage::{IterableStorage as _, StorageBranch};
 
 use mocks::backend::TestStorage;
 use mocks::encoding::TestEncoding;
@@ -47,6 +48,33 @@ fn map_of_map_iteration() {
     );
 }
 
+#[test]
+fn map_of_map_scoped_numeric_iteration() {
+    let mut storage = TestStorage::new();
+
+    let map = Map::<String, Map<u64, Item<u64, TestEncoding>>>::new(0);
+    let mut access = map.access(&mut storage);
+
+    access.entry_mut("alice").entry_mut(&300).set(&1).unwrap();
+    access.entry_mut("alice").entry_mut(&2).set(&2).unwrap();
+    access.entry_mut("alice").entry_mut(&1000).set(&3).unwrap();
+    access.entry_mut("bob").entry_mut(&1).set(&4).unwrap();
+
+    // scoping by the outer key leaves only the typed `u64` sub-keys, in numeric order
+    let alice = access.entry("alice");
+    assert_eq!(
+        alice.keys().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![(2, ()), (300, ()), (1000, ())]
+    );
+    assert_eq!(
+        alice
+            .bounded_pairs(Bound::Included(&3), Bound::Unbounded)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        vec![((300, ()), 1), ((1000, ()), 3)]
+    );
+}
+
 #[test]
 fn map_of_map_bounded_iteration() {
     let mut storage = TestStorage::new();
@@ -120,3 +148,285 @@ fn map_of_map_bounded_iteration() {
         ]
     );
 }
+
+#[test]
+fn iteration_errors_compose_with_question_mark() -> Result<(), Box<dyn std::error::Error>> {
+    let mut storage = TestStorage::new();
+
+    let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+    let mut access = map.access(&mut storage);
+
+    access.entry_mut("foo").set(&1337).unwrap();
+    access.entry_mut("bar").set(&42).unwrap();
+
+    let items = access.pairs().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        items,
+        vec![
+            (("bar".to_string(), ()), 42),
+            (("foo".to_string(), ()), 1337)
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn transaction_iteration_interleaved_with_staged_writes() {
+    use storey::containers::RevIterableAccessor as _;
+    use storey::storage::Transaction;
+
+    let mut storage = TestStorage::new();
+
+    let map = Map::<String, Map<String, Item<u64, TestEncoding>>>::new(0);
+    let mut access = map.access(&mut storage);
+    access.entry_mut("foo").entry_mut("bar").set(&1).unwrap();
+    access.entry_mut("foo").entry_mut("baz").set(&2).unwrap();
+    access.entry_mut("qux").entry_mut("quux").set(&3).unwrap();
+
+    let mut tx = Transaction::new(&mut storage);
+
+    map.access(&mut tx)
+        .entry_mut("foo")
+        .entry_mut("bar")
+        .remove();
+    map.access(&mut tx)
+        .entry_mut("foo")
+        .entry_mut("bax")
+        .set(&4)
+        .unwrap();
+    let pairs = map
+        .access(&tx)
+        .entry("foo")
+        .pairs()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        pairs,
+        vec![(("bax".to_string(), ()), 4), (("baz".to_string(), ()), 2)]
+    );
+
+    // re-inserting a removed key and overwriting a backend entry
+    map.access(&mut tx)
+        .entry_mut("foo")
+        .entry_mut("bar")
+        .set(&5)
+        .unwrap();
+    map.access(&mut tx)
+        .entry_mut("qux")
+        .entry_mut("quux")
+        .set(&6)
+        .unwrap();
+    let values = map
+        .access(&tx)
+        .values()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(values, vec![5, 4, 2, 6]);
+
+    map.access(&mut tx)
+        .entry_mut("qux")
+        .entry_mut("quux")
+        .remove();
+    let keys = map
+        .access(&tx)
+        .rev_keys()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        keys,
+        vec![
+            ("foo".to_string(), ("baz".to_string(), ())),
+            ("foo".to_string(), ("bax".to_string(), ())),
+            ("foo".to_string(), ("bar".to_string(), ())),
+        ]
+    );
+
+    tx.commit().unwrap();
+    let values = map
+        .access(&storage)
+        .values()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(values, vec![5, 4, 2]);
+}
+
+#[test]
+fn column_metadata_is_never_iterated() {
+    use std::collections::BTreeSet;
+
+    use storey::containers::{Column, RevIterableAccessor as _};
+
+    let mut storage = TestStorage::new();
+
+    let column = Column::<u64, TestEncoding>::new(0);
+    let map = Map::<String, Column<u64, TestEncoding>>::new(1);
+
+    let mut access = column.access(&mut storage);
+    for value in 10..15 {
+        access.push(&value).unwrap();
+    }
+    access.remove(5).unwrap();
+
+    let mut access = map.access(&mut storage);
+    for value in 20..23 {
+        access.entry_mut("foo").push(&value).unwrap();
+    }
+    access.entry_mut("bar").push(&30).unwrap();
+    access.entry_mut("bar").remove(1).unwrap();
+
+    // the counters exist...
+    assert_eq!(column.access(&storage).len().unwrap(), 4);
+    assert_eq!(map.access(&storage).entry("foo").len().unwrap(), 3);
+
+    // ...but only elements are iterated
+    let access = column.access(&storage);
+    assert_eq!(
+        access.keys().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![1, 2, 3, 4]
+    );
+    assert_eq!(
+        access.rev_values().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![13, 12, 11, 10]
+    );
+
+    let access = map.access(&storage);
+    assert_eq!(
+        access.pairs().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![
+            (("foo".to_string(), 1), 20),
+            (("foo".to_string(), 2), 21),
+            (("foo".to_string(), 3), 22),
+        ]
+    );
+    assert_eq!(access.entry("bar").keys().count(), 0);
+
+    // the raw namespaces hold nothing but elements
+    let raw_keys: BTreeSet<_> = [0u8, 1]
+        .into_iter()
+        .flat_map(|prefix| {
+            StorageBranch::new(&storage, vec![prefix])
+                .keys(Bound::Unbounded, Bound::Unbounded)
+                .map(move |key| (prefix, key))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(raw_keys.len(), 4 + 3);
+}
+
+/// A fixed permutation of `values`, so that insertion order never matches the sorted order.
+fn shuffled<T: Clone>(values: &[T]) -> Vec<T> {
+    let mut shuffled = values.to_vec();
+    let mut state = 0x2545_f491_u64;
+    for i in (1..shuffled.len()).rev() {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        shuffled.swap(i, (state >> 33) as usize % (i + 1));
+    }
+    shuffled
+}
+
+#[test]
+fn integer_keys_iterate_in_numeric_order() {
+    macro_rules! check {
+        ($t:ty: $($key:expr),*) => {{
+            let sorted: Vec<$t> = vec![$($key),*];
+
+            let mut storage = TestStorage::new();
+            let map = Map::<$t, Item<u8, TestEncoding>>::new(0);
+            let mut access = map.access(&mut storage);
+
+            for key in shuffled(&sorted) {
+                access.entry_mut(&key).set(&0).unwrap();
+            }
+
+            let keys = access.keys().map(|r| r.unwrap().0).collect::<Vec<_>>();
+            assert_eq!(keys, sorted);
+
+            // range queries rely on the same order
+            let mid = sorted.len() / 2;
+            let upper = access
+                .bounded_keys(Bound::Included(&sorted[mid]), Bound::Unbounded)
+                .map(|r| r.unwrap().0)
+                .collect::<Vec<_>>();
+            assert_eq!(upper, &sorted[mid..]);
+        }};
+    }
+
+    check!(u8: 0, 1, 2, 127, 128, 255);
+    check!(u16: 0, 1, 255, 256, 257, 65535);
+    check!(u32: 0, 1, 255, 256, 65536, 16777216, u32::MAX);
+    check!(u64: 0, 1, 256, 1 << 32, 1 << 56, u64::MAX);
+    check!(u128: 0, 1, 256, 1 << 64, u128::MAX);
+    check!(i8: i8::MIN, -127, -1, 0, 1, i8::MAX);
+    check!(i32: i32::MIN, -65536, -256, -1, 0, 1, 256, 65536, i32::MAX);
+    check!(i64: i64::MIN, -(1 << 40), -1, 0, 1, 1 << 40, i64::MAX);
+}
+
+#[test]
+fn composite_keys_iterate_in_tuple_order() {
+    let mut storage = TestStorage::new();
+
+    let map = Map::<u64, Map<i32, Item<u8, TestEncoding>>>::new(0);
+    let mut access = map.access(&mut storage);
+
+    let mut keys = Vec::new();
+    for outer in [0u64, 1, 255, 256, 1 << 40] {
+        for inner in [i32::MIN, -256, -1, 0, 1, 256, i32::MAX] {
+            keys.push((outer, inner));
+        }
+    }
+
+    for (outer, inner) in shuffled(&keys) {
+        access.entry_mut(&outer).entry_mut(&inner).set(&0).unwrap();
+    }
+
+    let iterated = access
+        .keys()
+        .map(|r| r.unwrap())
+        .map(|(outer, (inner, ()))| (outer, inner))
+        .collect::<Vec<_>>();
+    assert_eq!(iterated, keys);
+
+    let inner = access
+        .entry(&256)
+        .bounded_keys(Bound::Excluded(&-1), Bound::Included(&256))
+        .map(|r| r.unwrap().0)
+        .collect::<Vec<_>>();
+    assert_eq!(inner, vec![0, 1, 256]);
+}
+
+#[test]
+fn length_prefixed_keys_keep_entries_grouped() {
+    let mut storage = TestStorage::new();
+
+    // outer keys are length-prefixed, so "b" sorts before "aa"
+    let map = Map::<String, Map<u32, Item<u8, TestEncoding>>>::new(0);
+    let mut access = map.access(&mut storage);
+
+    let keys = [
+        ("b", 1),
+        ("b", 256),
+        ("aa", 1),
+        ("aa", 2),
+        ("ab", 0),
+        ("ab", u32::MAX),
+    ];
+
+    for (outer, inner) in shuffled(&keys) {
+        access.entry_mut(outer).entry_mut(&inner).set(&0).unwrap();
+    }
+
+    let iterated = access
+        .keys()
+        .map(|r| r.unwrap())
+        .map(|(outer, (inner, ()))| (outer, inner))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        iterated,
+        keys.iter()
+            .map(|(outer, inner)| (outer.to_string(), *inner))
+            .collect::<Vec<_>>()
+    );
+}
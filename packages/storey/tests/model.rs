@@ -0,0 +1,23 @@
+use mocks::backend::TestStorage;
+use mocks::encoding::TestEncoding;
+use proptest::prelude::*;
+use storey::containers::{Item, Map};
+use storey::testing::{check_item_model, check_map_model, item_ops, map_ops};
+
+proptest! {
+    #[test]
+    fn item_matches_model(ops in item_ops(any::<u64>(), 0..32)) {
+        let item = Item::<u64, TestEncoding>::new(0);
+        let mut storage = TestStorage::new();
+
+        check_item_model(&item, &mut storage, &ops)?;
+    }
+
+    #[test]
+    fn map_matches_model(ops in map_ops("[a-c]{0,2}", any::<u64>(), 0..64)) {
+        let map = Map::<String, Item<u64, TestEncoding>>::new(0);
+        let mut storage = TestStorage::new();
+
+        check_map_model(&map, &mut storage, &ops)?;
+    }
+}